@@ -0,0 +1,284 @@
+#[cfg(test)]
+mod tests;
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// The allyabase services a client can be pointed at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Service {
+    Addie,
+    Aretha,
+    Bdo,
+    Continuebee,
+    Covenant,
+    Dolores,
+    Fount,
+    Joan,
+    Julia,
+    Minnie,
+    Pref,
+    Prof,
+    Sanora
+}
+
+pub const ALL_SERVICES: [Service; 13] = [
+    Service::Addie, Service::Aretha, Service::Bdo, Service::Continuebee, Service::Covenant, Service::Dolores, Service::Fount,
+    Service::Joan, Service::Julia, Service::Minnie, Service::Pref, Service::Prof, Service::Sanora
+];
+
+impl Service {
+    /// The name used in nginx paths, subdomains and environment variables.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Service::Addie => "addie",
+            Service::Aretha => "aretha",
+            Service::Bdo => "bdo",
+            Service::Continuebee => "continuebee",
+            Service::Covenant => "covenant",
+            Service::Dolores => "dolores",
+            Service::Fount => "fount",
+            Service::Joan => "joan",
+            Service::Julia => "julia",
+            Service::Minnie => "minnie",
+            Service::Pref => "pref",
+            Service::Prof => "prof",
+            Service::Sanora => "sanora"
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        ALL_SERVICES.into_iter().find(|service| service.name() == name)
+    }
+
+    /// The `directPort` for this service in config/test-config.js.
+    pub fn direct_port(&self) -> u16 {
+        match self {
+            Service::Addie => 3005,
+            Service::Aretha => 7277,
+            Service::Bdo => 3003,
+            Service::Continuebee => 2999,
+            Service::Covenant => 3011,
+            Service::Dolores => 3007,
+            Service::Fount => 3006,
+            Service::Joan => 3004,
+            Service::Julia => 3000,
+            Service::Minnie => 2525,
+            Service::Pref => 3002,
+            Service::Prof => 3008,
+            Service::Sanora => 7243
+        }
+    }
+}
+
+/// How a node exposes its services.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Routing {
+    /// Behind one host, e.g. `http://nginx:80/fount/` (nginx-path-based-test.conf).
+    Path { base_url: String },
+    /// One host per service, e.g. `https://dev.fount.allyabase.com/`.
+    Subdomain { subdomain: String, domain: String },
+    /// Straight to each service's port, e.g. `http://localhost:3006/`.
+    Ports { host: String, ports: BTreeMap<Service, u16> }
+}
+
+#[derive(Debug)]
+pub enum DirectoryError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    UnknownService(String),
+    UnknownRouting(String),
+    Missing(&'static str)
+}
+
+impl fmt::Display for DirectoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DirectoryError::Io(error) => write!(f, "could not read the service directory: {}", error),
+            DirectoryError::Toml(error) => write!(f, "could not parse the service directory: {}", error),
+            DirectoryError::UnknownService(name) => write!(f, "unknown service {}", name),
+            DirectoryError::UnknownRouting(routing) => write!(f, "unknown routing {}, expected path, subdomain or ports", routing),
+            DirectoryError::Missing(key) => write!(f, "the service directory needs {}", key)
+        }
+    }
+}
+
+impl std::error::Error for DirectoryError {}
+
+impl From<std::io::Error> for DirectoryError {
+    fn from(error: std::io::Error) -> Self {
+        DirectoryError::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for DirectoryError {
+    fn from(error: toml::de::Error) -> Self {
+        DirectoryError::Toml(error)
+    }
+}
+
+/// The TOML layout `ServiceDirectory::from_toml` reads:
+///
+/// ```toml
+/// routing = "ports"          # "path", "subdomain" or "ports"
+/// base_url = "http://nginx:80"
+/// subdomain = "dev"
+/// domain = "allyabase.com"
+/// host = "localhost"
+///
+/// [ports]
+/// pref = 3001
+///
+/// [services]
+/// fount = "http://127.0.0.1:3006/"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+struct DirectoryFile {
+    routing: Option<String>,
+    base_url: Option<String>,
+    subdomain: Option<String>,
+    domain: Option<String>,
+    host: Option<String>,
+    #[serde(default)]
+    ports: BTreeMap<String, u16>,
+    #[serde(default)]
+    services: BTreeMap<String, String>
+}
+
+/// Where each service of an allyabase node lives. Clients take one through
+/// their `with_directory` constructors so the same code can run against a
+/// local node, the Docker environment or dev.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServiceDirectory {
+    pub routing: Routing,
+    overrides: BTreeMap<Service, String>
+}
+
+impl ServiceDirectory {
+    pub fn new(routing: Routing) -> Self {
+        ServiceDirectory { routing, overrides: BTreeMap::new() }
+    }
+
+    pub fn path_based(base_url: &str) -> Self {
+        Self::new(Routing::Path { base_url: base_url.to_string() })
+    }
+
+    pub fn subdomain(subdomain: &str) -> Self {
+        Self::new(Routing::Subdomain { subdomain: subdomain.to_string(), domain: "allyabase.com".to_string() })
+    }
+
+    pub fn direct_ports(host: &str) -> Self {
+        let ports = ALL_SERVICES.into_iter().map(|service| (service, service.direct_port())).collect();
+        Self::new(Routing::Ports { host: host.to_string(), ports })
+    }
+
+    /// The dev node the clients default to.
+    pub fn dev() -> Self {
+        Self::subdomain("dev")
+    }
+
+    /// Sends one service somewhere else, whatever the routing says.
+    pub fn with_url(mut self, service: Service, url: &str) -> Self {
+        self.overrides.insert(service, with_slash(url));
+        self
+    }
+
+    pub fn with_port(mut self, service: Service, port: u16) -> Self {
+        if let Routing::Ports { ports, .. } = &mut self.routing {
+            ports.insert(service, port);
+        }
+        self
+    }
+
+    /// The base URL for `service`, always ending in `/` like the clients expect.
+    pub fn url(&self, service: Service) -> String {
+        if let Some(url) = self.overrides.get(&service) {
+            return url.clone();
+        }
+
+        match &self.routing {
+            Routing::Path { base_url } => format!("{}{}/", with_slash(base_url), service.name()),
+            Routing::Subdomain { subdomain, domain } if subdomain.is_empty() => format!("https://{}.{}/", service.name(), domain),
+            Routing::Subdomain { subdomain, domain } => format!("https://{}.{}.{}/", subdomain, service.name(), domain),
+            Routing::Ports { host, ports } => {
+                let port = ports.get(&service).copied().unwrap_or(service.direct_port());
+                format!("http://{}:{}/", host, port)
+            }
+        }
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, DirectoryError> {
+        let file: DirectoryFile = toml::from_str(contents)?;
+
+        let mut directory = match file.routing.as_deref().unwrap_or("path") {
+            "path" => Self::path_based(file.base_url.as_deref().ok_or(DirectoryError::Missing("base_url"))?),
+            "subdomain" => Self::new(Routing::Subdomain {
+                subdomain: file.subdomain.unwrap_or_default(),
+                domain: file.domain.unwrap_or("allyabase.com".to_string())
+            }),
+            "ports" => Self::direct_ports(file.host.as_deref().unwrap_or("localhost")),
+            routing => return Err(DirectoryError::UnknownRouting(routing.to_string()))
+        };
+
+        for (name, port) in file.ports {
+            let service = Service::parse(&name).ok_or(DirectoryError::UnknownService(name))?;
+            directory = directory.with_port(service, port);
+        }
+        for (name, url) in file.services {
+            let service = Service::parse(&name).ok_or(DirectoryError::UnknownService(name))?;
+            directory = directory.with_url(service, &url);
+        }
+
+        Ok(directory)
+    }
+
+    pub fn from_toml_file(path: impl AsRef<Path>) -> Result<Self, DirectoryError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Reads the directory from the environment, falling back to `default`
+    /// when nothing there picks a node. In order:
+    ///
+    /// - `SHARON_CONFIG`: a TOML file, see `from_toml`
+    /// - `USE_DIRECT_PORTS=true` or `NODE_ENV=local`: direct ports on
+    ///   `ALLYABASE_HOST` (default `localhost`), as config/test-config.js does
+    /// - `SUB_DOMAIN`: `https://$SUB_DOMAIN.<service>.allyabase.com/`, as the
+    ///   MAGIC spell tests do
+    /// - `ALLYABASE_BASE_URL`: path-based routing under that URL
+    ///
+    /// `ALLYABASE_<SERVICE>_URL`, e.g. `ALLYABASE_FOUNT_URL`, then overrides
+    /// single services.
+    pub fn from_env_or(default: Self) -> Result<Self, DirectoryError> {
+        Self::from_vars(|key| std::env::var(key).ok(), default)
+    }
+
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>, default: Self) -> Result<Self, DirectoryError> {
+        let direct_ports = var("USE_DIRECT_PORTS").as_deref() == Some("true") || var("NODE_ENV").as_deref() == Some("local");
+
+        let mut directory = if let Some(path) = var("SHARON_CONFIG") {
+            Self::from_toml_file(path)?
+        } else if direct_ports {
+            Self::direct_ports(var("ALLYABASE_HOST").as_deref().unwrap_or("localhost"))
+        } else if let Some(subdomain) = var("SUB_DOMAIN") {
+            Self::subdomain(&subdomain)
+        } else if let Some(base_url) = var("ALLYABASE_BASE_URL") {
+            Self::path_based(&base_url)
+        } else {
+            default
+        };
+
+        for service in ALL_SERVICES {
+            if let Some(url) = var(&format!("ALLYABASE_{}_URL", service.name().to_uppercase())) {
+                directory = directory.with_url(service, &url);
+            }
+        }
+
+        Ok(directory)
+    }
+}
+
+fn with_slash(url: &str) -> String {
+    if url.ends_with('/') { url.to_string() } else { format!("{}/", url) }
+}
@@ -0,0 +1,67 @@
+use crate::{DirectoryError, Routing, Service, ServiceDirectory};
+use std::collections::HashMap;
+
+fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let map: HashMap<String, String> = pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+    move |key| map.get(key).cloned()
+}
+
+#[test]
+fn test_routing() {
+    assert_eq!(ServiceDirectory::path_based("http://nginx:80").url(Service::Fount), "http://nginx:80/fount/");
+    assert_eq!(ServiceDirectory::path_based("http://localhost:8080/").url(Service::Bdo), "http://localhost:8080/bdo/");
+    assert_eq!(ServiceDirectory::dev().url(Service::Bdo), "https://dev.bdo.allyabase.com/");
+    assert_eq!(ServiceDirectory::subdomain("").url(Service::Joan), "https://joan.allyabase.com/");
+    assert_eq!(ServiceDirectory::direct_ports("localhost").url(Service::Sanora), "http://localhost:7243/");
+
+    let directory = ServiceDirectory::direct_ports("127.0.0.1")
+        .with_port(Service::Pref, 3001)
+        .with_url(Service::Fount, "http://fount.internal:3002");
+    assert_eq!(directory.url(Service::Pref), "http://127.0.0.1:3001/");
+    assert_eq!(directory.url(Service::Fount), "http://fount.internal:3002/");
+    assert_eq!(directory.url(Service::Covenant), "http://127.0.0.1:3011/");
+}
+
+#[test]
+fn test_from_vars() {
+    let docker = ServiceDirectory::path_based("http://nginx:80");
+
+    assert_eq!(ServiceDirectory::from_vars(vars(&[]), docker.clone()).expect("default"), docker);
+
+    let local = ServiceDirectory::from_vars(vars(&[("NODE_ENV", "local")]), docker.clone()).expect("local");
+    assert_eq!(local.url(Service::Fount), "http://localhost:3006/");
+    let local = ServiceDirectory::from_vars(vars(&[("USE_DIRECT_PORTS", "true"), ("ALLYABASE_HOST", "10.0.0.2")]), docker.clone()).expect("ports");
+    assert_eq!(local.url(Service::Dolores), "http://10.0.0.2:3007/");
+
+    let dev = ServiceDirectory::from_vars(vars(&[("SUB_DOMAIN", "hitchhikers")]), docker.clone()).expect("subdomain");
+    assert_eq!(dev.url(Service::Fount), "https://hitchhikers.fount.allyabase.com/");
+
+    let proxied = ServiceDirectory::from_vars(vars(&[("ALLYABASE_BASE_URL", "http://localhost:8080"), ("ALLYABASE_FOUNT_URL", "http://localhost:3006")]), docker).expect("base url");
+    assert_eq!(proxied.url(Service::Bdo), "http://localhost:8080/bdo/");
+    assert_eq!(proxied.url(Service::Fount), "http://localhost:3006/");
+}
+
+#[test]
+fn test_from_toml() {
+    let directory = ServiceDirectory::from_toml(r#"
+routing = "ports"
+host = "allyabase"
+
+[ports]
+fount = 3002
+julia = 3006
+
+[services]
+sanora = "https://sanora.example.com"
+"#).expect("toml");
+    assert_eq!(directory.url(Service::Fount), "http://allyabase:3002/");
+    assert_eq!(directory.url(Service::Julia), "http://allyabase:3006/");
+    assert_eq!(directory.url(Service::Sanora), "https://sanora.example.com/");
+
+    let directory = ServiceDirectory::from_toml("routing = \"subdomain\"\nsubdomain = \"dev\"").expect("subdomain");
+    assert_eq!(directory.routing, Routing::Subdomain { subdomain: "dev".to_string(), domain: "allyabase.com".to_string() });
+
+    assert!(matches!(ServiceDirectory::from_toml("routing = \"path\""), Err(DirectoryError::Missing("base_url"))));
+    assert!(matches!(ServiceDirectory::from_toml("routing = \"carrier-pigeon\""), Err(DirectoryError::UnknownRouting(_))));
+    assert!(matches!(ServiceDirectory::from_toml("base_url = \"http://nginx\"\n[ports]\nzeus = 1"), Err(DirectoryError::UnknownService(_))));
+}
@@ -0,0 +1,35 @@
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) use sessionless_helpers_rs::verifies;
+
+// Signed, since the mock's clock can be offset either way.
+pub const ALLOWED_TIME_DIFFERENCE: i64 = sessionless_helpers_rs::ALLOWED_TIME_DIFFERENCE as i64;
+
+pub(crate) fn now(clock_offset: i64) -> i64 {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as i64;
+    millis + clock_offset
+}
+
+/// The servers' timestamp middleware reads `+req.query.timestamp || +req.body.timestamp`
+/// and lets anything that isn't a number through, so this does too.
+pub(crate) fn is_stale(timestamp: Option<&str>, now: i64) -> bool {
+    match timestamp.and_then(|timestamp| timestamp.parse::<i64>().ok()) {
+        Some(timestamp) => (now - timestamp).abs() > ALLOWED_TIME_DIFFERENCE,
+        None => false
+    }
+}
+
+/// A JSON value the way JavaScript string concatenation renders it, which is
+/// how the servers build the messages they verify.
+pub(crate) fn js(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => "undefined".to_string(),
+        Value::Array(values) => values.iter().map(js).collect::<Vec<_>>().join(","),
+        value => value.to_string()
+    }
+}
@@ -0,0 +1,157 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tiny_http::Method;
+use crate::auth::{js, verifies};
+use crate::state::State;
+use crate::Reply;
+
+/// bdo.js's routes. Bdo authenticates through continuebee, which the mock
+/// folds in: users are created with `timestamp + pubKey + hash` and every
+/// later call signs `timestamp + uuid + hash`.
+pub(crate) fn route(state: &mut State, method: &Method, segments: &[&str], query: &HashMap<String, String>, body: &Value) -> Reply {
+    match (method, segments) {
+        (Method::Put, ["user", "create"]) => create_user(state, body),
+        (Method::Put, ["user", uuid, "bdo"]) => put_bdo(state, uuid, body),
+        (Method::Get, ["user", uuid, "bdo"]) => get_bdo(state, uuid, query),
+        (Method::Get, ["user", uuid, "bases"]) => get_bases(state, uuid, query),
+        (Method::Put, ["user", uuid, "bases"]) => put_bases(state, uuid, body),
+        (Method::Get, ["user", uuid, "spellbooks"]) => get_spellbooks(state, uuid, query),
+        (Method::Put, ["user", uuid, "spellbooks"]) => put_spellbook(state, uuid, body),
+        (Method::Delete, ["user", "delete"]) => delete_user(state, body),
+        _ => Reply::not_found()
+    }
+}
+
+fn auth_error() -> Reply {
+    Reply::error(403, "Auth error")
+}
+
+// continuebee's `GET /user/:uuid?timestamp&hash&signature`.
+fn authenticates(state: &State, uuid: &str, timestamp: &str, hash: &str, signature: &str) -> bool {
+    let Some(user) = state.bdo_users.get(uuid) else {
+        return false;
+    };
+
+    user.hash == hash && verifies(signature, &format!("{}{}{}", timestamp, uuid, hash), &user.pub_key)
+}
+
+fn authenticates_query(state: &State, uuid: &str, query: &HashMap<String, String>) -> bool {
+    let param = |key: &str| query.get(key).cloned().unwrap_or_default();
+    authenticates(state, uuid, &param("timestamp"), &param("hash"), &param("signature"))
+}
+
+fn authenticates_body(state: &State, uuid: &str, body: &Value) -> bool {
+    authenticates(state, uuid, &js(&body["timestamp"]), &js(&body["hash"]), &js(&body["signature"]))
+}
+
+/// Public BDOs are also stored under the owner's pubKey.
+fn put(state: &mut State, uuid: &str, hash: &str, bdo: &Value, pub_key: Option<&str>) {
+    state.bdos.insert(format!("{}_{}", uuid, hash), bdo.clone());
+    if let Some(pub_key) = pub_key {
+        state.bdos.insert(pub_key.to_string(), bdo.clone());
+    }
+}
+
+fn create_user(state: &mut State, body: &Value) -> Reply {
+    let pub_key = js(&body["pubKey"]);
+    let hash = js(&body["hash"]);
+    let message = format!("{}{}{}", js(&body["timestamp"]), pub_key, hash);
+
+    if !verifies(&js(&body["signature"]), &message, &pub_key) {
+        return auth_error();
+    }
+
+    let uuid = state.create_bdo_user(&pub_key, &hash).uuid;
+    let bdo = &body["bdo"];
+    if !bdo.is_null() {
+        let public = body["public"].as_bool().unwrap_or_default();
+        put(state, &uuid, &hash, bdo, public.then_some(pub_key.as_str()));
+    }
+
+    Reply::json(json!({ "uuid": uuid, "bdo": bdo }))
+}
+
+fn put_bdo(state: &mut State, uuid: &str, body: &Value) -> Reply {
+    if !authenticates_body(state, uuid, body) {
+        return auth_error();
+    }
+
+    let hash = js(&body["hash"]);
+    let pub_key = body["pubKey"].as_str();
+    put(state, uuid, &hash, &body["bdo"], pub_key);
+
+    Reply::json(json!({ "uuid": uuid, "bdo": body["bdo"] }))
+}
+
+/// With `pubKey` this reads that key's public BDO instead of the caller's own.
+fn get_bdo(state: &State, uuid: &str, query: &HashMap<String, String>) -> Reply {
+    if !authenticates_query(state, uuid, query) {
+        return auth_error();
+    }
+
+    let key = match query.get("pubKey") {
+        Some(pub_key) => pub_key.clone(),
+        None => format!("{}_{}", uuid, query.get("hash").cloned().unwrap_or_default())
+    };
+    let bdo = state.bdos.get(&key).cloned().unwrap_or(Value::Null);
+
+    Reply::json(json!({ "uuid": uuid, "bdo": bdo }))
+}
+
+fn get_bases(state: &State, uuid: &str, query: &HashMap<String, String>) -> Reply {
+    if !authenticates_query(state, uuid, query) {
+        return auth_error();
+    }
+
+    Reply::json(json!({ "bases": state.bases }))
+}
+
+/// Merges into the node's bases, which are shared by every user.
+fn put_bases(state: &mut State, uuid: &str, body: &Value) -> Reply {
+    if !authenticates_body(state, uuid, body) {
+        return auth_error();
+    }
+    let Some(bases) = body["bases"].as_object() else {
+        return Reply::not_found();
+    };
+
+    state.bases.extend(bases.clone());
+
+    Reply::json(json!({ "bases": state.bases }))
+}
+
+fn get_spellbooks(state: &State, uuid: &str, query: &HashMap<String, String>) -> Reply {
+    if !authenticates_query(state, uuid, query) {
+        return auth_error();
+    }
+
+    Reply::json(json!({ "spellbooks": state.spellbooks }))
+}
+
+fn put_spellbook(state: &mut State, uuid: &str, body: &Value) -> Reply {
+    if !authenticates_body(state, uuid, body) {
+        return auth_error();
+    }
+    let spellbook = &body["spellbook"];
+    if spellbook["spellbookName"].is_null() {
+        return Reply::not_found();
+    }
+
+    state.spellbooks.push(spellbook.clone());
+
+    Reply::json(json!({ "spellbooks": state.spellbooks }))
+}
+
+/// Bdo answers a delete with an empty 202.
+fn delete_user(state: &mut State, body: &Value) -> Reply {
+    let uuid = js(&body["uuid"]);
+    if !authenticates_body(state, &uuid, body) {
+        return auth_error();
+    }
+
+    let hash = js(&body["hash"]);
+    state.bdos.remove(&format!("{}_{}", uuid, hash));
+    state.bdo_users.remove(&uuid);
+
+    Reply::empty(202)
+}
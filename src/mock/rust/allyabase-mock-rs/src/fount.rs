@@ -0,0 +1,195 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tiny_http::Method;
+use crate::auth::{js, verifies};
+use crate::state::{State, EXPERIENCE_TO_MP_RATIO};
+use crate::Reply;
+
+// Fount answers failed spells with this made up status.
+const SPELL_FAILED: u16 = 900;
+
+/// fount.js's routes, with its status codes and error bodies.
+pub(crate) fn route(state: &mut State, now: i64, method: &Method, segments: &[&str], query: &HashMap<String, String>, body: &Value) -> Reply {
+    match (method, segments) {
+        (Method::Put, ["user", "create"]) => create_user(state, now, body),
+        (Method::Get, ["user", "pubKey", pub_key]) => get_user_by_pub_key(state, pub_key, query),
+        (Method::Get, ["user", uuid]) => get_user(state, uuid, query),
+        (Method::Get, ["user", uuid, "nineum"]) => get_nineum(state, uuid, query),
+        (Method::Post, ["user", uuid, "grant"]) => grant(state, uuid, body),
+        (Method::Post, ["user", uuid, "transfer"]) => transfer(state, uuid, body),
+        (Method::Delete, ["user", uuid]) => delete_user(state, uuid, body),
+        (Method::Post, ["resolve", spell_name]) => resolve(state, spell_name, body),
+        _ => Reply::not_found()
+    }
+}
+
+fn param<'a>(query: &'a HashMap<String, String>, key: &str) -> &'a str {
+    query.get(key).map(String::as_str).unwrap_or_default()
+}
+
+fn create_user(state: &mut State, now: i64, body: &Value) -> Reply {
+    let pub_key = js(&body["pubKey"]);
+    let message = format!("{}{}", js(&body["timestamp"]), pub_key);
+
+    if !verifies(&js(&body["signature"]), &message, &pub_key) {
+        return Reply::error(403, "auth error");
+    }
+
+    Reply::json(state.create_fount_user(&pub_key, now as u64).to_json())
+}
+
+fn get_user(state: &State, uuid: &str, query: &HashMap<String, String>) -> Reply {
+    let Some(user) = state.fount_users.get(uuid) else {
+        return Reply::not_found();
+    };
+    let message = format!("{}{}", param(query, "timestamp"), uuid);
+
+    if !verifies(param(query, "signature"), &message, &user.pub_key) {
+        return Reply::error(403, "auth error");
+    }
+
+    Reply::json(user.to_json())
+}
+
+fn get_user_by_pub_key(state: &State, pub_key: &str, query: &HashMap<String, String>) -> Reply {
+    let Some(user) = state.fount_user_by_pub_key(pub_key) else {
+        return Reply::not_found();
+    };
+    let message = format!("{}{}", param(query, "timestamp"), pub_key);
+
+    if !verifies(param(query, "signature"), &message, &user.pub_key) {
+        return Reply::error(403, "auth error");
+    }
+
+    Reply::json(user.to_json())
+}
+
+fn get_nineum(state: &State, uuid: &str, query: &HashMap<String, String>) -> Reply {
+    let Some(user) = state.fount_users.get(uuid) else {
+        return Reply::not_found();
+    };
+    let message = format!("{}{}", param(query, "timestamp"), uuid);
+
+    if !verifies(param(query, "signature"), &message, &user.pub_key) {
+        return Reply::error(403, "auth error");
+    }
+
+    Reply::json(json!({ "nineum": user.nineum }))
+}
+
+/// Spends MP on experience for `destinationUUID`. Like fount, a grant the
+/// source can't afford quietly changes nothing.
+fn grant(state: &mut State, uuid: &str, body: &Value) -> Reply {
+    let destination_uuid = js(&body["destinationUUID"]);
+    let (Some(source), true) = (state.fount_users.get(uuid), state.fount_users.contains_key(&destination_uuid)) else {
+        return Reply::not_found();
+    };
+    let message = format!("{}{}{}{}{}", js(&body["timestamp"]), uuid, destination_uuid, js(&body["amount"]), js(&body["description"]));
+
+    if !verifies(&js(&body["signature"]), &message, &source.pub_key) {
+        return Reply::error(403, "Auth error");
+    }
+
+    let amount = body["amount"].as_u64().unwrap_or_default() as u32;
+    if amount <= source.mp * EXPERIENCE_TO_MP_RATIO {
+        if let Some(source) = state.fount_users.get_mut(uuid) {
+            source.mp -= amount.div_ceil(EXPERIENCE_TO_MP_RATIO);
+        }
+        if let Some(destination) = state.fount_users.get_mut(&destination_uuid) {
+            destination.experience_pool += amount;
+        }
+    }
+
+    Reply::json(state.fount_users[uuid].to_json())
+}
+
+/// Moves whichever of `nineumUniqueIds` the source holds. Paid transfers are
+/// unimplemented in fount, and so here.
+fn transfer(state: &mut State, uuid: &str, body: &Value) -> Reply {
+    let destination_uuid = js(&body["destinationUUID"]);
+    let (Some(source), true) = (state.fount_users.get(uuid), state.fount_users.contains_key(&destination_uuid)) else {
+        return Reply::not_found();
+    };
+    let ids: Vec<String> = body["nineumUniqueIds"].as_array().into_iter().flatten().map(js).collect();
+    let message = format!("{}{}{}{}{}{}", js(&body["timestamp"]), uuid, destination_uuid, ids.join(""), js(&body["price"]), js(&body["currency"]));
+
+    if !verifies(&js(&body["signature"]), &message, &source.pub_key) {
+        return Reply::error(403, "Auth error");
+    }
+    if body["price"].as_u64().is_some_and(|price| price != 0) {
+        return Reply::error(501, "unimplemented");
+    }
+    if uuid == destination_uuid {
+        return Reply::json(source.to_json());
+    }
+
+    let mut kept = source.nineum.clone();
+    let mut moved = vec![];
+    for id in ids {
+        if let Some(index) = kept.iter().position(|held| *held == id) {
+            moved.push(kept.remove(index));
+        }
+    }
+
+    if let Some(destination) = state.fount_users.get_mut(&destination_uuid) {
+        let mut nineum = destination.nineum.clone();
+        nineum.extend(moved);
+        destination.set_nineum(nineum);
+    }
+    let source = state.fount_users.get_mut(uuid).expect("source");
+    source.set_nineum(kept);
+
+    Reply::json(source.to_json())
+}
+
+/// Fount sends nothing back on a delete.
+fn delete_user(state: &mut State, uuid: &str, body: &Value) -> Reply {
+    let Some(user) = state.fount_users.get(uuid) else {
+        return Reply::not_found();
+    };
+    let message = format!("{}{}", js(&body["timestamp"]), js(&body["uuid"]));
+
+    if !verifies(&js(&body["signature"]), &message, &user.pub_key) {
+        return Reply::error(403, "auth error");
+    }
+
+    state.fount_users.remove(uuid);
+    Reply::empty(200)
+}
+
+/// Checks the caster's signature and MP and spends it. What the spell then
+/// does at its destinations is up to a handler registered with
+/// `MockAllyabase::on_spell`, whose fields are added to the result.
+fn resolve(state: &mut State, spell_name: &str, body: &Value) -> Reply {
+    let failed = Reply::status(SPELL_FAILED, json!({ "success": false }));
+    let caster_uuid = js(&body["casterUUID"]);
+    let Some(caster) = state.fount_users.get(&caster_uuid) else {
+        return failed;
+    };
+    let message = format!(
+        "{}{}{}{}{}{}",
+        js(&body["timestamp"]), js(&body["spell"]), caster_uuid, js(&body["totalCost"]), js(&body["mp"]), js(&body["ordinal"])
+    );
+
+    if !verifies(&js(&body["casterSignature"]), &message, &caster.pub_key) {
+        return failed;
+    }
+    if body["mp"] != json!(true) {
+        // Paying in money goes through addie, which the mock doesn't have.
+        return failed;
+    }
+    let total_cost = body["totalCost"].as_u64().unwrap_or_default() as u32;
+    if caster.mp < total_cost {
+        return failed;
+    }
+
+    let caster = state.fount_users.get_mut(&caster_uuid).expect("caster");
+    caster.mp -= total_cost;
+
+    let mut result = json!({ "success": true, "signatureMap": {} });
+    if let Some(Value::Object(extra)) = state.spells.get(spell_name).map(|handler| handler(body)) {
+        result.as_object_mut().expect("result").extend(extra);
+    }
+
+    Reply::json(result)
+}
@@ -0,0 +1,208 @@
+pub mod auth;
+pub mod state;
+mod bdo;
+mod fount;
+
+#[cfg(test)]
+mod tests;
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, UNIX_EPOCH};
+use tiny_http::{Header, Request, Response, Server};
+use crate::auth::{is_stale, js, now};
+pub use crate::state::{BdoAccount, FountAccount, SpellHandler, State};
+pub use service_directory_rs::{Service, ServiceDirectory};
+
+/// An allyabase node in the test process: fount and bdo behind one port with
+/// nginx-style paths, checking Sessionless signatures and timestamps like the
+/// real services and keeping users in memory. It stops when dropped.
+///
+/// ```no_run
+/// let node = allyabase_mock_rs::MockAllyabase::start().expect("mock node");
+/// let directory = node.directory();
+/// // Fount::with_directory(&directory, None), BDO::with_directory(&directory, None), ...
+/// ```
+pub struct MockAllyabase {
+    server: Arc<Server>,
+    state: Arc<Mutex<State>>,
+    url: String,
+    thread: Option<JoinHandle<()>>
+}
+
+impl MockAllyabase {
+    /// Listens on a free port on 127.0.0.1.
+    pub fn start() -> Result<Self, Box<dyn std::error::Error>> {
+        let server = Arc::new(Server::http("127.0.0.1:0").map_err(|error| error.to_string())?);
+        let port = server.server_addr().to_ip().ok_or("mock node is not listening on an IP address")?.port();
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let thread = {
+            let server = server.clone();
+            let state = state.clone();
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle(&state, request);
+                }
+            })
+        };
+
+        Ok(MockAllyabase {
+            server,
+            state,
+            url: format!("http://127.0.0.1:{}/", port),
+            thread: Some(thread)
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Path-based routing to this node, for the clients' `with_directory`.
+    pub fn directory(&self) -> ServiceDirectory {
+        ServiceDirectory::path_based(&self.url)
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Runs the node's clock `millis` ahead (or behind, if negative) of this machine's.
+    pub fn set_clock_offset(&self, millis: i64) {
+        self.state().clock_offset = millis;
+    }
+
+    /// Lets `spell_name` do something once fount has accepted it: `handler`
+    /// gets the spell as sent and returns an object whose fields are added to
+    /// the `SpellResult`.
+    pub fn on_spell(&self, spell_name: &str, handler: impl Fn(&Value) -> Value + Send + Sync + 'static) {
+        self.state().spells.insert(spell_name.to_string(), Box::new(handler));
+    }
+
+    /// See `State::mint_nineum`. `flavor` is the twelve hex characters of
+    /// charge, direction, rarity, size, texture and shape.
+    pub fn mint_nineum(&self, uuid: &str, flavor: &str, count: usize) -> Option<Vec<String>> {
+        self.state().mint_nineum(uuid, flavor, count)
+    }
+}
+
+impl Drop for MockAllyabase {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// What a route sends back. A `None` body is sent empty, like `res.send()`.
+pub(crate) struct Reply {
+    status: u16,
+    body: Option<Value>
+}
+
+impl Reply {
+    pub(crate) fn json(body: Value) -> Self {
+        Reply { status: 200, body: Some(body) }
+    }
+
+    pub(crate) fn status(status: u16, body: Value) -> Self {
+        Reply { status, body: Some(body) }
+    }
+
+    pub(crate) fn empty(status: u16) -> Self {
+        Reply { status, body: None }
+    }
+
+    pub(crate) fn error(status: u16, message: &str) -> Self {
+        Self::status(status, json!({ "error": message }))
+    }
+
+    pub(crate) fn not_found() -> Self {
+        Self::error(404, "not found")
+    }
+}
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'+', _) => decoded.push(b' '),
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 2;
+            },
+            (byte, _) => decoded.push(byte)
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (decode(key), decode(value)),
+            None => (decode(pair), String::new())
+        })
+        .collect()
+}
+
+fn route(state: &Mutex<State>, method: &tiny_http::Method, url: &str, body: &str) -> Reply {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let query = parse_query(query);
+    let body: Value = if body.trim().is_empty() {
+        Value::Null
+    } else {
+        match serde_json::from_str(body) {
+            Ok(body) => body,
+            Err(error) => return Reply::error(400, &error.to_string())
+        }
+    };
+
+    let mut state = state.lock().unwrap();
+    let now = now(state.clock_offset);
+
+    // Both services turn away requests more than five minutes off, with a 200.
+    let timestamp = query.get("timestamp").cloned().or(body.get("timestamp").map(js));
+    if is_stale(timestamp.as_deref(), now) {
+        return Reply::json(json!({ "error": "no time like the present" }));
+    }
+
+    let segments: Vec<String> = path.split('/').filter(|segment| !segment.is_empty()).map(decode).collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    match segments.split_first() {
+        Some((&"fount", rest)) => fount::route(&mut state, now, method, rest, &query, &body),
+        Some((&"bdo", rest)) => bdo::route(&mut state, method, rest, &query, &body),
+        _ => Reply::not_found()
+    }
+}
+
+fn handle(state: &Mutex<State>, mut request: Request) {
+    let mut body = String::new();
+    let reply = match request.as_reader().read_to_string(&mut body) {
+        Ok(_) => route(state, request.method(), request.url(), &body),
+        Err(error) => Reply::error(400, &error.to_string())
+    };
+
+    // Dated by the node's clock, so clients can measure their drift from it.
+    let date = UNIX_EPOCH + Duration::from_millis(now(state.lock().unwrap().clock_offset).max(0) as u64);
+    let content = reply.body.map(|body| body.to_string()).unwrap_or_default();
+    let header = Header::from_bytes("Content-Type", "application/json; charset=utf-8").expect("header");
+    let response = Response::from_string(content)
+        .with_status_code(reply.status)
+        .with_header(header)
+        .with_header(Header::from_bytes("Date", httpdate::fmt_http_date(date)).expect("header"));
+    let _ = request.respond(response);
+}
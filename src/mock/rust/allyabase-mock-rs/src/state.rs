@@ -0,0 +1,139 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sessionless::Sessionless;
+use std::collections::HashMap;
+
+// fount's maxMPAndRegenerationRate.maxMP, which new users start with.
+pub const MAX_MP: u32 = 1000;
+
+// fount's experienceToMPRatio: granting experience costs one MP per 15.
+pub const EXPERIENCE_TO_MP_RATIO: u32 = 15;
+
+// The Open Source Force galaxy fount mints into by default.
+pub const DEFAULT_GALAXY: &str = "28880014";
+
+pub type SpellHandler = Box<dyn Fn(&Value) -> Value + Send + Sync>;
+
+/// A fount user as fount sends it back. Nineum are kept alongside, the way
+/// fount keeps them under their own redis key.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct FountAccount {
+    pub pub_key: String,
+    pub mp: u32,
+    #[serde(rename = "maxMP")]
+    pub max_mp: u32,
+    #[serde(rename = "lastMPUsed")]
+    pub last_mp_used: u64,
+    pub experience: u64,
+    #[serde(rename = "lastExperienceCalculated")]
+    pub last_experience_calculated: u64,
+    pub experience_pool: u32,
+    pub nineum_count: u64,
+    pub ordinal: u32,
+    pub uuid: String,
+    #[serde(skip)]
+    pub nineum: Vec<String>
+}
+
+impl FountAccount {
+    fn new(pub_key: &str, now: u64) -> Self {
+        FountAccount {
+            pub_key: pub_key.to_string(),
+            mp: MAX_MP,
+            max_mp: MAX_MP,
+            last_mp_used: now,
+            experience: 0,
+            last_experience_calculated: now,
+            experience_pool: 0,
+            nineum_count: 0,
+            ordinal: 1,
+            uuid: Sessionless::generate_uuid().to_string(),
+            nineum: vec![]
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).expect("fount account")
+    }
+
+    pub(crate) fn set_nineum(&mut self, nineum: Vec<String>) {
+        self.nineum_count = nineum.len() as u64;
+        self.nineum = nineum;
+    }
+}
+
+/// A continuebee user, which is what bdo authenticates against.
+#[derive(Clone, Debug)]
+pub struct BdoAccount {
+    pub uuid: String,
+    pub pub_key: String,
+    pub hash: String
+}
+
+/// Everything the mock node remembers. It starts empty and lives as long as
+/// the `MockAllyabase` that owns it.
+#[derive(Default)]
+pub struct State {
+    /// Added to the mock's clock, to test how clients handle skew.
+    pub clock_offset: i64,
+    pub fount_users: HashMap<String, FountAccount>,
+    pub bdo_users: HashMap<String, BdoAccount>,
+    /// Keyed like bdo's redis: `uuid_hash`, or the pubKey for public BDOs.
+    pub bdos: HashMap<String, Value>,
+    pub bases: Map<String, Value>,
+    pub spellbooks: Vec<Value>,
+    pub(crate) spells: HashMap<String, SpellHandler>,
+    minted: u64
+}
+
+impl State {
+    pub(crate) fn create_fount_user(&mut self, pub_key: &str, now: u64) -> FountAccount {
+        if let Some(user) = self.fount_user_by_pub_key(pub_key) {
+            return user.clone();
+        }
+
+        let user = FountAccount::new(pub_key, now);
+        self.fount_users.insert(user.uuid.clone(), user.clone());
+        user
+    }
+
+    pub fn fount_user_by_pub_key(&self, pub_key: &str) -> Option<&FountAccount> {
+        self.fount_users.values().find(|user| user.pub_key == pub_key)
+    }
+
+    pub(crate) fn bdo_user(&self, pub_key: &str, hash: &str) -> Option<&BdoAccount> {
+        self.bdo_users.values().find(|user| user.pub_key == pub_key && user.hash == hash)
+    }
+
+    pub(crate) fn create_bdo_user(&mut self, pub_key: &str, hash: &str) -> BdoAccount {
+        if let Some(user) = self.bdo_user(pub_key, hash) {
+            return user.clone();
+        }
+
+        let user = BdoAccount {
+            uuid: Sessionless::generate_uuid().to_string(),
+            pub_key: pub_key.to_string(),
+            hash: hash.to_string()
+        };
+        self.bdo_users.insert(user.uuid.clone(), user.clone());
+        user
+    }
+
+    /// Gives the fount user at `uuid` `count` fresh nineum of one flavor and
+    /// returns their ids, or `None` if there is no such user.
+    pub fn mint_nineum(&mut self, uuid: &str, flavor: &str, count: usize) -> Option<Vec<String>> {
+        let user = self.fount_users.get_mut(uuid)?;
+
+        let mut nineum = user.nineum.clone();
+        let mut minted = vec![];
+        for _ in 0..count {
+            self.minted += 1;
+            minted.push(format!("01{}{}01{:08}", DEFAULT_GALAXY, flavor, self.minted));
+        }
+        nineum.extend(minted.iter().cloned());
+        user.set_nineum(nineum);
+
+        Some(minted)
+    }
+}
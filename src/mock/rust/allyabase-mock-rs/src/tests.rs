@@ -0,0 +1,151 @@
+use crate::{MockAllyabase, Service};
+use crate::auth::{now, ALLOWED_TIME_DIFFERENCE};
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn timestamp() -> String {
+    SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards").as_millis().to_string()
+}
+
+fn create_fount_user(client: &Client, node: &MockAllyabase, sessionless: &Sessionless) -> Value {
+    let timestamp = timestamp();
+    let pub_key = sessionless.public_key().to_hex();
+    let signature = sessionless.sign(format!("{}{}", timestamp, pub_key)).to_hex();
+
+    client
+        .put(format!("{}user/create", node.directory().url(Service::Fount)))
+        .json(&json!({ "timestamp": timestamp, "pubKey": pub_key, "signature": signature }))
+        .send()
+        .expect("create user")
+        .json()
+        .expect("fount user")
+}
+
+#[test]
+fn test_fount_signatures() {
+    let node = MockAllyabase::start().expect("mock node");
+    let client = Client::new();
+    let sessionless = Sessionless::new();
+    let fount = node.directory().url(Service::Fount);
+
+    let user = create_fount_user(&client, &node, &sessionless);
+    let uuid = user["uuid"].as_str().expect("uuid");
+    assert_eq!(user["mp"], json!(1000));
+    assert_eq!(user["pubKey"], json!(sessionless.public_key().to_hex()));
+    assert_eq!(create_fount_user(&client, &node, &sessionless)["uuid"], json!(uuid));
+
+    let timestamp = timestamp();
+    let signature = sessionless.sign(format!("{}{}", timestamp, uuid)).to_hex();
+    let res = client.get(format!("{}user/{}?timestamp={}&signature={}", fount, uuid, timestamp, signature)).send().expect("get user");
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let forged = Sessionless::new().sign(format!("{}{}", timestamp, uuid)).to_hex();
+    let res = client.get(format!("{}user/{}?timestamp={}&signature={}", fount, uuid, timestamp, forged)).send().expect("get user");
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    assert_eq!(res.json::<Value>().expect("error"), json!({ "error": "auth error" }));
+
+    let res = client.get(format!("{}user/{}?timestamp={}&signature={}", fount, "not-a-user", timestamp, signature)).send().expect("get user");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_spells() {
+    let node = MockAllyabase::start().expect("mock node");
+    let client = Client::new();
+    let sessionless = Sessionless::new();
+    let uuid = create_fount_user(&client, &node, &sessionless)["uuid"].as_str().expect("uuid").to_string();
+    node.on_spell("joinup", |spell| json!({ "joined": spell["components"]["name"] }));
+
+    let cast = |total_cost: u32, signer: &Sessionless| {
+        let timestamp = timestamp();
+        let signature = signer.sign(format!("{}{}{}{}{}{}", timestamp, "joinup", uuid, total_cost, true, 1)).to_hex();
+        client
+            .post(format!("{}resolve/joinup", node.directory().url(Service::Fount)))
+            .json(&json!({
+                "timestamp": timestamp,
+                "spell": "joinup",
+                "casterUUID": uuid,
+                "totalCost": total_cost,
+                "mp": true,
+                "ordinal": 1,
+                "casterSignature": signature,
+                "gateways": [],
+                "components": { "name": "zach" }
+            }))
+            .send()
+            .expect("resolve")
+    };
+
+    let res = cast(400, &sessionless);
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.json::<Value>().expect("result"), json!({ "success": true, "signatureMap": {}, "joined": "zach" }));
+    assert_eq!(node.state().fount_users[&uuid].mp, 600);
+
+    let res = cast(50, &Sessionless::new());
+    assert_eq!(res.status().as_u16(), 900);
+    assert_eq!(res.json::<Value>().expect("result"), json!({ "success": false }));
+
+    let res = cast(700, &sessionless);
+    assert_eq!(res.status().as_u16(), 900);
+    assert_eq!(node.state().fount_users[&uuid].mp, 600);
+}
+
+#[test]
+fn test_timestamp_window() {
+    let node = MockAllyabase::start().expect("mock node");
+    let client = Client::new();
+    let sessionless = Sessionless::new();
+
+    node.set_clock_offset(ALLOWED_TIME_DIFFERENCE + 1000);
+    let res = create_fount_user(&client, &node, &sessionless);
+    assert_eq!(res, json!({ "error": "no time like the present" }));
+    assert!(node.state().fount_users.is_empty());
+
+    node.set_clock_offset(ALLOWED_TIME_DIFFERENCE - 1000);
+    assert_eq!(create_fount_user(&client, &node, &sessionless)["mp"], json!(1000));
+
+    // Responses are dated by the node's clock.
+    let res = client.get(node.url()).send().expect("request");
+    let date = httpdate::parse_http_date(res.headers()["date"].to_str().expect("date")).expect("http date");
+    let drift = date.duration_since(UNIX_EPOCH).expect("date").as_millis() as i64 - now(0);
+    assert!((drift - (ALLOWED_TIME_DIFFERENCE - 1000)).abs() < 2000, "{}", drift);
+}
+
+#[test]
+fn test_bdo_auth() {
+    let node = MockAllyabase::start().expect("mock node");
+    let client = Client::new();
+    let sessionless = Sessionless::new();
+    let bdo = node.directory().url(Service::Bdo);
+    let hash = "mock-hash";
+
+    let timestamp = timestamp();
+    let pub_key = sessionless.public_key().to_hex();
+    let signature = sessionless.sign(format!("{}{}{}", timestamp, pub_key, hash)).to_hex();
+    let user: Value = client
+        .put(format!("{}user/create", bdo))
+        .json(&json!({ "timestamp": timestamp, "pubKey": pub_key, "hash": hash, "bdo": { "foo": "bar" }, "public": false, "signature": signature }))
+        .send()
+        .expect("create user")
+        .json()
+        .expect("bdo user");
+    let uuid = user["uuid"].as_str().expect("uuid");
+
+    let signature = sessionless.sign(format!("{}{}{}", timestamp, uuid, hash)).to_hex();
+    let res = client.get(format!("{}user/{}/bdo?timestamp={}&hash={}&signature={}", bdo, uuid, timestamp, hash, signature)).send().expect("get bdo");
+    assert_eq!(res.json::<Value>().expect("bdo"), json!({ "uuid": uuid, "bdo": { "foo": "bar" } }));
+
+    let res = client.get(format!("{}user/{}/bdo?timestamp={}&hash={}&signature={}", bdo, uuid, timestamp, "other-hash", signature)).send().expect("get bdo");
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let res = client.delete(format!("{}user/delete", bdo))
+        .json(&json!({ "timestamp": timestamp, "uuid": uuid, "hash": hash, "signature": signature }))
+        .send()
+        .expect("delete");
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    assert!(node.state().bdo_users.is_empty());
+}
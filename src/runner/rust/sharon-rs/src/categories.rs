@@ -0,0 +1,104 @@
+use crate::discovery::ClientCrate;
+
+/// The groups Sharon's JS runners use, in the order `run-all-tests.js`
+/// runs them. Like `run-all-tests.js`, covenant is only a service here, so
+/// `all` doesn't run it twice.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Group {
+    Protocols,
+    Services,
+    System
+}
+
+pub const ALL_GROUPS: [Group; 3] = [Group::Protocols, Group::Services, Group::System];
+
+impl Group {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "protocols" | "protocol" => Some(Group::Protocols),
+            "services" | "service" => Some(Group::Services),
+            "system" => Some(Group::System),
+            _ => None
+        }
+    }
+
+    /// The group a single category such as `permissions` belongs to.
+    pub fn of_category(category: &str) -> Option<Self> {
+        ALL_GROUPS.into_iter().find(|group| group.categories().contains(&category))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Group::Protocols => "Protocol Tests",
+            Group::Services => "Service Tests",
+            Group::System => "System Tests"
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Group::Protocols => "Core protocol validation",
+            Group::Services => "Individual microservice tests",
+            Group::System => "Cross-service integration tests"
+        }
+    }
+
+    pub fn categories(&self) -> &'static [&'static str] {
+        match self {
+            Group::Protocols => &["sessionless", "magic", "teleportation"],
+            Group::Services => &["addie", "bdo", "continuebee", "dolores", "fount", "joan", "julia", "pref", "prof", "sanora", "covenant", "aretha"],
+            Group::System => &["permissions", "cross-service", "client-server"]
+        }
+    }
+}
+
+/// Which of a crate's tests a suite runs, by libtest name filter.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    All,
+    Only(&'static str),
+    Skip(&'static str)
+}
+
+impl Filter {
+    /// The arguments for `cargo test`, before and after the `--`.
+    pub fn args(&self) -> (Vec<String>, Vec<String>) {
+        match self {
+            Filter::All => (vec![], vec![]),
+            Filter::Only(name) => (vec![name.to_string()], vec![]),
+            Filter::Skip(name) => (vec![], vec!["--skip".to_string(), name.to_string()])
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suite {
+    pub category: String,
+    pub client: ClientCrate,
+    pub filter: Filter
+}
+
+/// The Rust suites behind one category. Categories that only have mocha
+/// tests so far come back empty.
+pub fn suites(group: Group, category: &str, crates: &[ClientCrate]) -> Vec<Suite> {
+    let suite = |client: &ClientCrate, filter: Filter| Suite { category: category.to_string(), client: client.clone(), filter };
+
+    match (group, category) {
+        // A service's own suite; its spells are covered under magic.
+        (Group::Services, service) => crates
+            .iter()
+            .filter(|client| client.service == service)
+            .map(|client| suite(client, Filter::Skip("magic")))
+            .collect(),
+        (Group::Protocols, "magic") => crates
+            .iter()
+            .map(|client| suite(client, Filter::Only("magic")))
+            .collect(),
+        (Group::System, "cross-service") => crates
+            .iter()
+            .filter(|client| client.depends_on_services())
+            .map(|client| suite(client, Filter::Skip("magic")))
+            .collect(),
+        _ => vec![]
+    }
+}
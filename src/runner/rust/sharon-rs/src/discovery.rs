@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A Rust client crate found under `tests/<service>/src/client/rust/`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientCrate {
+    pub service: String,
+    pub name: String,
+    pub manifest: PathBuf,
+    /// The other service crates this one pulls in by path, e.g. `fount-rs`.
+    pub service_deps: Vec<String>
+}
+
+impl ClientCrate {
+    pub fn depends_on_services(&self) -> bool {
+        !self.service_deps.is_empty()
+    }
+}
+
+/// Every client crate under `root/tests`, sorted by service.
+pub fn discover(root: &Path) -> Result<Vec<ClientCrate>, std::io::Error> {
+    let mut crates = vec![];
+
+    for service in fs::read_dir(root.join("tests"))? {
+        let service = service?;
+        let rust = service.path().join("src").join("client").join("rust");
+        let Ok(entries) = fs::read_dir(&rust) else {
+            continue;
+        };
+
+        for entry in entries {
+            let manifest = entry?.path().join("Cargo.toml");
+            if !manifest.is_file() {
+                continue;
+            }
+            let contents = fs::read_to_string(&manifest)?;
+            let Some(name) = package_name(&contents) else {
+                continue;
+            };
+
+            crates.push(ClientCrate {
+                service: service.file_name().to_string_lossy().to_string(),
+                name,
+                manifest,
+                service_deps: service_deps(&contents)
+            });
+        }
+    }
+
+    crates.sort_by(|a, b| a.service.cmp(&b.service).then(a.name.cmp(&b.name)));
+    Ok(crates)
+}
+
+// The manifests are simple enough that a line scan beats pulling in a TOML parser.
+
+pub(crate) fn package_name(manifest: &str) -> Option<String> {
+    let package = manifest.split("[package]").nth(1)?;
+    package
+        .lines()
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "name").then(|| value.trim().trim_matches('"').to_string())
+        })
+}
+
+pub(crate) fn service_deps(manifest: &str) -> Vec<String> {
+    manifest
+        .lines()
+        .filter(|line| line.contains("path") && line.contains("/src/client/rust/"))
+        .filter_map(|line| line.split_once('=').map(|(name, _)| name.trim().to_string()))
+        .collect()
+}
@@ -0,0 +1,102 @@
+pub mod categories;
+pub mod discovery;
+pub mod runner;
+
+#[cfg(test)]
+mod tests;
+
+use std::path::PathBuf;
+pub use crate::categories::{suites, Filter, Group, Suite, ALL_GROUPS};
+pub use crate::discovery::{discover, ClientCrate};
+pub use crate::runner::{count_results, SuiteResult};
+
+/// Command line options, matching the flags of the JS runners where they overlap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub groups: Vec<Group>,
+    pub categories: Vec<String>,
+    pub services: Vec<String>,
+    pub root: PathBuf,
+    pub verbose: bool,
+    pub quiet: bool,
+    pub no_color: bool,
+    pub help: bool
+}
+
+impl Options {
+    /// `default_root` is used unless `--root=` is given.
+    pub fn parse(args: &[String], default_root: PathBuf) -> Result<Self, String> {
+        let mut options = Options {
+            groups: vec![],
+            categories: vec![],
+            services: vec![],
+            root: default_root,
+            verbose: false,
+            quiet: false,
+            no_color: false,
+            help: false
+        };
+
+        for arg in args {
+            match arg.as_str() {
+                "--verbose" | "-v" => options.verbose = true,
+                "--quiet" | "-q" => options.quiet = true,
+                "--no-color" => options.no_color = true,
+                "--help" | "-h" => options.help = true,
+                "all" => options.groups.extend(ALL_GROUPS),
+                arg if arg.starts_with("--root=") => options.root = PathBuf::from(&arg["--root=".len()..]),
+                arg if arg.starts_with("--service=") => options.services.push(arg["--service=".len()..].to_string()),
+                arg => match (Group::parse(arg), Group::of_category(arg)) {
+                    (Some(group), _) => options.groups.push(group),
+                    (None, Some(group)) => {
+                        options.groups.push(group);
+                        options.categories.push(arg.to_string());
+                    },
+                    (None, None) => return Err(format!("unknown argument {}", arg))
+                }
+            }
+        }
+
+        if options.groups.is_empty() {
+            options.groups.extend(ALL_GROUPS);
+        }
+        let mut seen = vec![];
+        options.groups.retain(|group| {
+            let first = !seen.contains(group);
+            seen.push(*group);
+            first
+        });
+
+        Ok(options)
+    }
+
+    /// Whether a category was named on the command line, or none were.
+    pub fn includes(&self, category: &str) -> bool {
+        self.categories.is_empty() || self.categories.iter().any(|named| named == category)
+    }
+
+    /// Whether a category was named on the command line.
+    pub fn names(&self, category: &str) -> bool {
+        self.categories.iter().any(|named| named == category)
+    }
+
+    /// Whether `--service=` narrowed the run to exclude this crate.
+    pub fn skips(&self, client: &ClientCrate) -> bool {
+        !self.services.is_empty() && !self.services.iter().any(|service| *service == client.service || *service == client.name)
+    }
+}
+
+pub const USAGE: &str = "Usage: sharon [all|services|protocols|system|<category>]... [options]
+
+Runs the Rust client suites under tests/*/src/client/rust against a running
+allyabase node and exits non-zero if any of them fail, or if nothing ran or a
+named category has no Rust suites. A category such as magic or fount runs
+just that part of its group.
+
+Options:
+  --service=name       Only run suites for this service or crate (repeatable)
+  --root=path          The sharon checkout to discover suites in
+  --verbose, -v        Stream cargo's output instead of capturing it
+  --quiet, -q          Only print failures and the summary
+  --no-color           Disable colored output
+  --help, -h           Show this help message";
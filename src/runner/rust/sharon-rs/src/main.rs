@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Instant;
+use sharon_rs::{discover, runner, suites, Options, USAGE};
+
+const RESET: &str = "\x1b[0m";
+const BRIGHT: &str = "\x1b[1m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // This crate lives at src/runner/rust/sharon-rs in the sharon checkout.
+    let default_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../../..");
+
+    let options = match Options::parse(&args, default_root) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let colorize = |color: &str, text: &str| if options.no_color { text.to_string() } else { format!("{}{}{}", color, text, RESET) };
+    let info = |message: String| if !options.quiet { println!("{}", message) };
+
+    let crates = match discover(&options.root) {
+        Ok(crates) => crates,
+        Err(error) => {
+            eprintln!("Could not discover suites under {}: {}", options.root.display(), error);
+            return ExitCode::from(2);
+        }
+    };
+
+    println!("{}", colorize(&format!("{}{}", BRIGHT, GREEN), "🚀 Sharon - Planet Nine Rust SDK Suites"));
+    info(String::new());
+
+    let start = Instant::now();
+    let (mut passed, mut failed) = (0, 0);
+    let mut unmatched = Vec::<&str>::new();
+
+    for group in &options.groups {
+        info(colorize(&format!("{}{}", BRIGHT, CYAN), &format!("📋 {}", group.name())));
+        info(format!("   {}", group.description()));
+        info(String::new());
+
+        for category in group.categories().iter().filter(|category| options.includes(category)) {
+            let selected: Vec<_> = suites(*group, category, &crates)
+                .into_iter()
+                .filter(|suite| !options.skips(&suite.client))
+                .collect();
+            if selected.is_empty() {
+                info(format!("   {}", colorize(YELLOW, &format!("⚠️  {} - No Rust suites found", category))));
+                if options.names(category) {
+                    unmatched.push(category);
+                }
+                continue;
+            }
+
+            for suite in selected {
+                info(format!("   {}", colorize(BRIGHT, &format!("Testing {} ({})...", category, suite.client.name))));
+                let result = runner::run(&suite, options.verbose);
+                let counts = format!("{} passed, {} failed, {}ms", result.passed, result.failed, result.duration.as_millis());
+
+                if result.success {
+                    info(format!("   {}", colorize(GREEN, &format!("✅ {} passed ({})", suite.client.name, counts))));
+                    passed += 1;
+                } else {
+                    println!("   {}", colorize(RED, &format!("❌ {} failed ({})", suite.client.name, counts)));
+                    if !options.quiet && !result.output.trim().is_empty() {
+                        println!("     {}", colorize(RED, "Error output:"));
+                        for line in result.output.lines() {
+                            println!("     {}", line);
+                        }
+                    }
+                    failed += 1;
+                }
+            }
+        }
+
+        info(String::new());
+    }
+
+    println!("{}", colorize(BRIGHT, "📊 Test Summary"));
+    println!("   Total Suites: {}", passed + failed);
+    println!("   {}", colorize(GREEN, &format!("Passed: {}", passed)));
+    println!("   {}", colorize(RED, &format!("Failed: {}", failed)));
+    println!("   Duration: {}ms\n", start.elapsed().as_millis());
+
+    if failed > 0 {
+        println!("{}", colorize(&format!("{}{}", RED, BRIGHT), "💥 Some tests failed!"));
+        ExitCode::FAILURE
+    } else if passed == 0 || !unmatched.is_empty() {
+        let selection = match unmatched.is_empty() {
+            true => "the selection".to_string(),
+            false => unmatched.join(", ")
+        };
+        println!("{}", colorize(&format!("{}{}", RED, BRIGHT), &format!("💥 No Rust suites ran for {}!", selection)));
+        ExitCode::FAILURE
+    } else {
+        println!("{}", colorize(&format!("{}{}", GREEN, BRIGHT), "🎉 All tests passed!"));
+        ExitCode::SUCCESS
+    }
+}
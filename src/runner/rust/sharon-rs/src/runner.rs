@@ -0,0 +1,70 @@
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use crate::categories::Suite;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SuiteResult {
+    pub success: bool,
+    pub passed: usize,
+    pub failed: usize,
+    pub duration: Duration,
+    pub output: String
+}
+
+/// Builds `cargo test` for a suite without running it. Sharon runs against
+/// a live node, so it includes the suites that are ignored without one; the
+/// contract tests replay fixtures instead and are left out.
+pub fn command(suite: &Suite) -> Command {
+    let (filters, libtest) = suite.filter.args();
+
+    let mut command = Command::new("cargo");
+    command
+        .arg("test")
+        .arg("--manifest-path")
+        .arg(&suite.client.manifest)
+        .args(filters)
+        .args(["--", "--include-ignored", "--skip", "contract"])
+        .args(libtest);
+    command
+}
+
+/// Runs a suite, streaming its output when `verbose` and capturing it
+/// otherwise. A suite that fails to build counts as failed.
+pub fn run(suite: &Suite, verbose: bool) -> SuiteResult {
+    let start = Instant::now();
+    let mut command = command(suite);
+
+    if verbose {
+        return match command.status() {
+            Ok(status) => SuiteResult { success: status.success(), passed: 0, failed: 0, duration: start.elapsed(), output: String::new() },
+            Err(error) => SuiteResult { success: false, passed: 0, failed: 0, duration: start.elapsed(), output: error.to_string() }
+        };
+    }
+
+    match command.stdout(Stdio::piped()).stderr(Stdio::piped()).output() {
+        Ok(out) => {
+            let output = format!("{}{}", String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr));
+            let (passed, failed) = count_results(&output);
+            SuiteResult { success: out.status.success(), passed, failed, duration: start.elapsed(), output }
+        },
+        Err(error) => SuiteResult { success: false, passed: 0, failed: 0, duration: start.elapsed(), output: error.to_string() }
+    }
+}
+
+/// Adds up libtest's `test result:` lines, one per test binary.
+pub fn count_results(output: &str) -> (usize, usize) {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("test result:"))
+        .fold((0, 0), |(passed, failed), summary| {
+            (passed + count(summary, "passed"), failed + count(summary, "failed"))
+        })
+}
+
+fn count(summary: &str, label: &str) -> usize {
+    summary
+        .split(';')
+        .find_map(|part| part.trim().trim_start_matches("ok.").trim_start_matches("FAILED.").trim().strip_suffix(label))
+        .and_then(|number| number.trim().parse().ok())
+        .unwrap_or(0)
+}
@@ -0,0 +1,86 @@
+use crate::discovery::{package_name, service_deps};
+use crate::runner::command;
+use crate::{count_results, discover, suites, Filter, Group, Options, ALL_GROUPS};
+use std::path::PathBuf;
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../../..")
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn test_options() {
+    let options = Options::parse(&args(&[]), root()).expect("defaults");
+    assert_eq!(options.groups, ALL_GROUPS.to_vec());
+    assert!(options.includes("fount"));
+
+    let options = Options::parse(&args(&["services", "--service=fount", "-q", "--root=/srv/sharon"]), root()).expect("services");
+    assert_eq!(options.groups, vec![Group::Services]);
+    assert_eq!(options.services, vec!["fount".to_string()]);
+    assert_eq!(options.root, PathBuf::from("/srv/sharon"));
+    assert!(options.quiet);
+
+    let options = Options::parse(&args(&["permissions", "system"]), root()).expect("permissions");
+    assert_eq!(options.groups, vec![Group::System]);
+    assert!(options.includes("permissions"));
+    assert!(options.names("permissions"));
+    assert!(!options.includes("cross-service"));
+    assert!(!Options::parse(&args(&["system"]), root()).expect("system").names("permissions"));
+
+    assert_eq!(Group::of_category("covenant"), Some(Group::Services));
+    let categories: Vec<&str> = ALL_GROUPS.iter().flat_map(|group| group.categories().iter().copied()).collect();
+    assert_eq!(categories.iter().filter(|category| **category == "covenant").count(), 1);
+
+    assert!(Options::parse(&args(&["everything"]), root()).is_err());
+}
+
+#[test]
+fn test_count_results() {
+    let output = "running 3 tests
+test tests::test_fount ... FAILED
+test result: FAILED. 2 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.05s
+
+   Doc-tests fount_rs
+test result: ok. 4 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+    assert_eq!(count_results(output), (6, 1));
+    assert_eq!(count_results("error[E0425]: cannot find value"), (0, 0));
+}
+
+#[test]
+fn test_manifest_scan() {
+    let manifest = r#"[package]
+name = "joan-rs"
+version = "0.0.1"
+
+[dependencies]
+sessionless = "0.1.1"
+bdo-rs = { path = "../../../../../bdo/src/client/rust/bdo-rs" }
+fount-rs = { path = "../../../../../fount/src/client/rust/fount-rs" }
+"#;
+    assert_eq!(package_name(manifest), Some("joan-rs".to_string()));
+    assert_eq!(service_deps(manifest), vec!["bdo-rs".to_string(), "fount-rs".to_string()]);
+    assert_eq!(package_name("[dependencies]\nname = \"nope\""), None);
+}
+
+#[test]
+fn test_discovered_suites() {
+    let crates = discover(&root()).expect("discover");
+    let fount = crates.iter().find(|client| client.name == "fount-rs").expect("fount-rs");
+    assert_eq!(fount.service, "fount");
+    assert!(crates.iter().any(|client| client.name == "joan-rs" && client.depends_on_services()));
+
+    let services = suites(Group::Services, "fount", &crates);
+    assert_eq!(services.len(), 1);
+    assert_eq!(services[0].filter, Filter::Skip("magic"));
+    let args: Vec<String> = command(&services[0]).get_args().map(|arg| arg.to_string_lossy().to_string()).collect();
+    assert_eq!(args[0], "test");
+    assert_eq!(args[args.len() - 6..], ["--", "--include-ignored", "--skip", "contract", "--skip", "magic"].map(String::from));
+
+    assert_eq!(suites(Group::Protocols, "magic", &crates).len(), crates.len());
+    assert!(suites(Group::Protocols, "teleportation", &crates).is_empty());
+    assert!(suites(Group::System, "cross-service", &crates).iter().all(|suite| suite.client.depends_on_services()));
+}
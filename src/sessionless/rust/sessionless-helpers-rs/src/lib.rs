@@ -0,0 +1,82 @@
+//! Signature and timestamp checks shared by the Rust clients and the mock
+//! node, done the way the allyabase services do them.
+
+#[cfg(test)]
+mod tests;
+
+use once_cell::sync::Lazy;
+use sessionless::hex::FromHex;
+use sessionless::{PublicKey, Sessionless, Signature};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Mirrors allowedTimeDifference in the services.
+pub const ALLOWED_TIME_DIFFERENCE: u64 = 300000;
+
+// Verification only needs the secp256k1 context, so one is shared rather than
+// paying for a fresh keypair on every call.
+static VERIFIER: Lazy<Sessionless> = Lazy::new(Sessionless::new);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimestampError {
+    Invalid(String),
+    Stale { timestamp: u64, now: u64 }
+}
+
+impl fmt::Display for TimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimestampError::Invalid(timestamp) => write!(f, "timestamp is not a number of milliseconds: {}", timestamp),
+            TimestampError::Stale { timestamp, now } => write!(
+                f,
+                "stale timestamp: {} is {}ms from now ({}), allowed difference is {}ms",
+                timestamp,
+                now.abs_diff(*timestamp),
+                now,
+                ALLOWED_TIME_DIFFERENCE
+            )
+        }
+    }
+}
+
+impl std::error::Error for TimestampError {}
+
+/// This machine's time in milliseconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+pub fn parse_public_key(pub_key: &str) -> Option<PublicKey> {
+    PublicKey::from_hex(pub_key).ok()
+}
+
+pub fn parse_signature(signature: &str) -> Option<Signature> {
+    Signature::from_hex(signature).ok()
+}
+
+pub fn signs(message: &str, pub_key: &PublicKey, signature: &Signature) -> bool {
+    VERIFIER.verify(message, pub_key, signature).is_ok()
+}
+
+/// `sessionless.verifySignature(signature, message, pubKey)`: false for
+/// anything that doesn't parse rather than an error, as the services do.
+pub fn verifies(signature: &str, message: &str, pub_key: &str) -> bool {
+    match (parse_public_key(pub_key), parse_signature(signature)) {
+        (Some(pub_key), Some(signature)) => signs(message, &pub_key, &signature),
+        _ => false
+    }
+}
+
+/// Parses a millisecond `timestamp` and checks it is within the services'
+/// window of `now`.
+pub fn check_timestamp(timestamp: &str, now: u64) -> Result<u64, TimestampError> {
+    let parsed: u64 = timestamp.parse().map_err(|_| TimestampError::Invalid(timestamp.to_string()))?;
+    if now.abs_diff(parsed) > ALLOWED_TIME_DIFFERENCE {
+        return Err(TimestampError::Stale { timestamp: parsed, now });
+    }
+
+    Ok(parsed)
+}
@@ -0,0 +1,27 @@
+use crate::{check_timestamp, now, verifies, TimestampError, ALLOWED_TIME_DIFFERENCE};
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+
+#[test]
+fn test_verifies() {
+    let sessionless = Sessionless::new();
+    let pub_key = sessionless.public_key().to_hex();
+    let signature = sessionless.sign("1700000000000abc").to_hex();
+
+    assert!(verifies(&signature, "1700000000000abc", &pub_key));
+    assert!(!verifies(&signature, "1700000000001abc", &pub_key));
+    assert!(!verifies(&signature, "1700000000000abc", &Sessionless::new().public_key().to_hex()));
+    assert!(!verifies("nope", "1700000000000abc", &pub_key));
+    assert!(!verifies(&signature, "1700000000000abc", "nope"));
+}
+
+#[test]
+fn test_check_timestamp() {
+    let now = now();
+    assert_eq!(check_timestamp(&now.to_string(), now + ALLOWED_TIME_DIFFERENCE), Ok(now));
+    assert_eq!(
+        check_timestamp(&now.to_string(), now + ALLOWED_TIME_DIFFERENCE + 1),
+        Err(TimestampError::Stale { timestamp: now, now: now + ALLOWED_TIME_DIFFERENCE + 1 })
+    );
+    assert_eq!(check_timestamp("yesterday", now), Err(TimestampError::Invalid("yesterday".to_string())));
+}
@@ -0,0 +1,87 @@
+use serde_json::Value;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::{HttpRequest, HttpResponse, Method, Transport, TransportError};
+
+/// What fount and bdo answer, with a 200, to a timestamp outside their window.
+pub const STALE_TIMESTAMP: &str = "no time like the present";
+
+/// Drift a `Date` header can't tell apart from none: it only has whole
+/// seconds, and the response took a while to arrive.
+pub const TOLERANCE: i64 = 2000;
+
+/// The time a client signs with: this machine's clock plus an offset learned
+/// from the server it talks to.
+#[derive(Debug, Default)]
+pub struct Clock {
+    offset: AtomicI64
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// This machine's time in milliseconds, uncorrected.
+    pub fn local_millis() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis() as i64
+    }
+
+    pub fn now(&self) -> i64 {
+        Self::local_millis() + self.offset()
+    }
+
+    /// `now` as the services expect it in signed messages.
+    pub fn timestamp(&self) -> String {
+        self.now().to_string()
+    }
+
+    /// How far ahead of this machine the server is, in milliseconds.
+    pub fn offset(&self) -> i64 {
+        self.offset.load(Ordering::Relaxed)
+    }
+
+    pub fn set_offset(&self, millis: i64) {
+        self.offset.store(millis, Ordering::Relaxed);
+    }
+
+    /// Measures the server's drift from `response`'s `Date` header, and adopts
+    /// it as the offset if it differs from the current one by more than
+    /// `TOLERANCE`. `None` if there is no usable header.
+    pub fn observe(&self, response: &HttpResponse) -> Option<i64> {
+        let date = httpdate::parse_http_date(response.header("Date")?).ok()?;
+        let server = date.duration_since(UNIX_EPOCH).ok()?.as_millis() as i64 + 500;
+        let drift = server - Self::local_millis();
+        if (drift - self.offset()).abs() > TOLERANCE {
+            self.set_offset(drift);
+        }
+
+        Some(drift)
+    }
+
+    /// Asks the server at `url` for the time, for a client that wants its
+    /// offset before it signs anything. Any answer will do, as long as it
+    /// carries a `Date` header.
+    pub async fn probe(&self, transport: &dyn Transport, url: &str) -> Result<Option<i64>, TransportError> {
+        let response = transport.send(HttpRequest::new(Method::Get, url, None)).await?;
+
+        Ok(self.observe(&response))
+    }
+
+    /// Whether the server turned `response`'s request away for its timestamp.
+    pub fn is_rejected(response: &HttpResponse) -> bool {
+        response
+            .json::<Value>()
+            .map(|body| body.get("error").and_then(Value::as_str) == Some(STALE_TIMESTAMP))
+            .unwrap_or(false)
+    }
+}
+
+/// `drift` for people: "61.2s behind the server".
+pub(crate) fn describe(drift: i64) -> String {
+    let direction = if drift > 0 { "behind" } else { "ahead of" };
+    format!("{:.1}s {} the server", drift.abs() as f64 / 1000.0, direction)
+}
@@ -0,0 +1,360 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::shape::{diff, ShapeDiff};
+use crate::{HttpRequest, HttpResponse, Method, PooledTransport, Transport, TransportError, TransportFuture};
+
+// Fields whose values change on every run, so fixtures store a placeholder.
+pub const NORMALIZED_FIELDS: [&str; 3] = ["timestamp", "signature", "casterSignature"];
+
+#[derive(Debug)]
+pub enum FixtureError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    UnknownMode(String),
+    Missing(PathBuf)
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixtureError::Io(error) => write!(f, "could not read or write the fixture: {}", error),
+            FixtureError::Json(error) => write!(f, "could not parse the fixture: {}", error),
+            FixtureError::UnknownMode(mode) => write!(f, "unknown fixture mode {}, expected record, replay or verify", mode),
+            FixtureError::Missing(path) => write!(f, "no fixture has been recorded at {}, record one with SHARON_FIXTURES=record", path.display())
+        }
+    }
+}
+
+impl std::error::Error for FixtureError {}
+
+impl From<std::io::Error> for FixtureError {
+    fn from(error: std::io::Error) -> Self {
+        FixtureError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for FixtureError {
+    fn from(error: serde_json::Error) -> Self {
+        FixtureError::Json(error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Send to a node and save what it answers.
+    Record,
+    /// Answer from a saved fixture, without a node.
+    Replay,
+    /// Send to a node and diff its answers against a saved fixture.
+    Verify
+}
+
+impl FixtureMode {
+    /// `SHARON_FIXTURES`: `record`, `verify` or, by default, `replay`.
+    pub fn from_env() -> Result<Self, FixtureError> {
+        Self::parse(std::env::var("SHARON_FIXTURES").ok().as_deref())
+    }
+
+    pub fn parse(mode: Option<&str>) -> Result<Self, FixtureError> {
+        match mode {
+            None | Some("") | Some("replay") => Ok(FixtureMode::Replay),
+            Some("record") => Ok(FixtureMode::Record),
+            Some("verify") => Ok(FixtureMode::Verify),
+            Some(mode) => Err(FixtureError::UnknownMode(mode.to_string()))
+        }
+    }
+}
+
+/// One request and its response, normalized so recordings from different
+/// runs and nodes compare equal.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Exchange {
+    pub method: Method,
+    /// The path under the service's base URL, with uuids and public keys
+    /// replaced by `:uuid` and `:pubKey`.
+    pub route: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub query: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+    pub status: u16,
+    /// `null` for an empty body, a string for one that isn't JSON.
+    pub response: Value
+}
+
+impl Exchange {
+    pub fn recorded(base_url: &str, request: &HttpRequest, response: &HttpResponse) -> Self {
+        let (route, query) = split_url(base_url, &request.url);
+        let body = if response.body.is_empty() {
+            Value::Null
+        } else {
+            response.json().unwrap_or_else(|_| Value::String(response.text()))
+        };
+
+        Exchange {
+            method: request.method,
+            route,
+            query,
+            request: request.body.as_ref().map(normalize),
+            status: response.status,
+            response: normalize(&body)
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("{} {}", self.method, self.route)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub exchanges: Vec<Exchange>
+}
+
+impl Fixture {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FixtureError> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), FixtureError> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+
+        Ok(())
+    }
+
+    /// Compares `found`'s responses to these, exchange by exchange: statuses,
+    /// then the shape of every field.
+    pub fn diff(&self, found: &Fixture) -> Vec<ShapeDiff> {
+        let mut diffs = vec![];
+        for (i, expected) in self.exchanges.iter().enumerate() {
+            match found.exchanges.get(i) {
+                Some(found) if found.method == expected.method && found.route == expected.route => {
+                    if found.status != expected.status {
+                        diffs.push(ShapeDiff::Status { path: expected.name(), expected: expected.status, found: found.status });
+                    }
+                    diffs.extend(diff(&format!("{} $", expected.name()), &expected.response, &found.response));
+                },
+                Some(found) => {
+                    diffs.push(ShapeDiff::Missing { path: expected.name(), expected: "exchange" });
+                    diffs.push(ShapeDiff::Added { path: found.name(), found: "exchange" });
+                },
+                None => diffs.push(ShapeDiff::Missing { path: expected.name(), expected: "exchange" })
+            }
+        }
+        for found in found.exchanges.iter().skip(self.exchanges.len()) {
+            diffs.push(ShapeDiff::Added { path: found.name(), found: "exchange" });
+        }
+
+        diffs
+    }
+}
+
+/// Replaces the values of `NORMALIZED_FIELDS` with `<field>`, at any depth.
+pub fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| match NORMALIZED_FIELDS.contains(&key.as_str()) {
+                    true => (key.clone(), Value::String(format!("<{}>", key))),
+                    false => (key.clone(), normalize(value))
+                })
+                .collect::<Map<String, Value>>()
+        ),
+        Value::Array(values) => Value::Array(values.iter().map(normalize).collect()),
+        value => value.clone()
+    }
+}
+
+fn is_uuid(segment: &str) -> bool {
+    segment.len() == 36 && segment.char_indices().all(|(i, c)| match i {
+        8 | 13 | 18 | 23 => c == '-',
+        _ => c.is_ascii_hexdigit()
+    })
+}
+
+fn is_public_key(segment: &str) -> bool {
+    segment.len() == 66 && segment.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub(crate) fn split_url(base_url: &str, url: &str) -> (String, BTreeMap<String, String>) {
+    let relative = url.strip_prefix(base_url).unwrap_or(url);
+    let (path, query) = relative.split_once('?').unwrap_or((relative, ""));
+
+    let route = path
+        .split('/')
+        .map(|segment| match segment {
+            segment if is_uuid(segment) => ":uuid",
+            segment if is_public_key(segment) => ":pubKey",
+            segment => segment
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match NORMALIZED_FIELDS.contains(&key) {
+                true => (key.to_string(), format!("<{}>", key)),
+                false => (key.to_string(), value.to_string())
+            }
+        })
+        .collect();
+
+    (route, query)
+}
+
+/// Passes requests on to `inner` and keeps every exchange.
+pub struct RecordingTransport {
+    base_url: String,
+    inner: Arc<dyn Transport>,
+    fixture: Mutex<Fixture>
+}
+
+impl RecordingTransport {
+    /// `base_url` is the service's, which routes are recorded relative to.
+    pub fn new(base_url: &str, inner: Arc<dyn Transport>) -> Self {
+        RecordingTransport { base_url: base_url.to_string(), inner, fixture: Mutex::new(Fixture::default()) }
+    }
+
+    pub fn fixture(&self) -> Fixture {
+        self.fixture.lock().unwrap().clone()
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let response = self.inner.send(request.clone()).await?;
+            self.fixture.lock().unwrap().exchanges.push(Exchange::recorded(&self.base_url, &request, &response));
+
+            Ok(response)
+        })
+    }
+}
+
+/// Answers requests from a fixture, in order. A request for a different
+/// route than was recorded next is an error; one whose body or query is
+/// shaped differently is answered anyway and reported by `diffs`.
+pub struct ReplayTransport {
+    base_url: String,
+    exchanges: Mutex<VecDeque<Exchange>>,
+    diffs: Mutex<Vec<ShapeDiff>>
+}
+
+impl ReplayTransport {
+    pub fn new(base_url: &str, fixture: Fixture) -> Self {
+        ReplayTransport {
+            base_url: base_url.to_string(),
+            exchanges: Mutex::new(fixture.exchanges.into()),
+            diffs: Mutex::new(vec![])
+        }
+    }
+
+    pub fn from_file(base_url: &str, path: impl AsRef<Path>) -> Result<Self, FixtureError> {
+        Ok(Self::new(base_url, Fixture::load(path)?))
+    }
+
+    /// The exchanges nothing has asked for yet.
+    pub fn remaining(&self) -> Vec<Exchange> {
+        self.exchanges.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn diffs(&self) -> Vec<ShapeDiff> {
+        self.diffs.lock().unwrap().clone()
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let (route, query) = split_url(&self.base_url, &request.url);
+            let sent = format!("{} {}", request.method, route);
+            let exchange = match self.exchanges.lock().unwrap().pop_front() {
+                Some(exchange) if exchange.method == request.method && exchange.route == route => exchange,
+                Some(exchange) => return Err(TransportError::Replay(format!("expected {} but the client sent {}", exchange.name(), sent))),
+                None => return Err(TransportError::Replay(format!("nothing was recorded for {}", sent)))
+            };
+
+            let mut diffs = self.diffs.lock().unwrap();
+            diffs.extend(diff(&format!("{} query", sent), &serde_json::to_value(&exchange.query)?, &serde_json::to_value(&query)?));
+            let body = request.body.as_ref().map(normalize).unwrap_or(Value::Null);
+            diffs.extend(diff(&format!("{} request $", sent), exchange.request.as_ref().unwrap_or(&Value::Null), &body));
+
+            Ok(match exchange.response {
+                Value::Null => HttpResponse { status: exchange.status, headers: vec![], body: vec![] },
+                ref response => HttpResponse::json_body(exchange.status, response)
+            })
+        })
+    }
+}
+
+/// The transport for a contract test, in the mode `SHARON_FIXTURES` picks:
+///
+/// - replay: answers from the fixture at `path` and checks the requests' shapes
+/// - record: sends to the node and saves what it answers to `path`
+/// - verify: sends to the node and diffs its answers against `path`, which is
+///   how drift between server releases shows up
+pub struct Contract {
+    mode: FixtureMode,
+    path: PathBuf,
+    recording: Option<Arc<RecordingTransport>>,
+    replay: Option<Arc<ReplayTransport>>
+}
+
+impl Contract {
+    /// Replaying a fixture that hasn't been recorded yet is an error, so a
+    /// contract test can't pass without checking anything.
+    pub fn from_env(base_url: &str, path: impl AsRef<Path>) -> Result<Self, FixtureError> {
+        Self::new(FixtureMode::from_env()?, base_url, path, PooledTransport::shared())
+    }
+
+    /// `live` carries requests to the node when recording or verifying.
+    pub fn new(mode: FixtureMode, base_url: &str, path: impl AsRef<Path>, live: Arc<dyn Transport>) -> Result<Self, FixtureError> {
+        let path = path.as_ref().to_path_buf();
+        let (recording, replay) = match mode {
+            FixtureMode::Replay if !path.exists() => return Err(FixtureError::Missing(path)),
+            FixtureMode::Replay => (None, Some(Arc::new(ReplayTransport::from_file(base_url, &path)?))),
+            FixtureMode::Record | FixtureMode::Verify => (Some(Arc::new(RecordingTransport::new(base_url, live))), None)
+        };
+
+        Ok(Contract { mode, path, recording, replay })
+    }
+
+    pub fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    pub fn transport(&self) -> Arc<dyn Transport> {
+        match (&self.recording, &self.replay) {
+            (Some(recording), _) => recording.clone(),
+            (_, Some(replay)) => replay.clone(),
+            _ => unreachable!("a contract records or replays")
+        }
+    }
+
+    /// Saves the recording, or returns everything that didn't match the fixture.
+    pub fn finish(self) -> Result<Vec<ShapeDiff>, FixtureError> {
+        match (self.mode, self.recording, self.replay) {
+            (FixtureMode::Record, Some(recording), _) => {
+                recording.fixture().save(&self.path)?;
+                Ok(vec![])
+            },
+            (FixtureMode::Verify, Some(recording), _) => Ok(Fixture::load(&self.path)?.diff(&recording.fixture())),
+            (_, _, Some(replay)) => {
+                let mut diffs = replay.diffs();
+                diffs.extend(replay.remaining().iter().map(|exchange| ShapeDiff::Missing { path: exchange.name(), expected: "exchange" }));
+                Ok(diffs)
+            },
+            _ => unreachable!("a contract records or replays")
+        }
+    }
+}
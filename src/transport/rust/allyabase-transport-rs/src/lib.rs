@@ -0,0 +1,191 @@
+pub mod clock;
+pub mod fixtures;
+pub mod loopback;
+pub mod pooled;
+pub mod retry;
+pub mod shape;
+pub mod trace;
+
+#[cfg(test)]
+mod tests;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+pub use crate::clock::Clock;
+pub use crate::fixtures::{Contract, Exchange, Fixture, FixtureError, FixtureMode, RecordingTransport, ReplayTransport};
+pub use crate::loopback::LoopbackTransport;
+pub use crate::pooled::{PooledTransport, SharedPool};
+pub use crate::retry::{is_retryable, send_signed, Idempotency, RetryPolicy};
+pub use crate::shape::{Shape, ShapeDiff};
+pub use crate::trace::{Payloads, TracingTransport};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Method::Get => write!(f, "GET"),
+            Method::Post => write!(f, "POST"),
+            Method::Put => write!(f, "PUT"),
+            Method::Delete => write!(f, "DELETE")
+        }
+    }
+}
+
+/// A request as the clients make them: JSON in, if anything.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub body: Option<Value>
+}
+
+impl HttpRequest {
+    pub fn new(method: Method, url: &str, body: Option<Value>) -> Self {
+        HttpRequest { method, url: url.to_string(), body }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>
+}
+
+impl HttpResponse {
+    /// A response carrying `body` as JSON.
+    pub fn json_body(status: u16, body: &Value) -> Self {
+        HttpResponse {
+            status,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: serde_json::to_vec(body).unwrap_or_default()
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The first header called `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+#[derive(Debug)]
+pub enum TransportError {
+    Http(reqwest::Error),
+    Replay(String),
+    /// A non-idempotent request failed in a way that would have been retried.
+    Idempotency(String),
+    /// The server refused the request's timestamp. `drift` is how far ahead
+    /// of this machine the server's `Date` header put it, if it sent one.
+    ClockSkew { drift: Option<i64> }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportError::Http(error) => write!(f, "{}", error),
+            TransportError::Replay(message) => write!(f, "replay: {}", message),
+            TransportError::Idempotency(message) => write!(f, "{}", message),
+            TransportError::ClockSkew { drift: Some(drift) } => write!(
+                f,
+                "the server rejected the timestamp: this clock is {}, which later requests correct for",
+                clock::describe(*drift)
+            ),
+            TransportError::ClockSkew { drift: None } => write!(
+                f,
+                "the server rejected the timestamp and sent no Date header to measure this clock's drift by"
+            )
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+impl From<reqwest::Error> for TransportError {
+    fn from(error: reqwest::Error) -> Self {
+        TransportError::Http(error)
+    }
+}
+
+impl From<serde_json::Error> for TransportError {
+    fn from(error: serde_json::Error) -> Self {
+        TransportError::Replay(error.to_string())
+    }
+}
+
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<HttpResponse, TransportError>> + Send + 'a>>;
+
+/// Whatever carries the clients' requests to a node.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+/// Sends requests with a reqwest client of the caller's choosing.
+#[derive(Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_client(client: reqwest::Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let builder = match request.method {
+                Method::Get => self.client.get(&request.url),
+                Method::Post => self.client.post(&request.url),
+                Method::Put => self.client.put(&request.url),
+                Method::Delete => self.client.delete(&request.url)
+            };
+            let builder = match request.body {
+                Some(ref body) => builder.json(body),
+                None => builder
+            };
+
+            let res = builder.send().await?;
+            let status = res.status().as_u16();
+            let headers = res
+                .headers()
+                .iter()
+                .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+                .collect();
+            let body = res.bytes().await?.to_vec();
+
+            Ok(HttpResponse { status, headers, body })
+        })
+    }
+}
@@ -0,0 +1,30 @@
+use std::sync::Mutex;
+use crate::{HttpRequest, HttpResponse, Transport, TransportFuture};
+
+type Handler = dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync;
+
+/// Answers requests in memory with a handler, without touching the network,
+/// and keeps every request it was sent.
+pub struct LoopbackTransport {
+    handler: Box<Handler>,
+    requests: Mutex<Vec<HttpRequest>>
+}
+
+impl LoopbackTransport {
+    pub fn new(handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static) -> Self {
+        LoopbackTransport { handler: Box::new(handler), requests: Mutex::new(vec![]) }
+    }
+
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let response = (self.handler)(&request);
+        self.requests.lock().unwrap().push(request);
+
+        Box::pin(async move { Ok(response) })
+    }
+}
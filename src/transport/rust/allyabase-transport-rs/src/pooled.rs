@@ -0,0 +1,76 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::{Handle, Id};
+use crate::{HttpRequest, ReqwestTransport, Transport, TransportError, TransportFuture};
+
+pub const MAX_IDLE_PER_HOST: usize = 32;
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+static SHARED: Lazy<Arc<SharedPool>> = Lazy::new(|| Arc::new(SharedPool::default()));
+
+/// One reqwest connection pool for every client that holds it. Cloning it
+/// shares the pool rather than opening another.
+#[derive(Clone)]
+pub struct PooledTransport {
+    inner: ReqwestTransport
+}
+
+impl PooledTransport {
+    /// A new pool, keeping up to `max_idle_per_host` connections open for
+    /// `idle_timeout` between requests.
+    pub fn new(max_idle_per_host: usize, idle_timeout: Duration) -> Result<Self, TransportError> {
+        let client = reqwest::Client::builder()
+            .pool_max_idle_per_host(max_idle_per_host)
+            .pool_idle_timeout(idle_timeout)
+            .build()?;
+
+        Ok(PooledTransport { inner: ReqwestTransport::with_client(client) })
+    }
+
+    /// The pool every client in the process uses unless handed another
+    /// transport. See `SharedPool`.
+    pub fn shared() -> Arc<SharedPool> {
+        SHARED.clone()
+    }
+}
+
+impl Default for PooledTransport {
+    fn default() -> Self {
+        Self::new(MAX_IDLE_PER_HOST, IDLE_TIMEOUT).expect("reqwest client")
+    }
+}
+
+impl Transport for PooledTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        self.inner.send(request)
+    }
+}
+
+/// A `PooledTransport` per async runtime, picked when each request is sent.
+/// Pooled connections stay tied to the runtime that opened them, so clients
+/// on the same runtime share one pool and a client moved to another runtime,
+/// such as the next `#[actix_rt::test]`, gets that runtime's pool instead of
+/// connections whose runtime has shut down.
+#[derive(Default)]
+pub struct SharedPool {
+    pools: Mutex<HashMap<Option<Id>, Arc<PooledTransport>>>
+}
+
+impl SharedPool {
+    /// The pool for the runtime this is called on, opened on first use and
+    /// kept for the life of the process.
+    pub fn current(&self) -> Arc<PooledTransport> {
+        let runtime = Handle::try_current().ok().map(|handle| handle.id());
+        let mut pools = self.pools.lock().expect("shared pools");
+        pools.entry(runtime).or_insert_with(|| Arc::new(PooledTransport::default())).clone()
+    }
+}
+
+impl Transport for SharedPool {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let pool = self.current();
+        Box::pin(async move { pool.send(request).await })
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+use crate::{Clock, HttpRequest, HttpResponse, Transport, TransportError};
+
+/// Whether sending a request twice does the same thing as sending it once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Idempotency {
+    Idempotent,
+    /// Grants, transfers and spells: a retry after a lost response could
+    /// spend twice.
+    NonIdempotent
+}
+
+/// How the clients retry a failed request: after a 5xx or a connection
+/// error, up to `max_attempts` times in all, waiting twice as long after each
+/// attempt. Non-idempotent requests are only retried when `non_idempotent`
+/// is set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub non_idempotent: bool
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(2),
+            non_idempotent: false
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Sends every request once.
+    pub fn never() -> Self {
+        RetryPolicy { max_attempts: 1, ..Default::default() }
+    }
+
+    /// Retries grants, transfers and spells too.
+    pub fn with_non_idempotent(mut self) -> Self {
+        self.non_idempotent = true;
+        self
+    }
+
+    /// How long to wait after `attempt`, counting from 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+/// Whether `result` is a failure worth another attempt. Only 5xx counts:
+/// fount refuses a spell with 900, and that answer won't change.
+pub fn is_retryable(result: &Result<HttpResponse, TransportError>) -> bool {
+    match result {
+        Ok(response) => (500..600).contains(&response.status),
+        Err(TransportError::Http(error)) => error.is_connect() || error.is_timeout(),
+        Err(_) => false
+    }
+}
+
+fn failure(result: &Result<HttpResponse, TransportError>) -> String {
+    match result {
+        Ok(response) => format!("answered {}", response.status),
+        Err(error) => format!("failed: {}", error)
+    }
+}
+
+/// Sends the request `sign` builds from a timestamp off `clock`, calling it
+/// again before every attempt so each one is signed afresh; the services
+/// refuse a timestamp that has aged out of their window. Every response's
+/// `Date` header corrects `clock`, and a refused timestamp is a `ClockSkew`.
+pub async fn send_signed(
+    transport: &dyn Transport,
+    policy: &RetryPolicy,
+    clock: &Clock,
+    idempotency: Idempotency,
+    mut sign: impl FnMut(&str) -> HttpRequest
+) -> Result<HttpResponse, TransportError> {
+    let mut attempt = 1;
+    loop {
+        let request = sign(&clock.timestamp());
+        let name = format!("{} {}", request.method, request.url.split('?').next().unwrap_or_default());
+        let result = transport.send(request).await;
+        if let Ok(ref response) = result {
+            let drift = clock.observe(response);
+            if Clock::is_rejected(response) {
+                return Err(TransportError::ClockSkew { drift });
+            }
+        }
+        if !is_retryable(&result) || attempt >= policy.max_attempts {
+            return result;
+        }
+        if idempotency == Idempotency::NonIdempotent && !policy.non_idempotent {
+            return Err(TransportError::Idempotency(format!(
+                "{} {} and is not safe to retry; RetryPolicy::with_non_idempotent opts in",
+                name,
+                failure(&result)
+            )));
+        }
+
+        tokio::time::sleep(policy.delay(attempt)).await;
+        attempt += 1;
+    }
+}
@@ -0,0 +1,90 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The type of a JSON value, without the value: what a contract between
+/// client and server is about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Shape {
+    Null,
+    Bool,
+    Number,
+    String,
+    /// Shaped like its first element, or `None` when empty.
+    Array(Option<Box<Shape>>),
+    Object(BTreeMap<String, Shape>)
+}
+
+impl Shape {
+    pub fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => Shape::Null,
+            Value::Bool(_) => Shape::Bool,
+            Value::Number(_) => Shape::Number,
+            Value::String(_) => Shape::String,
+            Value::Array(values) => Shape::Array(values.first().map(|value| Box::new(Shape::of(value)))),
+            Value::Object(fields) => Shape::Object(fields.iter().map(|(key, value)| (key.clone(), Shape::of(value))).collect())
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Null => "null",
+            Shape::Bool => "bool",
+            Shape::Number => "number",
+            Shape::String => "string",
+            Shape::Array(_) => "array",
+            Shape::Object(_) => "object"
+        }
+    }
+}
+
+/// One field that differs between two shapes. Paths look like `$.user.nineum[]`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShapeDiff {
+    Missing { path: String, expected: &'static str },
+    Added { path: String, found: &'static str },
+    Changed { path: String, expected: &'static str, found: &'static str },
+    Status { path: String, expected: u16, found: u16 }
+}
+
+impl fmt::Display for ShapeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeDiff::Missing { path, expected } => write!(f, "{} ({}) is missing", path, expected),
+            ShapeDiff::Added { path, found } => write!(f, "{} ({}) is new", path, found),
+            ShapeDiff::Changed { path, expected, found } => write!(f, "{} was {} but is now {}", path, expected, found),
+            ShapeDiff::Status { path, expected, found } => write!(f, "{} answered {} but now answers {}", path, expected, found)
+        }
+    }
+}
+
+/// Compares `found` to `expected` field by field, under `path`.
+pub fn diff(path: &str, expected: &Value, found: &Value) -> Vec<ShapeDiff> {
+    let mut diffs = vec![];
+    diff_shapes(path, &Shape::of(expected), &Shape::of(found), &mut diffs);
+    diffs
+}
+
+fn diff_shapes(path: &str, expected: &Shape, found: &Shape, diffs: &mut Vec<ShapeDiff>) {
+    match (expected, found) {
+        (Shape::Object(expected), Shape::Object(found)) => {
+            for (key, expected) in expected {
+                let path = format!("{}.{}", path, key);
+                match found.get(key) {
+                    Some(found) => diff_shapes(&path, expected, found, diffs),
+                    None => diffs.push(ShapeDiff::Missing { path, expected: expected.name() })
+                }
+            }
+            for (key, found) in found.iter().filter(|(key, _)| !expected.contains_key(*key)) {
+                diffs.push(ShapeDiff::Added { path: format!("{}.{}", path, key), found: found.name() });
+            }
+        },
+        (Shape::Array(Some(expected)), Shape::Array(Some(found))) => diff_shapes(&format!("{}[]", path), expected, found, diffs),
+        (Shape::Array(_), Shape::Array(_)) => {},
+        (expected, found) if expected != found => {
+            diffs.push(ShapeDiff::Changed { path: path.to_string(), expected: expected.name(), found: found.name() });
+        },
+        _ => {}
+    }
+}
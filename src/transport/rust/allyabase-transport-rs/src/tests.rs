@@ -0,0 +1,364 @@
+use crate::{is_retryable, send_signed, Clock, Contract, Fixture, FixtureError, FixtureMode, HttpRequest, HttpResponse, Idempotency, LoopbackTransport, Method, Payloads, PooledTransport, RecordingTransport, ReplayTransport, ReqwestTransport, RetryPolicy, ShapeDiff, TracingTransport, Transport, TransportError};
+use crate::fixtures::normalize;
+use crate::shape::diff;
+use crate::trace::{redact, redact_url};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::{DefaultGuard, Interest};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+const BASE_URL: &str = "http://localhost:3006/";
+const UUID: &str = "5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f";
+
+// A node that answers every request with `body`.
+fn canned(body: Value) -> Arc<dyn Transport> {
+    Arc::new(LoopbackTransport::new(move |_| HttpResponse::json_body(200, &body)))
+}
+
+fn get_user(timestamp: &str) -> HttpRequest {
+    HttpRequest::new(Method::Get, &format!("{}user/{}?timestamp={}&signature=abc{}", BASE_URL, UUID, timestamp, timestamp), None)
+}
+
+fn grant(amount: Value) -> HttpRequest {
+    let body = json!({ "timestamp": "1700000000000", "destinationUUID": UUID, "amount": amount, "signature": "abc" });
+    HttpRequest::new(Method::Post, &format!("{}user/{}/grant", BASE_URL, UUID), Some(body))
+}
+
+#[test]
+fn test_shape_diff() {
+    let expected = json!({ "uuid": "a", "mp": 1000, "nineum": ["x"], "bdo": { "foo": "bar" } });
+    assert_eq!(diff("$", &expected, &json!({ "uuid": "b", "mp": 3, "nineum": [], "bdo": { "foo": "baz" } })), vec![]);
+
+    let found = json!({ "uuid": "a", "mp": "1000", "nineum": [1], "bdo": {}, "maxMP": 1000 });
+    assert_eq!(diff("$", &expected, &found), vec![
+        ShapeDiff::Missing { path: "$.bdo.foo".to_string(), expected: "string" },
+        ShapeDiff::Changed { path: "$.mp".to_string(), expected: "number", found: "string" },
+        ShapeDiff::Changed { path: "$.nineum[]".to_string(), expected: "string", found: "number" },
+        ShapeDiff::Added { path: "$.maxMP".to_string(), found: "number" }
+    ]);
+}
+
+#[test]
+fn test_normalize() {
+    let spell = json!({ "timestamp": "1", "casterSignature": "abc", "gateways": [{ "signature": "def", "uuid": UUID }] });
+    assert_eq!(normalize(&spell), json!({
+        "timestamp": "<timestamp>",
+        "casterSignature": "<casterSignature>",
+        "gateways": [{ "signature": "<signature>", "uuid": UUID }]
+    }));
+}
+
+#[actix_rt::test]
+async fn test_record_and_replay() {
+    let node = canned(json!({ "uuid": UUID, "mp": 1000, "timestamp": 5 }));
+    let recording = RecordingTransport::new(BASE_URL, node);
+    recording.send(get_user("1700000000000")).await.expect("get user");
+    recording.send(grant(json!(150))).await.expect("grant");
+
+    let fixture = recording.fixture();
+    assert_eq!(fixture.exchanges[0].route, "user/:uuid");
+    assert_eq!(fixture.exchanges[0].query["timestamp"], "<timestamp>");
+    assert_eq!(fixture.exchanges[0].query["signature"], "<signature>");
+    assert_eq!(fixture.exchanges[1].route, "user/:uuid/grant");
+    assert_eq!(fixture.exchanges[1].response, json!({ "uuid": UUID, "mp": 1000, "timestamp": "<timestamp>" }));
+
+    // Replays under another base URL and at another time match.
+    let replay = ReplayTransport::new("http://nginx:80/fount/", fixture.clone());
+    let response = replay.send(HttpRequest { url: get_user("1800000000000").url.replace(BASE_URL, "http://nginx:80/fount/"), ..get_user("") }).await.expect("get user");
+    assert_eq!(response.json::<Value>().expect("user")["mp"], json!(1000));
+    assert_eq!(replay.remaining().len(), 1);
+
+    // A request shaped differently is answered but reported.
+    let request = grant(json!("150"));
+    replay.send(HttpRequest { url: request.url.replace(BASE_URL, "http://nginx:80/fount/"), ..request }).await.expect("grant");
+    assert_eq!(replay.diffs(), vec![ShapeDiff::Changed { path: "POST user/:uuid/grant request $.amount".to_string(), expected: "number", found: "string" }]);
+
+    match replay.send(get_user("1")).await {
+        Err(TransportError::Replay(message)) => assert!(message.contains("nothing was recorded"), "{}", message),
+        other => panic!("expected a replay error, got {:?}", other)
+    }
+
+    let replay = ReplayTransport::new(BASE_URL, fixture);
+    match replay.send(grant(json!(150))).await {
+        Err(TransportError::Replay(message)) => assert_eq!(message, "expected GET user/:uuid but the client sent POST user/:uuid/grant"),
+        other => panic!("expected a replay error, got {:?}", other)
+    }
+}
+
+#[actix_rt::test]
+async fn test_contract() {
+    let path = std::env::temp_dir().join(format!("allyabase-transport-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    assert!(matches!(Contract::new(FixtureMode::Replay, BASE_URL, &path, canned(json!({}))), Err(FixtureError::Missing(_))));
+
+    let v1 = canned(json!({ "uuid": UUID, "mp": 1000 }));
+    let contract = Contract::new(FixtureMode::Record, BASE_URL, &path, v1).expect("record");
+    contract.transport().send(get_user("1")).await.expect("get user");
+    assert_eq!(contract.finish().expect("save"), vec![]);
+
+    let contract = Contract::new(FixtureMode::Replay, BASE_URL, &path, canned(json!({}))).expect("replay");
+    contract.transport().send(get_user("2")).await.expect("get user");
+    assert_eq!(contract.finish().expect("replay"), vec![]);
+
+    let v2 = canned(json!({ "uuid": UUID, "mp": "1000" }));
+    let contract = Contract::new(FixtureMode::Verify, BASE_URL, &path, v2).expect("verify");
+    contract.transport().send(get_user("3")).await.expect("get user");
+    contract.transport().send(grant(json!(1))).await.expect("grant");
+    assert_eq!(contract.finish().expect("verify"), vec![
+        ShapeDiff::Changed { path: "GET user/:uuid $.mp".to_string(), expected: "number", found: "string" },
+        ShapeDiff::Added { path: "POST user/:uuid/grant".to_string(), found: "exchange" }
+    ]);
+
+    assert_eq!(Fixture::load(&path).expect("fixture").exchanges.len(), 1);
+    std::fs::remove_file(&path).expect("remove fixture");
+
+    assert_eq!(FixtureMode::parse(Some("verify")).expect("mode"), FixtureMode::Verify);
+    assert!(FixtureMode::parse(Some("playback")).is_err());
+}
+
+#[actix_rt::test]
+async fn test_loopback() {
+    let loopback = LoopbackTransport::new(|request| match request.method {
+        Method::Get => HttpResponse::json_body(200, &json!({ "uuid": UUID })),
+        _ => HttpResponse::json_body(403, &json!({ "error": "auth error" }))
+    });
+
+    let response = loopback.send(get_user("1")).await.expect("get user");
+    assert!(response.is_success());
+    assert_eq!(response.header("Content-Type"), Some("application/json"));
+    assert_eq!(response.json::<Value>().expect("user")["uuid"], json!(UUID));
+
+    let response = loopback.send(grant(json!(1))).await.expect("grant");
+    assert_eq!((response.status, response.text()), (403, r#"{"error":"auth error"}"#.to_string()));
+    assert_eq!(loopback.requests(), vec![get_user("1"), grant(json!(1))]);
+}
+
+#[test]
+fn test_shared_pool() {
+    assert!(Arc::ptr_eq(&PooledTransport::shared(), &PooledTransport::shared()));
+    assert!(PooledTransport::new(4, Duration::from_secs(1)).is_ok());
+
+    // Clients on one runtime share a pool; another runtime gets its own.
+    let runtime = || tokio::runtime::Builder::new_current_thread().build().expect("runtime");
+    let first = runtime();
+    let pool = first.block_on(async { PooledTransport::shared().current() });
+    assert!(Arc::ptr_eq(&pool, &first.block_on(async { PooledTransport::shared().current() })));
+    assert!(!Arc::ptr_eq(&pool, &runtime().block_on(async { PooledTransport::shared().current() })));
+}
+
+// A node that answers 503 to the first `failures` requests.
+fn flaky(failures: u32) -> LoopbackTransport {
+    let count = AtomicU32::new(0);
+    LoopbackTransport::new(move |_| match count.fetch_add(1, Ordering::SeqCst) < failures {
+        true => HttpResponse::json_body(503, &json!({ "error": "unavailable" })),
+        false => HttpResponse::json_body(200, &json!({ "uuid": UUID }))
+    })
+}
+
+fn quick() -> RetryPolicy {
+    RetryPolicy { base_delay: Duration::from_millis(1), ..Default::default() }
+}
+
+#[actix_rt::test]
+async fn test_retry() {
+    let policy = RetryPolicy { base_delay: Duration::from_millis(100), max_delay: Duration::from_millis(300), ..Default::default() };
+    assert_eq!((policy.delay(1), policy.delay(2), policy.delay(3)), (Duration::from_millis(100), Duration::from_millis(200), Duration::from_millis(300)));
+
+    assert!(is_retryable(&Ok(HttpResponse::json_body(503, &json!({})))));
+    assert!(!is_retryable(&Ok(HttpResponse::json_body(900, &json!({ "success": false })))));
+    assert!(!is_retryable(&Err(TransportError::Replay("no".to_string()))));
+
+    // Every attempt is signed afresh.
+    let node = flaky(2);
+    let mut signed = 0;
+    let response = send_signed(&node, &quick(), &Clock::new(), Idempotency::Idempotent, |_| {
+        signed += 1;
+        get_user(&signed.to_string())
+    }).await.expect("get user");
+    assert_eq!(response.status, 200);
+    assert_eq!(node.requests(), vec![get_user("1"), get_user("2"), get_user("3")]);
+
+    let node = flaky(3);
+    assert_eq!(send_signed(&node, &quick(), &Clock::new(), Idempotency::Idempotent, |_| get_user("1")).await.expect("get user").status, 503);
+    assert_eq!(node.requests().len(), 3);
+
+    let node = flaky(1);
+    assert_eq!(send_signed(&node, &RetryPolicy::never(), &Clock::new(), Idempotency::NonIdempotent, |_| grant(json!(1))).await.expect("grant").status, 503);
+    let node = flaky(1);
+    match send_signed(&node, &quick(), &Clock::new(), Idempotency::NonIdempotent, |_| grant(json!(1))).await {
+        Err(TransportError::Idempotency(message)) => assert!(message.starts_with("POST http://localhost:3006/user/"), "{}", message),
+        other => panic!("expected an idempotency error, got {:?}", other)
+    }
+    assert_eq!(node.requests().len(), 1);
+
+    let node = flaky(1);
+    assert_eq!(send_signed(&node, &quick().with_non_idempotent(), &Clock::new(), Idempotency::NonIdempotent, |_| grant(json!(1))).await.expect("grant").status, 200);
+    assert_eq!(node.requests().len(), 2);
+
+    // Nothing listens on port 9, so every attempt fails to connect.
+    let mut attempts = 0;
+    let result = send_signed(&ReqwestTransport::new(), &quick(), &Clock::new(), Idempotency::Idempotent, |_| {
+        attempts += 1;
+        HttpRequest::new(Method::Get, "http://127.0.0.1:9/", None)
+    }).await;
+    assert!(matches!(result, Err(TransportError::Http(ref error)) if error.is_connect()), "{:?}", result);
+    assert_eq!(attempts, 3);
+}
+
+// A node whose clock runs `drift` milliseconds ahead, refusing timestamps
+// more than five minutes off it.
+fn skewed(drift: i64) -> LoopbackTransport {
+    LoopbackTransport::new(move |request| {
+        let now = Clock::local_millis() + drift;
+        let date = std::time::UNIX_EPOCH + Duration::from_millis(now as u64);
+        let timestamp: i64 = request.url.split("timestamp=").nth(1).and_then(|rest| rest.split('&').next()).and_then(|timestamp| timestamp.parse().ok()).unwrap_or(now);
+        let body = match (now - timestamp).abs() > 300000 {
+            true => json!({ "error": "no time like the present" }),
+            false => json!({ "uuid": UUID })
+        };
+        HttpResponse { headers: vec![("date".to_string(), httpdate::fmt_http_date(date))], ..HttpResponse::json_body(200, &body) }
+    })
+}
+
+#[actix_rt::test]
+async fn test_clock_skew() {
+    let clock = Clock::new();
+    let node = skewed(-600000);
+    match send_signed(&node, &quick(), &clock, Idempotency::Idempotent, get_user).await {
+        Err(TransportError::ClockSkew { drift: Some(drift) }) => assert!((drift + 600000).abs() < 2000, "{}", drift),
+        other => panic!("expected clock skew, got {:?}", other)
+    }
+
+    assert_eq!(
+        TransportError::ClockSkew { drift: Some(61234) }.to_string(),
+        "the server rejected the timestamp: this clock is 61.2s behind the server, which later requests correct for"
+    );
+
+    // The next request signs with the server's time.
+    assert!((clock.offset() + 600000).abs() < 2000);
+    let response = send_signed(&node, &quick(), &clock, Idempotency::Idempotent, get_user).await.expect("get user");
+    assert_eq!(response.json::<Value>().expect("user")["uuid"], json!(UUID));
+
+    // Small drift isn't worth correcting, and a probe measures it up front.
+    let clock = Clock::new();
+    assert!(clock.probe(&skewed(500), BASE_URL).await.expect("probe").is_some());
+    assert_eq!(clock.offset(), 0);
+    assert!((clock.probe(&skewed(90000), BASE_URL).await.expect("probe").expect("drift") - 90000).abs() < 2000);
+    assert!((clock.offset() - 90000).abs() < 2000);
+
+    let rejected = HttpResponse::json_body(200, &json!({ "error": "no time like the present" }));
+    assert!(Clock::is_rejected(&rejected));
+    assert!(!Clock::is_rejected(&HttpResponse::json_body(403, &json!({ "error": "auth error" }))));
+    assert!(matches!(send_signed(&LoopbackTransport::new(move |_| rejected.clone()), &quick(), &Clock::new(), Idempotency::Idempotent, get_user).await, Err(TransportError::ClockSkew { drift: None })));
+}
+
+// Keeps one line per event, up to `level`, prefixed with the fields of the
+// span it happened in.
+struct Capture {
+    level: Level,
+    spans: Mutex<Vec<String>>,
+    entered: Mutex<Vec<usize>>,
+    lines: Arc<Mutex<Vec<String>>>
+}
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push_str(&format!(" {}={}", field.name(), value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Capture {
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= self.level
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut fields = Fields(attributes.metadata().name().to_string());
+        attributes.record(&mut fields);
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(fields.0);
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut fields = Fields(String::new());
+        values.record(&mut fields);
+        self.spans.lock().unwrap()[span.into_u64() as usize - 1].push_str(&fields.0);
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(event.metadata().level().to_string());
+        event.record(&mut fields);
+        let span = self.entered.lock().unwrap().last().map(|id| self.spans.lock().unwrap()[id - 1].clone()).unwrap_or_default();
+        self.lines.lock().unwrap().push(format!("{}: {}", span, fields.0));
+    }
+
+    fn enter(&self, span: &Id) {
+        self.entered.lock().unwrap().push(span.into_u64() as usize);
+    }
+
+    fn exit(&self, _span: &Id) {
+        self.entered.lock().unwrap().pop();
+    }
+}
+
+fn capture(level: Level) -> (DefaultGuard, Arc<Mutex<Vec<String>>>) {
+    let lines = Arc::new(Mutex::new(vec![]));
+    let capture = Capture { level, spans: Mutex::new(vec![]), entered: Mutex::new(vec![]), lines: lines.clone() };
+    (tracing::subscriber::set_default(capture), lines)
+}
+
+#[test]
+fn test_redact() {
+    let spell = json!({ "casterSignature": "abc", "gateways": [{ "signature": "def", "uuid": UUID }], "bdo": { "secret": 1 } });
+    assert_eq!(redact(&spell), json!({ "casterSignature": "<redacted>", "gateways": [{ "signature": "<redacted>", "uuid": UUID }], "bdo": "<redacted>" }));
+    assert_eq!(redact_url(&get_user("1").url), format!("{}user/{}?timestamp=1&signature=<redacted>", BASE_URL, UUID));
+    assert_eq!(redact_url(BASE_URL), BASE_URL);
+}
+
+#[actix_rt::test]
+async fn test_tracing() {
+    let node = LoopbackTransport::new(|_| HttpResponse::json_body(200, &json!({ "uuid": UUID, "bdo": { "secret": "hush" } })));
+
+    let (guard, lines) = capture(Level::DEBUG);
+    TracingTransport::new("bdo", BASE_URL, Payloads::Redacted, &node).send(grant(json!(1))).await.expect("grant");
+    drop(guard);
+    let lines = lines.lock().unwrap().clone();
+    assert_eq!(lines.len(), 3, "{:?}", lines);
+    assert!(lines.iter().all(|line| line.starts_with("allyabase service=bdo method=POST route=user/:uuid/grant")), "{:?}", lines);
+    assert!(lines[0].ends_with(r#"DEBUG message=request url=http://localhost:3006/user/5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f/grant body={"amount":1,"destinationUUID":"5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f","signature":"<redacted>","timestamp":"1700000000000"}"#), "{}", lines[0]);
+    assert!(lines[1].contains(": INFO message=response status=200 latency_ms="), "{}", lines[1]);
+    assert!(lines[2].ends_with(r#"DEBUG message=response body body={"bdo":"<redacted>","uuid":"5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f"}"#), "{}", lines[2]);
+
+    // Full payloads are opt-in.
+    let (guard, lines) = capture(Level::DEBUG);
+    TracingTransport::new("bdo", BASE_URL, Payloads::Full, &node).send(get_user("1")).await.expect("get user");
+    drop(guard);
+    let lines = lines.lock().unwrap().clone();
+    assert!(lines[0].contains("signature=abc1"), "{}", lines[0]);
+    assert!(lines[2].contains(r#""secret":"hush""#), "{}", lines[2]);
+
+    // Production logs at info see only the span and its status.
+    let (guard, lines) = capture(Level::INFO);
+    TracingTransport::new("bdo", BASE_URL, Payloads::Full, &node).send(get_user("1")).await.expect("get user");
+    drop(guard);
+    let lines = lines.lock().unwrap().clone();
+    assert_eq!(lines.len(), 1, "{:?}", lines);
+    assert!(!lines[0].contains("abc1") && !lines[0].contains("hush"), "{}", lines[0]);
+}
@@ -0,0 +1,129 @@
+use serde_json::Value;
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::{Instrument, Level, Span};
+use crate::fixtures::split_url;
+use crate::{HttpRequest, HttpResponse, Transport, TransportFuture};
+
+/// Fields whose values stay out of the logs unless a client opts into
+/// `Payloads::Full`: what a caller signed, and what they stored in bdo.
+pub const REDACTED_FIELDS: [&str; 3] = ["signature", "casterSignature", "bdo"];
+
+/// How much of each request and response the clients log at debug level.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Payloads {
+    /// Bodies and queries with `REDACTED_FIELDS` blanked out.
+    #[default]
+    Redacted,
+    /// Everything, signatures included. Only for debugging against a test node.
+    Full
+}
+
+/// `value` with every `REDACTED_FIELDS` value replaced, however deep.
+pub fn redact(value: &Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| match REDACTED_FIELDS.contains(&key.as_str()) {
+                    true => (key.clone(), Value::String("<redacted>".to_string())),
+                    false => (key.clone(), redact(value))
+                })
+                .collect()
+        ),
+        Value::Array(values) => Value::Array(values.iter().map(redact).collect()),
+        value => value.clone()
+    }
+}
+
+/// `url` with the values of `REDACTED_FIELDS` in its query replaced.
+pub fn redact_url(url: &str) -> String {
+    let Some((path, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let query: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if REDACTED_FIELDS.contains(&key) => format!("{}=<redacted>", key),
+            _ => pair.to_string()
+        })
+        .collect();
+
+    format!("{}?{}", path, query.join("&"))
+}
+
+/// Sends through `inner` inside an `allyabase` span carrying the service,
+/// method, route, status and latency of each request. At debug level it also
+/// logs the payloads, as `payloads` allows.
+pub struct TracingTransport<'a> {
+    service: &'static str,
+    base_url: &'a str,
+    payloads: Payloads,
+    inner: &'a dyn Transport
+}
+
+impl<'a> TracingTransport<'a> {
+    /// Routes are logged relative to `base_url`, with uuids and public keys
+    /// templated out: `user/:uuid/bdo`.
+    pub fn new(service: &'static str, base_url: &'a str, payloads: Payloads, inner: &'a dyn Transport) -> Self {
+        TracingTransport { service, base_url, payloads, inner }
+    }
+
+    fn show(&self, body: &Value) -> String {
+        match self.payloads {
+            Payloads::Redacted => redact(body).to_string(),
+            Payloads::Full => body.to_string()
+        }
+    }
+
+    fn show_response(&self, response: &HttpResponse) -> String {
+        match (response.json::<Value>(), self.payloads) {
+            (Ok(body), _) => self.show(&body),
+            (Err(_), Payloads::Full) => response.text(),
+            (Err(_), Payloads::Redacted) => format!("<{} bytes>", response.body.len())
+        }
+    }
+}
+
+impl Transport for TracingTransport<'_> {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let (route, _) = split_url(self.base_url, &request.url);
+        let span = tracing::info_span!(
+            "allyabase",
+            service = self.service,
+            method = %request.method,
+            route = %route,
+            status = Empty,
+            latency_ms = Empty
+        );
+
+        Box::pin(async move {
+            if tracing::enabled!(Level::DEBUG) {
+                let url = match self.payloads {
+                    Payloads::Redacted => redact_url(&request.url),
+                    Payloads::Full => request.url.clone()
+                };
+                let body = request.body.as_ref().map(|body| self.show(body)).unwrap_or_default();
+                tracing::debug!(url = %url, body = %body, "request");
+            }
+
+            let started = Instant::now();
+            let result = self.inner.send(request).await;
+            let latency_ms = started.elapsed().as_millis() as u64;
+            Span::current().record("latency_ms", latency_ms);
+
+            match result {
+                Ok(ref response) => {
+                    Span::current().record("status", response.status);
+                    tracing::info!(status = response.status, latency_ms, "response");
+                    if tracing::enabled!(Level::DEBUG) {
+                        tracing::debug!(body = %self.show_response(response), "response body");
+                    }
+                },
+                Err(ref error) => tracing::warn!(error = %error, latency_ms, "request failed")
+            }
+
+            result
+        }.instrument(span))
+    }
+}
@@ -1,15 +1,17 @@
-use crate::{AddieUser, Addie, Gateway, Nineum, Spell, SpellResult, SuccessResult, PaymentIntent};
-use sessionless::hex::IntoHex;
-use std::collections::HashMap;
-use serde_json::json;
-use serde_json::Value;
+use crate::{AddieUser, Addie, Payee, PaymentIntent, Processor, SuccessResult};
 use rand::Rng;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::path_based("http://nginx:80")).expect("service directory")
+}
 
 #[actix_rt::test]
 async fn test_addie() {
 
     let mut saved_user: Option<AddieUser>;
-    let addie = Addie::new(Some("http://nginx:80/addie/".to_string()));
+    let addie = Addie::with_directory(&directory());
 
     async fn create_user(addie: &Addie) -> Option<AddieUser> {
 	let result = addie.create_user().await;
@@ -72,7 +74,7 @@ async fn test_addie() {
     }
 
     async fn get_payment_intent(addie: &Addie, saved_user: &AddieUser) -> Option<PaymentIntent> {
-        let payees: Vec<String> = Vec::new();
+        let payees: Vec<Payee> = Vec::new();
         let result = addie.get_payment_intent(&saved_user.uuid, "stripe", &2000, "USD", &payees).await;
 
         match result {
@@ -115,10 +117,7 @@ async fn test_addie() {
 
         match result {
             Ok(success) => {
-                assert_eq!(
-                    success.success,
-                    true
-                );
+                assert!(success.success);
                 Some(success)
             }
             Err(error) => {
@@ -138,29 +137,55 @@ async fn test_addie() {
     }
 
     if let Some(ref user) = saved_user {
-	Some(add_processor_account(&addie, user).await.expect("add processor"));
+	add_processor_account(&addie, user).await.expect("add processor");
         saved_user = Some(get_user_by_uuid(&addie, user).await.expect("adding processor account"));
     } else {
 	panic!("Failed to add processor account");
     }
 
     if let Some(ref user) = saved_user {
-        Some(get_payment_intent(&addie, user).await.expect("get payment intent"));
+        get_payment_intent(&addie, user).await.expect("get payment intent");
         saved_user = Some(get_user_by_uuid(&addie, user).await.expect("getting payment intent"));
     } else {
         panic!("Failed to get payment intent");
     }
 
     if let Some(ref user) = saved_user {
-        Some(get_payment_intent_without_splits(&addie, user).await.expect("get payment intent without splits"));
+        get_payment_intent_without_splits(&addie, user).await.expect("get payment intent without splits");
         saved_user = Some(get_user_by_uuid(&addie, user).await.expect("getting payment intent"));
     } else {
         panic!("Failed to get payment intent");
     }
     
     if let Some(ref user) = saved_user {
-	delete_user(&addie, &user).await;
+	delete_user(&addie, user).await;
     } else {
 	panic!("Failed to delete user");
     }     
 }
+
+#[actix_rt::test]
+async fn test_payee_splits() {
+    let addie = Addie::new(Some("http://127.0.0.1:9/".to_string()));
+    let payees = vec![
+        Payee { pub_key: "031c4d981632f6d2d1171c0a8af6242b521954ae64d10528fd74fa4a9aeb9419ea".to_string(), amount: 500 },
+        Payee { pub_key: "026fa1a11b1810b44e1dacaadcd2a5d080e43654a5ac96af04e9d2af2358d38ccd".to_string(), amount: 4500 }
+    ];
+
+    let result = addie.get_payment_intent("2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a", "stripe", &4999, "USD", &payees).await;
+    assert!(result.unwrap_err().to_string().contains("payees are owed 5000"));
+}
+
+#[test]
+fn test_processor_accounts() {
+    let user: AddieUser = serde_json::from_value(serde_json::json!({
+        "uuid": "2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a",
+        "pubKey": "031c4d981632f6d2d1171c0a8af6242b521954ae64d10528fd74fa4a9aeb9419ea",
+        "stripeAccountId": "acct_1PxExampleAcct"
+    })).unwrap();
+
+    let accounts = user.processor_accounts();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].processor, Processor::Stripe);
+    assert_eq!(accounts[0].account_id, "acct_1PxExampleAcct");
+}
@@ -0,0 +1,193 @@
+pub mod structs;
+
+#[cfg(test)]
+mod tests;
+
+use reqwest::{Client, Response};
+use serde_json::json;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::option::Option;
+pub use crate::structs::{AddieUser, Payee, PaymentIntent, Processor, ProcessorAccount, SuccessResult};
+pub use service_directory_rs::{Service, ServiceDirectory};
+
+pub struct Addie {
+    base_url: String,
+    client: Client,
+    pub sessionless: Sessionless,
+}
+
+impl Addie {
+    pub fn new(base_url: Option<String>) -> Self {
+        Self::from_sessionless(base_url, Sessionless::new())
+    }
+
+    /// Points the client at addie in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory) -> Self {
+        Self::new(Some(directory.url(Service::Addie)))
+    }
+
+    pub fn from_sessionless(base_url: Option<String>, sessionless: Sessionless) -> Self {
+        Addie {
+            base_url: base_url.unwrap_or("https://dev.addie.allyabase.com/".to_string()),
+            client: Client::new(),
+            sessionless,
+        }
+    }
+
+    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.client.get(url).send().await
+    }
+
+    async fn post(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .post(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn put(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .put(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn delete(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .delete(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    fn get_timestamp() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis()
+            .to_string()
+    }
+
+    pub async fn create_user(&self) -> Result<AddieUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let pub_key = self.sessionless.public_key().to_hex();
+        let signature = self.sessionless.sign(format!("{}{}", timestamp, pub_key)).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "pubKey": pub_key,
+            "signature": signature
+        });
+
+        let url = format!("{}user/create", self.base_url);
+        let res = self.put(&url, payload).await?;
+        let user: AddieUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    pub async fn get_user_by_uuid(&self, uuid: &str) -> Result<AddieUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let url = format!("{}user/{}?timestamp={}&signature={}", self.base_url, uuid, timestamp, signature);
+        let res = self.get(&url).await?;
+        let user: AddieUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    /// Connects a processor account to the user. Addie currently only onboards
+    /// Stripe accounts this way.
+    pub async fn add_processor_account(&self, uuid: &str, name: &str, email: &str) -> Result<AddieUser, Box<dyn std::error::Error>> {
+        self.add_account_for_processor(uuid, &Processor::Stripe, name, email).await
+    }
+
+    pub async fn add_account_for_processor(&self, uuid: &str, processor: &Processor, name: &str, email: &str) -> Result<AddieUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}{}", timestamp, uuid, name, email);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "name": name,
+            "email": email,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}/processor/{}", self.base_url, uuid, processor.as_str());
+        let res = self.put(&url, payload).await?;
+        let user: AddieUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    /// Creates a payment intent whose `amount` is split between `payees`. The
+    /// payees' shares can't add up to more than the whole.
+    pub async fn get_payment_intent(&self, uuid: &str, processor: &str, amount: &u32, currency: &str, payees: &[Payee]) -> Result<PaymentIntent, Box<dyn std::error::Error>> {
+        let split: u64 = payees.iter().map(|payee| payee.amount as u64).sum();
+        if split > *amount as u64 {
+            return Err(format!("payees are owed {} but the payment is only {}", split, amount).into());
+        }
+
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}{}", timestamp, uuid, amount, currency);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "amount": amount,
+            "currency": currency,
+            "payees": payees,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}/processor/{}/intent", self.base_url, uuid, processor);
+        let res = self.post(&url, payload).await?;
+        let intent: PaymentIntent = res.json().await?;
+
+        Ok(intent)
+    }
+
+    pub async fn get_payment_intent_without_splits(&self, uuid: &str, processor: &str, amount: &u32, currency: &str) -> Result<PaymentIntent, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}{}", timestamp, uuid, amount, currency);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "amount": amount,
+            "currency": currency,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}/processor/{}/intent-without-splits", self.base_url, uuid, processor);
+        let res = self.post(&url, payload).await?;
+        let intent: PaymentIntent = res.json().await?;
+
+        Ok(intent)
+    }
+
+    pub async fn delete_user(&self, uuid: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+          "timestamp": timestamp,
+          "uuid": uuid,
+          "signature": signature
+        });
+
+        let url = format!("{}user/{}", self.base_url, uuid);
+        let res = self.delete(&url, payload).await?;
+
+        // Addie answers a delete with an empty body, so the status is the result.
+        Ok(SuccessResult { success: res.status().is_success() })
+    }
+}
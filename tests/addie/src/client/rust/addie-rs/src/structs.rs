@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all="lowercase")]
+pub enum Processor {
+    Stripe,
+    Square
+}
+
+impl Processor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Processor::Stripe => "stripe",
+            Processor::Square => "square"
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct ProcessorAccount {
+    pub processor: Processor,
+    pub account_id: String
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct AddieUser {
+    pub uuid: String,
+    pub pub_key: Option<String>,
+    pub stripe_account_id: Option<String>,
+    pub square_account_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+impl AddieUser {
+    /// The processor accounts this user has connected.
+    pub fn processor_accounts(&self) -> Vec<ProcessorAccount> {
+        [(Processor::Stripe, &self.stripe_account_id), (Processor::Square, &self.square_account_id)]
+            .into_iter()
+            .filter_map(|(processor, account_id)| account_id.as_ref().map(|account_id| ProcessorAccount {
+                processor,
+                account_id: account_id.clone()
+            }))
+            .collect()
+    }
+}
+
+/// One recipient's share of a split payment, in the currency's smallest unit.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct Payee {
+    pub pub_key: String,
+    pub amount: u32
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct PaymentIntent {
+    pub payment_intent: String,
+    pub ephemeral_key: Option<String>,
+    pub customer: String,
+    pub publishable_key: Option<String>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuccessResult {
+    pub success: bool
+}
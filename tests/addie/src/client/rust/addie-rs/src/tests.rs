@@ -0,0 +1,191 @@
+use crate::{AddieUser, Addie, Payee, PaymentIntent, Processor, SuccessResult};
+use rand::Rng;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::direct_ports("localhost")).expect("service directory")
+}
+
+#[actix_rt::test]
+async fn test_addie() {
+
+    let mut saved_user: Option<AddieUser>;
+    let addie = Addie::with_directory(&directory());
+
+    async fn create_user(addie: &Addie) -> Option<AddieUser> {
+	let result = addie.create_user().await;
+
+	match result {
+	    Ok(user) => {
+		println!("Successfully got AddieUser: {}", user.uuid);
+		assert_eq!(
+		    user.uuid.len(),
+		    36
+		);
+                Some(user)
+	    },
+	    Err(error) => {
+		eprintln!("Error occurred create_user: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	}
+    }
+
+    async fn get_user_by_uuid(addie: &Addie, saved_user: &AddieUser) -> Option<AddieUser> {
+	let result = addie.get_user_by_uuid(&saved_user.uuid).await; 
+     
+	match result {
+	    Ok(user) => {
+		assert_eq!(
+		    user.uuid.len(),
+		    36
+		);
+                Some(user)
+	    }
+	    Err(error) => {
+		eprintln!("Error occurred get_user: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	} 
+    }
+
+    async fn add_processor_account(addie: &Addie, saved_user: &AddieUser) -> Option<AddieUser> {
+        let email_seed: u32 = rand::thread_rng().gen_range(0..100000);
+        let email: String = format!("zach+{}@planetnine.app", &email_seed);
+        let result = addie.add_processor_account(&saved_user.uuid, "Foo", &email).await;
+
+        match result {
+            Ok(user) => {
+                assert_eq!(
+                    user.uuid.len(),
+                    36
+                );
+                Some(user)
+            }
+            Err(error) => {
+                eprintln!("Error occurred get_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        } 
+    }
+
+    async fn get_payment_intent(addie: &Addie, saved_user: &AddieUser) -> Option<PaymentIntent> {
+        let payees: Vec<Payee> = Vec::new();
+        let result = addie.get_payment_intent(&saved_user.uuid, "stripe", &2000, "USD", &payees).await;
+
+        match result {
+            Ok(intent) => {
+                assert_eq!(
+                    intent.customer.len(),
+                    18
+                );
+                Some(intent)
+            }
+            Err(error) => {
+                eprintln!("Error occurred get_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn get_payment_intent_without_splits(addie: &Addie, saved_user: &AddieUser) -> Option<PaymentIntent> {
+        let result = addie.get_payment_intent_without_splits(&saved_user.uuid, "stripe", &2000, "USD").await;
+
+        match result {
+            Ok(intent) => {
+                assert_eq!(
+                    intent.customer.len(),
+                    18
+                );
+                Some(intent)
+            }
+            Err(error) => {
+                eprintln!("Error occurred get_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn delete_user(addie: &Addie, saved_user: &AddieUser) -> Option<SuccessResult> {
+        let result = addie.delete_user(&saved_user.uuid).await;
+
+        match result {
+            Ok(success) => {
+                assert!(success.success);
+                Some(success)
+            }
+            Err(error) => {
+                eprintln!("Error occurred delete: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    saved_user = Some(create_user(&addie).await.expect("user"));
+
+    if let Some(ref user) = saved_user {
+        saved_user = Some(get_user_by_uuid(&addie, user).await.expect("get user 1"));
+    } else {
+        panic!("Failed to get user");
+    }
+
+    if let Some(ref user) = saved_user {
+	add_processor_account(&addie, user).await.expect("add processor");
+        saved_user = Some(get_user_by_uuid(&addie, user).await.expect("adding processor account"));
+    } else {
+	panic!("Failed to add processor account");
+    }
+
+    if let Some(ref user) = saved_user {
+        get_payment_intent(&addie, user).await.expect("get payment intent");
+        saved_user = Some(get_user_by_uuid(&addie, user).await.expect("getting payment intent"));
+    } else {
+        panic!("Failed to get payment intent");
+    }
+
+    if let Some(ref user) = saved_user {
+        get_payment_intent_without_splits(&addie, user).await.expect("get payment intent without splits");
+        saved_user = Some(get_user_by_uuid(&addie, user).await.expect("getting payment intent"));
+    } else {
+        panic!("Failed to get payment intent");
+    }
+    
+    if let Some(ref user) = saved_user {
+	delete_user(&addie, user).await;
+    } else {
+	panic!("Failed to delete user");
+    }     
+}
+
+#[actix_rt::test]
+async fn test_payee_splits() {
+    let addie = Addie::new(Some("http://127.0.0.1:9/".to_string()));
+    let payees = vec![
+        Payee { pub_key: "031c4d981632f6d2d1171c0a8af6242b521954ae64d10528fd74fa4a9aeb9419ea".to_string(), amount: 500 },
+        Payee { pub_key: "026fa1a11b1810b44e1dacaadcd2a5d080e43654a5ac96af04e9d2af2358d38ccd".to_string(), amount: 4500 }
+    ];
+
+    let result = addie.get_payment_intent("2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a", "stripe", &4999, "USD", &payees).await;
+    assert!(result.unwrap_err().to_string().contains("payees are owed 5000"));
+}
+
+#[test]
+fn test_processor_accounts() {
+    let user: AddieUser = serde_json::from_value(serde_json::json!({
+        "uuid": "2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a",
+        "pubKey": "031c4d981632f6d2d1171c0a8af6242b521954ae64d10528fd74fa4a9aeb9419ea",
+        "stripeAccountId": "acct_1PxExampleAcct"
+    })).unwrap();
+
+    let accounts = user.processor_accounts();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].processor, Processor::Stripe);
+    assert_eq!(accounts[0].account_id, "acct_1PxExampleAcct");
+}
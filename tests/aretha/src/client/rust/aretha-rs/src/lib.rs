@@ -0,0 +1,118 @@
+pub mod structs;
+
+#[cfg(test)]
+mod tests;
+
+use serde_json::json;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::option::Option;
+use fount_rs::{Fount, FountUser};
+use fount_rs::nineum::DecodedNineum;
+use crate::structs::{check_flavor, check_galaxy};
+pub use crate::structs::{ArethaUser, GalaxyAssignment, SuccessResult, Ticket, TicketError};
+
+/// Aretha is only reached through MAGIC, so every call here is a spell cast
+/// through `fount` by the fount user at `caster_uuid`, priced by the
+/// spellbooks `fount` was given. Tickets themselves live in fount as nineum.
+pub struct Aretha {
+    pub sessionless: Sessionless,
+}
+
+impl Aretha {
+    pub fn new(sessionless: Option<Sessionless>) -> Self {
+        Aretha {
+            sessionless: sessionless.unwrap_or(Sessionless::new()),
+        }
+    }
+
+    /// Creates an aretha user for this client's key with `arethaUserCreate`.
+    pub async fn create_user(&self, fount: &Fount, caster_uuid: &str, ordinal: u32) -> Result<ArethaUser, Box<dyn std::error::Error>> {
+        let components = json!({
+            "pubKey": self.sessionless.public_key().to_hex()
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "arethaUserCreate", ordinal, components).await?;
+
+        result.field("user")
+    }
+
+    /// Gives the fount user at `uuid` control of `galaxy`, the eight digit
+    /// part of a nineum id that tickets are minted in.
+    pub async fn set_galaxy(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str, galaxy: &str) -> Result<GalaxyAssignment, Box<dyn std::error::Error>> {
+        check_galaxy(galaxy)?;
+
+        let components = json!({
+            "uuid": uuid,
+            "galaxy": galaxy
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "arethaUserGalaxy", ordinal, components).await?.succeeded()?;
+        let data = result.extra.get("data").cloned().unwrap_or(json!({}));
+
+        Ok(serde_json::from_value(data)?)
+    }
+
+    /// Grants the fount user at `uuid` the administrative nineum that lets
+    /// them mint tickets in their galaxy.
+    pub async fn grant_admin(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let components = json!({
+            "uuid": uuid
+        });
+        fount.cast_from_spellbook(caster_uuid, "arethaUserGrant", ordinal, components).await?.succeeded()?;
+
+        Ok(SuccessResult { success: true })
+    }
+
+    /// Mints `quantity` tickets of `flavor` for the user at `uuid`.
+    pub async fn mint_tickets(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str, flavor: &str, quantity: u32) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        check_flavor(flavor)?;
+        if quantity == 0 {
+            return Err(TicketError::NoTickets.into());
+        }
+
+        let components = json!({
+            "uuid": uuid,
+            "flavor": flavor,
+            "quantity": quantity
+        });
+        fount.cast_from_spellbook(caster_uuid, "arethaUserTickets", ordinal, components).await?.succeeded()?;
+
+        Ok(SuccessResult { success: true })
+    }
+
+    /// The tickets of `flavor` held by the fount user at `uuid`, or all their
+    /// nineum when `flavor` is `None`. `fount` has to hold that user's key.
+    pub async fn tickets(&self, fount: &Fount, uuid: &str, flavor: Option<&str>) -> Result<Vec<Ticket>, Box<dyn std::error::Error>> {
+        if let Some(flavor) = flavor {
+            check_flavor(flavor)?;
+        }
+        let nineum = fount.get_nineum(uuid).await?;
+
+        Ok(tickets_of_flavor(&nineum.decode()?, flavor))
+    }
+
+    /// Whether the user at `uuid` holds `ticket`.
+    pub async fn holds(&self, fount: &Fount, uuid: &str, ticket: &Ticket) -> Result<bool, Box<dyn std::error::Error>> {
+        let nineum = fount.get_nineum(uuid).await?;
+
+        Ok(nineum.nineum.iter().any(|id| id == ticket.id()))
+    }
+
+    /// Hands `tickets` from the fount user at `uuid` to `destination_uuid`.
+    pub async fn grant_tickets(&self, fount: &Fount, uuid: &str, destination_uuid: &str, tickets: &[Ticket]) -> Result<FountUser, Box<dyn std::error::Error>> {
+        let held = fount.get_nineum(uuid).await?;
+        if let Some(missing) = tickets.iter().find(|ticket| !held.nineum.iter().any(|id| id == ticket.id())) {
+            return Err(TicketError::NotHeld(missing.id().to_string()).into());
+        }
+
+        let ids = tickets.iter().map(|ticket| ticket.id().to_string()).collect();
+        fount.transfer_nineum(uuid, destination_uuid, &ids, &0, "USD").await
+    }
+}
+
+pub(crate) fn tickets_of_flavor(nineum: &[DecodedNineum], flavor: Option<&str>) -> Vec<Ticket> {
+    nineum
+        .iter()
+        .filter(|decoded| flavor.is_none_or(|flavor| decoded.flavor() == flavor.to_lowercase()))
+        .map(|decoded| Ticket { nineum: decoded.clone() })
+        .collect()
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use serde_json::Value;
+use fount_rs::nineum::DecodedNineum;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct ArethaUser {
+    pub uuid: String,
+    pub pub_key: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// A ticket is a nineum of the event's flavor; whoever holds it in fount
+/// holds the ticket.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ticket {
+    pub nineum: DecodedNineum
+}
+
+impl Ticket {
+    pub fn id(&self) -> &str {
+        &self.nineum.id
+    }
+
+    pub fn flavor(&self) -> String {
+        self.nineum.flavor()
+    }
+
+    pub fn galaxy(&self) -> &str {
+        &self.nineum.galaxy
+    }
+
+    /// Tickets of a flavor are numbered in the order they were minted.
+    pub fn ordinal(&self) -> &str {
+        &self.nineum.ordinal
+    }
+}
+
+/// What `arethaUserGalaxy` reports back in `data`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct GalaxyAssignment {
+    pub galaxy: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TicketError {
+    InvalidFlavor(String),
+    InvalidGalaxy(String),
+    NoTickets,
+    NotHeld(String)
+}
+
+impl fmt::Display for TicketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TicketError::InvalidFlavor(flavor) => write!(f, "flavor must be twelve hex digits, got {}", flavor),
+            TicketError::InvalidGalaxy(galaxy) => write!(f, "galaxy must be eight hex digits, got {}", galaxy),
+            TicketError::NoTickets => write!(f, "at least one ticket has to be minted"),
+            TicketError::NotHeld(id) => write!(f, "ticket {} is not held by the sender", id)
+        }
+    }
+}
+
+impl std::error::Error for TicketError {}
+
+fn is_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub(crate) fn check_flavor(flavor: &str) -> Result<(), TicketError> {
+    if is_hex(flavor, 12) { Ok(()) } else { Err(TicketError::InvalidFlavor(flavor.to_string())) }
+}
+
+pub(crate) fn check_galaxy(galaxy: &str) -> Result<(), TicketError> {
+    if is_hex(galaxy, 8) { Ok(()) } else { Err(TicketError::InvalidGalaxy(galaxy.to_string())) }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuccessResult {
+    pub success: bool
+}
@@ -0,0 +1,78 @@
+use crate::{tickets_of_flavor, Aretha, Ticket, TicketError};
+use fount_rs::Fount;
+use fount_rs::spellbook::SpellbookRegistry;
+use fount_rs::nineum::DecodedNineum;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use fount_rs::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::direct_ports("localhost")).expect("service directory")
+}
+
+// config/service-spellbook.json prices the spells these tests cast.
+fn spellbooks() -> SpellbookRegistry {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../../../config/service-spellbook.json");
+    SpellbookRegistry::from_file(path).expect("service spellbook")
+}
+
+#[actix_rt::test]
+async fn test_aretha() {
+    let fount = Fount::with_directory(&directory(), None).with_spellbooks(spellbooks());
+    let aretha = Aretha::new(Some(Sessionless::from_private_key(*fount.sessionless.private_key())));
+    let attendee = Fount::with_directory(&directory(), None);
+    let caster = fount.create_user().await.expect("fount user");
+    let guest = attendee.create_user().await.expect("attendee");
+    let flavor = "010203040506";
+
+    let user = aretha.create_user(&fount, &caster.uuid, 0).await.expect("arethaUserCreate");
+    assert_eq!(user.uuid.len(), 36);
+    assert_eq!(user.pub_key, Some(fount.sessionless.public_key().to_hex()));
+
+    aretha.set_galaxy(&fount, &caster.uuid, 1, &caster.uuid, "28880014").await.expect("arethaUserGalaxy");
+    let granted = aretha.grant_admin(&fount, &caster.uuid, 2, &caster.uuid).await.expect("arethaUserGrant");
+    assert!(granted.success);
+
+    aretha.mint_tickets(&fount, &caster.uuid, 3, &user.uuid, flavor, 10).await.expect("arethaUserTickets");
+    assert!(aretha.mint_tickets(&fount, &caster.uuid, 4, &user.uuid, "not a flavor", 10).await.is_err());
+
+    let tickets = aretha.tickets(&fount, &caster.uuid, Some(flavor)).await.expect("tickets");
+    assert_eq!(tickets.len(), 10);
+    assert!(tickets.iter().all(|ticket| ticket.flavor() == flavor));
+
+    aretha.grant_tickets(&fount, &caster.uuid, &guest.uuid, &tickets[..1]).await.expect("grant ticket");
+    assert!(aretha.holds(&attendee, &guest.uuid, &tickets[0]).await.expect("holds"));
+    assert!(!aretha.holds(&fount, &caster.uuid, &tickets[0]).await.expect("no longer holds"));
+    assert!(aretha.grant_tickets(&fount, &caster.uuid, &guest.uuid, &tickets[..1]).await.is_err());
+}
+
+#[test]
+fn test_tickets_of_flavor() {
+    let held: Vec<DecodedNineum> = ["01288800140102030405060200000001", "01288800140102030405060200000002", "012888001401030204050602000000aa"]
+        .iter()
+        .map(|id| DecodedNineum::parse(id).expect("nineum"))
+        .collect();
+
+    let tickets = tickets_of_flavor(&held, Some("010203040506"));
+    assert_eq!(tickets.len(), 2);
+    assert_eq!(tickets[1].ordinal(), "00000002");
+    assert_eq!(tickets[1].galaxy(), "28880014");
+    assert_eq!(tickets_of_flavor(&held, Some("01030204050A")).len(), 0);
+    assert_eq!(tickets_of_flavor(&held, Some("010302040506"))[0], Ticket { nineum: held[2].clone() });
+    assert_eq!(tickets_of_flavor(&held, None).len(), 3);
+}
+
+#[actix_rt::test]
+async fn test_invalid_tickets_are_not_cast() {
+    // Nothing listens on port 9, so only the local checks can produce these errors.
+    let fount = Fount::new(Some("http://127.0.0.1:9/".to_string()), None);
+    let aretha = Aretha::new(None);
+
+    let error = aretha.mint_tickets(&fount, "caster", 0, "user", "01020304050g", 1).await.expect_err("flavor");
+    assert_eq!(error.to_string(), TicketError::InvalidFlavor("01020304050g".to_string()).to_string());
+    let error = aretha.mint_tickets(&fount, "caster", 0, "user", "010203040506", 0).await.expect_err("quantity");
+    assert_eq!(error.to_string(), TicketError::NoTickets.to_string());
+    let error = aretha.set_galaxy(&fount, "caster", 0, "user", "2888001").await.expect_err("galaxy");
+    assert_eq!(error.to_string(), TicketError::InvalidGalaxy("2888001".to_string()).to_string());
+}
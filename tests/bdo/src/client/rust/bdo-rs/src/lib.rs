@@ -1,18 +1,20 @@
 pub mod structs;
+pub mod messages;
 
 #[cfg(test)]
 mod tests;
 
-use reqwest::{Client, Response};
+use allyabase_transport_rs::{send_signed, Clock, HttpRequest, HttpResponse, Idempotency, Method, Payloads, PooledTransport, RetryPolicy, TracingTransport, Transport, TransportError};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde_json::Value;
 use sessionless::hex::IntoHex;
 use sessionless::{Sessionless, Signature};
-use std::time::{SystemTime, UNIX_EPOCH};
 use std::collections::HashMap;
 use std::option::Option;
+use std::sync::Arc;
 use crate::structs::{BDOUser, SuccessResult};
+pub use service_directory_rs::{Service, ServiceDirectory};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all="camelCase")]
@@ -36,161 +38,161 @@ pub struct Spellbooks {
 
 pub struct BDO {
     base_url: String,
-    client: Client,
+    transport: Arc<dyn Transport>,
+    retry: RetryPolicy,
+    clock: Clock,
+    payloads: Payloads,
     pub sessionless: Sessionless,
 }
 
 impl BDO {
     pub fn new(base_url: Option<String>, sessionless: Option<Sessionless>) -> Self {
-        println!("🏗️ BDO::new() called with base_url: {:?}", base_url);
-        let final_base_url = base_url.unwrap_or("https://dev.bdo.allyabase.com/".to_string());
-        println!("🏗️ BDO using final base_url: {}", final_base_url);
         BDO {
-            base_url: final_base_url,
-            client: Client::new(),
+            base_url: base_url.unwrap_or("https://dev.bdo.allyabase.com/".to_string()),
+            transport: PooledTransport::shared(),
+            retry: RetryPolicy::default(),
+            clock: Clock::new(),
+            payloads: Payloads::default(),
             sessionless: sessionless.unwrap_or(Sessionless::new()),
         }
     }
 
-    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
-        self.client.get(url).send().await
+    /// Points the client at bdo in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory, sessionless: Option<Sessionless>) -> Self {
+        Self::new(Some(directory.url(Service::Bdo)), sessionless)
     }
 
-    async fn post(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
-        self.client
-            .post(url)
-            .json(&payload)
-            .send()
-            .await
+    /// Sends requests through `transport` instead of the connection pool the
+    /// client shares with others on its runtime, e.g. a `PooledTransport` of its own.
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = transport;
+        self
     }
 
-    async fn put(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
-        self.client
-            .put(url)
-            .json(&payload)
-            .send()
-            .await
+    /// Retries failed requests as `retry` says. Every bdo route is safe to
+    /// repeat, so `RetryPolicy::non_idempotent` makes no difference here.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
     }
 
-    async fn delete(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
-        self.client
-            .delete(url)
-            .json(&payload)
-            .send()
-            .await
+    /// How much of each request and response to log at debug level.
+    /// `Payloads::Full` shows signatures and BDOs, so leave it off in production.
+    pub fn with_payloads(mut self, payloads: Payloads) -> Self {
+        self.payloads = payloads;
+        self
     }
 
-    fn get_timestamp() -> String {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_millis()
-            .to_string()
+    /// The transport wrapped in a `tracing` span per request.
+    fn traced(&self) -> TracingTransport<'_> {
+        TracingTransport::new("bdo", &self.base_url, self.payloads, &*self.transport)
+    }
+
+    /// The clock this client signs with, corrected by every response bdo
+    /// sends back.
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Measures how far this machine's clock is from bdo's before anything
+    /// is signed, returning the drift in milliseconds if bdo says.
+    pub async fn sync_clock(&self) -> Result<Option<i64>, Box<dyn std::error::Error>> {
+        Ok(self.clock.probe(&self.traced(), &self.base_url).await?)
+    }
+
+    /// Sends the request `sign` builds from a fresh timestamp, again for
+    /// every retry.
+    async fn send(&self, sign: impl FnMut(&str) -> HttpRequest) -> Result<HttpResponse, TransportError> {
+        send_signed(&self.traced(), &self.retry, &self.clock, Idempotency::Idempotent, sign).await
     }
 
     pub async fn create_user(&self, hash: &str, bdo: &Value, is_public: &bool) -> Result<BDOUser, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
         let pub_key = self.sessionless.public_key().to_hex();
-        let signature = self.sessionless.sign(&format!("{}{}{}", timestamp, pub_key, hash)).to_hex();
-        
-        let payload = json!({
-            "timestamp": timestamp,
-            "pubKey": pub_key,
-            "hash": hash,
-            "bdo": bdo,
-            "public": is_public,
-            "signature": signature
-        }).as_object().unwrap().clone();
-
-dbg!("{}", payload.clone());
-
-        println!("🔧 BDO client base_url: {}", self.base_url);
         let url = format!("{}user/create", self.base_url);
-        println!("🔗 BDO final URL: {}", &url);
-dbg!("{}", &url);
-        let res = self.put(&url, serde_json::Value::Object(payload)).await?;
-dbg!("{}", &res);
-        let user: BDOUser = res.json().await?;
+        let res = self.send(|timestamp| {
+            let signature = self.sessionless.sign(messages::create_user(timestamp, &pub_key, hash)).to_hex();
+            let payload = json!({
+                "timestamp": timestamp,
+                "pubKey": pub_key,
+                "hash": hash,
+                "bdo": bdo,
+                "public": is_public,
+                "signature": signature
+            });
+            HttpRequest::new(Method::Put, &url, Some(payload))
+        }).await?;
+        let user: BDOUser = res.json()?;
 
         Ok(user)
     }
 
     pub async fn update_bdo(&self, uuid: &str, hash: &str, bdo: &Value, is_public: &bool) -> Result<BDOUser, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}{}", timestamp, uuid, hash);
-        let signature = self.sessionless.sign(message).to_hex();
-
-        let payload = json!({
-            "timestamp": timestamp,
-            "uuid": uuid,
-            "hash": hash,
-            "pub": is_public,
-            "pubKey": self.sessionless.public_key().to_hex(),
-            "bdo": bdo,
-            "signature": signature
-        }).as_object().unwrap().clone();
-
         let url = format!("{}user/{}/bdo", self.base_url, uuid);
-        let res = self.put(&url, serde_json::Value::Object(payload)).await?;
-        let user: BDOUser = res.json().await?;
+        let res = self.send(|timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid, hash)).to_hex();
+            let payload = json!({
+                "timestamp": timestamp,
+                "uuid": uuid,
+                "hash": hash,
+                "pub": is_public,
+                "pubKey": self.sessionless.public_key().to_hex(),
+                "bdo": bdo,
+                "signature": signature
+            });
+            HttpRequest::new(Method::Put, &url, Some(payload))
+        }).await?;
+        let user: BDOUser = res.json()?;
 
         Ok(user)
     }
 
     pub async fn get_bdo(&self, uuid: &str, hash: &str) -> Result<BDOUser, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}{}", timestamp, uuid, hash);
-        let signature = self.sessionless.sign(message).to_hex();
-
-        let url = format!("{}user/{}/bdo?timestamp={}&hash={}&signature={}", self.base_url, uuid, timestamp, hash, signature);
-        let res = self.get(&url).await?;
-        let user: BDOUser = res.json().await?;
+        let res = self.send(|timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid, hash)).to_hex();
+            let url = format!("{}user/{}/bdo?timestamp={}&hash={}&signature={}", self.base_url, uuid, timestamp, hash, signature);
+            HttpRequest::new(Method::Get, &url, None)
+        }).await?;
+        let user: BDOUser = res.json()?;
  
         Ok(user)
     }
 
     pub async fn get_public_bdo(&self, uuid: &str, hash: &str, pub_key: &str) -> Result<BDOUser, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}{}", timestamp, uuid, hash);
-        let signature = self.sessionless.sign(message).to_hex();
-
-        let url = format!("{}user/{}/bdo?timestamp={}&hash={}&signature={}&pubKey={}", self.base_url, uuid, timestamp, hash, signature, pub_key);
-dbg!("{}", &url);
-dbg!("{}", &self.sessionless.public_key().to_hex());
-        let res = self.get(&url).await?;
-        let user: BDOUser = res.json().await?;
+        let res = self.send(|timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid, hash)).to_hex();
+            let url = format!("{}user/{}/bdo?timestamp={}&hash={}&signature={}&pubKey={}", self.base_url, uuid, timestamp, hash, signature, pub_key);
+            HttpRequest::new(Method::Get, &url, None)
+        }).await?;
+        let user: BDOUser = res.json()?;
  
         Ok(user)
     }
 
     pub async fn get_bases(&self, uuid: &str, hash: &str) -> Result<Value, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}{}", timestamp, uuid, hash);
-        let signature = self.sessionless.sign(message).to_hex();
-
-        let url = format!("{}user/{}/bases?timestamp={}&hash={}&signature={}", self.base_url, uuid, timestamp, hash, signature);
-        let res = self.get(&url).await?;
-        let bases: Bases = res.json().await?;
+        let res = self.send(|timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid, hash)).to_hex();
+            let url = format!("{}user/{}/bases?timestamp={}&hash={}&signature={}", self.base_url, uuid, timestamp, hash, signature);
+            HttpRequest::new(Method::Get, &url, None)
+        }).await?;
+        let bases: Bases = res.json()?;
  
         Ok(bases.bases)
     }
 
     pub async fn save_bases(&self, uuid: &str, hash: &str, bases: &Bases) -> Result<Value, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}{}", timestamp, uuid, hash);
-        let signature = self.sessionless.sign(message).to_hex();
-
-        let payload = json!({
-            "timestamp": timestamp,
-            "uuid": uuid,
-            "hash": hash,
-            "bases": bases,
-            "signature": signature
-        }).as_object().unwrap().clone();
-
         let url = format!("{}user/{}/bases", self.base_url, uuid);
-        let res = self.put(&url, serde_json::Value::Object(payload)).await?;
-        let bases: Bases = res.json().await?;
+        let res = self.send(|timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid, hash)).to_hex();
+            let payload = json!({
+                "timestamp": timestamp,
+                "uuid": uuid,
+                "hash": hash,
+                "bases": bases,
+                "signature": signature
+            });
+            HttpRequest::new(Method::Put, &url, Some(payload))
+        }).await?;
+        let bases: Bases = res.json()?;
 
         Ok(bases.bases)
     }
@@ -198,76 +200,70 @@ dbg!("{}", &self.sessionless.public_key().to_hex());
 
 
     pub async fn get_spellbooks(&self, uuid: &str, hash: &str) -> Result<Vec<Spellbook>, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}{}", timestamp, uuid, hash);
-        let signature = self.sessionless.sign(message).to_hex();
-
-        let url = format!("{}user/{}/spellbooks?timestamp={}&hash={}&signature={}", self.base_url, uuid, timestamp, hash, signature);
-        let res = self.get(&url).await?;
-        let spellbooks: Spellbooks = res.json().await?;
+        let res = self.send(|timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid, hash)).to_hex();
+            let url = format!("{}user/{}/spellbooks?timestamp={}&hash={}&signature={}", self.base_url, uuid, timestamp, hash, signature);
+            HttpRequest::new(Method::Get, &url, None)
+        }).await?;
+        let spellbooks: Spellbooks = res.json()?;
  
         Ok(spellbooks.spellbooks)
     }
 
     pub async fn put_spellbook(&self, uuid: &str, hash: &str, spellbook: &Spellbook) -> Result<Vec<Spellbook>, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}{}", timestamp, uuid, hash);
-        let signature = self.sessionless.sign(message).to_hex();
-
-        let payload = json!({
-            "timestamp": timestamp,
-            "uuid": uuid,
-            "hash": hash,
-            "spellbook": spellbook,
-            "signature": signature
-        }).as_object().unwrap().clone();
-
         let url = format!("{}user/{}/spellbooks", self.base_url, uuid);
-        let res = self.put(&url, serde_json::Value::Object(payload)).await?;
-        let spellbooks: Vec<Spellbook> = res.json().await?;
+        let res = self.send(|timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid, hash)).to_hex();
+            let payload = json!({
+                "timestamp": timestamp,
+                "uuid": uuid,
+                "hash": hash,
+                "spellbook": spellbook,
+                "signature": signature
+            });
+            HttpRequest::new(Method::Put, &url, Some(payload))
+        }).await?;
+        let spellbooks: Vec<Spellbook> = res.json()?;
 
         Ok(spellbooks)
     }
 
     pub async fn delete_user(&self, uuid: &str, hash: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}", timestamp, uuid);
-        let signature = self.sessionless.sign(&message).to_hex();
-
-        let payload = json!({
-          "timestamp": timestamp,
-          "uuid": uuid,
-          "hash": hash,
-          "signature": signature
-        }).as_object().unwrap().clone();
-
         let url = format!("{}user/{}/delete", self.base_url, uuid);
-        let res = self.delete(&url, serde_json::Value::Object(payload)).await?;
-        let success: SuccessResult = res.json().await?;
+        let res = self.send(|timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid, hash)).to_hex();
+            let payload = json!({
+              "timestamp": timestamp,
+              "uuid": uuid,
+              "hash": hash,
+              "signature": signature
+            });
+            HttpRequest::new(Method::Delete, &url, Some(payload))
+        }).await?;
+        let success: SuccessResult = res.json()?;
 
         Ok(success)
     }
 
 
     pub async fn teleport(&self, uuid: &str, hash: &str, url: &str) -> Result<Value, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}{}", timestamp, uuid, hash);
-        let signature = self.sessionless.sign(&message).to_hex();
-
-        // Don't translate here - let the BDO server handle allyabase:// protocol
-        let teleport_url = format!(
-            "{}user/{}/teleport?timestamp={}&hash={}&signature={}&url={}", 
-            self.base_url, 
-            uuid, 
-            timestamp, 
-            hash, 
-            signature, 
-            urlencoding::encode(url)
-        );
-
-        dbg!(&teleport_url);
-        let res = self.get(&teleport_url).await?;
-        let teleported_content: Value = res.json().await?;
+        let res = self.send(|timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid, hash)).to_hex();
+
+            // Don't translate here - let the BDO server handle allyabase:// protocol
+            let teleport_url = format!(
+                "{}user/{}/teleport?timestamp={}&hash={}&signature={}&url={}", 
+                self.base_url, 
+                uuid, 
+                timestamp, 
+                hash, 
+                signature, 
+                urlencoding::encode(url)
+            );
+
+            HttpRequest::new(Method::Get, &teleport_url, None)
+        }).await?;
+        let teleported_content: Value = res.json()?;
 
         Ok(teleported_content)
     }
@@ -0,0 +1,16 @@
+//! The messages bdo checks signatures against. Bdo hands authentication to
+//! continuebee, so there are only two.
+//!
+//! config/signature-vectors.json pins these down, and `test_signature_vectors`
+//! checks them.
+
+/// `PUT /user/create`: `timestamp + pubKey + hash`.
+pub fn create_user(timestamp: &str, pub_key: &str, hash: &str) -> String {
+    format!("{}{}{}", timestamp, pub_key, hash)
+}
+
+/// Every other route, deleting included: `timestamp + uuid + hash`, which
+/// continuebee checks against the key the user was created with.
+pub fn user(timestamp: &str, uuid: &str, hash: &str) -> String {
+    format!("{}{}{}", timestamp, uuid, hash)
+}
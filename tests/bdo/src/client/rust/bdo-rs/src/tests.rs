@@ -1,19 +1,30 @@
-use crate::{Bases, BDOUser, BDO, Spellbook, SuccessResult};
+use crate::{Bases, BDOUser, BDO, Spellbook};
 use sessionless::hex::IntoHex;
 use sessionless::hex::FromHex;
 use sessionless::{Sessionless, PrivateKey};
 use std::collections::HashMap;
 use serde_json::json;
 use serde_json::Value;
+use crate::{Service, ServiceDirectory};
+use crate::messages;
+use allyabase_mock_rs::MockAllyabase;
+use allyabase_transport_rs::{Contract, HttpResponse, LoopbackTransport, Method, PooledTransport, RecordingTransport, ReplayTransport, RetryPolicy, Transport};
+use std::sync::Arc;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::direct_ports("localhost")).expect("service directory")
+}
 
 #[actix_rt::test]
+#[ignore = "needs a running node; sharon runs it, or cargo test -- --ignored"]
 async fn test_bdo() {
 
     let mut saved_user: BDOUser;
     let mut saved_user2: BDOUser; 
-    let bdo = BDO::new(Some("http://localhost:3003/".to_string()), None);
-    let bdo2 = BDO::new(Some("http://localhost:3003/".to_string()), None);
-    let bdo3 = BDO::new(Some("http://localhost:3003/".to_string()), Some(Sessionless::from_private_key(PrivateKey::from_hex("a29435a4fb1a27a284a60b3409efeebbe6a64db606ff38aeead579ccf2262dc4").expect("private key"))));
+    let bdo = BDO::with_directory(&directory(), None);
+    let bdo2 = BDO::with_directory(&directory(), None);
+    let bdo3 = BDO::with_directory(&directory(), Some(Sessionless::from_private_key(PrivateKey::from_hex("a29435a4fb1a27a284a60b3409efeebbe6a64db606ff38aeead579ccf2262dc4").expect("private key"))));
     let hash = "hereisanexampleofahash";
     let hash2 = "hereisasecondhash";
 
@@ -23,7 +34,7 @@ async fn test_bdo() {
             "foo": "foo",
             "pub": bdo.sessionless.public_key().to_hex()
          });
-	let result = bdo.create_user(&hash, &publicBDO, false).await;
+	let result = bdo.create_user(&hash, &publicBDO, &false).await;
     println!("got to here");
 
 	match result {
@@ -48,7 +59,7 @@ async fn test_bdo() {
         let privateBDO = json!({
             "bar": "bar"
          });
-	let result = bdo.create_user(&hash, &privateBDO, false).await;
+	let result = bdo.create_user(&hash, &privateBDO, &false).await;
     println!("got to here");
 
 	match result {
@@ -68,30 +79,6 @@ async fn test_bdo() {
 	}
     }
 
-    async fn update_bdo(bdo: &BDO, saved_user: &BDOUser, hash: &str) -> Option<BDOUser> {
-        let update = json!({
-            "foo": "bop",
-            "pub": bdo.sessionless.public_key().to_hex()
-         });
-        let result = bdo.update_bdo(&saved_user.uuid, &hash, &update, &true).await;
-        
-        match result {
-            Ok(user) => {
-                println!("Successfully got BDOUser: {}", user.uuid);
-                assert_eq!(
-                    user.uuid.len(),
-                    36
-                );
-                Some(user)
-            },
-            Err(error) => {
-                eprintln!("Error occurred update_bdo: {}", error);
-                println!("Error details: {:?}", error);
-                None
-            }
-        }
-    }
-
     async fn get_bdo(bdo: &BDO, bdo2: &BDO, saved_user: &BDOUser, hash: &str) -> Option<BDOUser> {
         let result = bdo2.get_public_bdo(&saved_user.uuid, &hash, &bdo.sessionless.public_key().to_hex()).await;
  
@@ -112,46 +99,6 @@ async fn test_bdo() {
         }
     }
 
-    async fn get_bases(bdo: &BDO, saved_user: &BDOUser, hash: &str) -> Option<Value> {
-        let result = bdo.get_bases(&saved_user.uuid, &hash).await;
-    
-        match result {
-            Ok(bases) => {
-                println!("Successfully got bases: {}", bases);
-                assert_eq!(
-                    true,
-                    true
-                );
-                Some(bases)
-            },
-            Err(error) => {
-                eprintln!("Error occurred get_bases: {}", error);
-                println!("Error details: {:?}", error);
-                None
-            }
-        }
-    }
-
-    async fn put_bases(bdo: &BDO, saved_user: &BDOUser, hash: &str, bases: &Bases) -> Option<Value> {
-        let result = bdo.save_bases(&saved_user.uuid, &hash, &bases).await;
-
-        match result {
-            Ok(bases) => {
-                println!("Successfully got bases: {}", bases);
-                assert_eq!(
-                    true,
-                    true
-                );
-                Some(bases)
-            },
-            Err(error) => {
-                eprintln!("Error occurred put_spellbook: {}", error);
-                println!("Error details: {:?}", error);
-                None
-            }
-        }
-    }
-
     async fn get_spellbooks(bdo: &BDO, saved_user: &BDOUser, hash: &str) -> Option<Vec<Spellbook>> {
         let result = bdo.get_spellbooks(&saved_user.uuid, &hash).await;
     
@@ -193,54 +140,177 @@ async fn test_bdo() {
     }
 */
 
-    async fn delete_user(bdo: &BDO, saved_user: &BDOUser, hash: &str) -> Option<SuccessResult> {
-        let result = bdo.delete_user(&saved_user.uuid, &hash).await;
-
-        match result {
-            Ok(success) => {
-                assert_eq!(
-                    success.success,
-                    true
-                );
-                Some(success)
-            }
-            Err(error) => {
-                eprintln!("Error occurred delete: {}", error);
-                println!("Error details: {:?}", error);
-                None
-            }
-        }
-    }
-        
     saved_user = create_user(&bdo, &hash).await.expect("user");
     saved_user2 = create_user2_with_private_bdo(&bdo2, &hash2).await.expect("user2");
 
     Some(get_bdo(&bdo, &bdo2, &saved_user2, &hash2).await.expect("get_bdo"));
     Some(get_spellbooks(&bdo, &saved_user, &hash).await);
+}
 
-/*    if let Some(ref user) = saved_user {
-	Some(update_bdo(&bdo, user, &hash).await.expect("update_bdo"));
-    } else {
-	panic!("Failed to get prompt");
-    }
+// Runs against an in-process mock node, so it needs no Docker environment.
+#[actix_rt::test]
+async fn test_bdo_offline() {
+    let node = MockAllyabase::start().expect("mock node");
+    let bdo = BDO::with_directory(&node.directory(), None);
+    let bdo2 = BDO::with_directory(&node.directory(), None);
+    let hash = "hereisanexampleofahash";
+    let pub_key = bdo.sessionless.public_key().to_hex();
+
+    let user = bdo.create_user(hash, &json!({ "foo": "foo" }), &false).await.expect("create_user");
+    assert_eq!(user.uuid.len(), 36);
+    assert_eq!(bdo.get_bdo(&user.uuid, hash).await.expect("get_bdo").bdo, json!({ "foo": "foo" }));
+
+    let updated = bdo.update_bdo(&user.uuid, hash, &json!({ "foo": "bop" }), &true).await.expect("update_bdo");
+    assert_eq!(updated.bdo, json!({ "foo": "bop" }));
 
-    if let (Some(ref user), Some(ref user2)) = (saved_user, saved_user2) {
-        Some(get_bdo(&bdo, user, &hash2).await.expect("get_bdo"));
-    } else { 
-        panic!("Failed to sign prompt");
-    } 
-
-    if let Some(ref user) = saved_user {
-        Some(get_spellbooks(&bdo, user, &hash).await);
-        
-        if let Some(ref user) = saved_user {
-            delete_user(&bdo, &user, &hash).await;
-        } else {
-	    panic!("Failed to delete user");
-	} 
-
-    } else {
-        panic!("Failed on associate");
-    }*/
+    let user2 = bdo2.create_user(hash, &json!({ "bar": "bar" }), &false).await.expect("create_user2");
+    let public = bdo2.get_public_bdo(&user2.uuid, hash, &pub_key).await.expect("get_public_bdo");
+    assert_eq!(public.bdo, json!({ "foo": "bop" }));
 
+    let bases = Bases { bases: json!({ "mock": { "name": "mock" } }) };
+    let saved = bdo.save_bases(&user.uuid, hash, &bases).await.expect("save_bases");
+    assert_eq!(bdo2.get_bases(&user2.uuid, hash).await.expect("get_bases"), saved);
+    assert!(bdo.get_spellbooks(&user.uuid, hash).await.expect("get_spellbooks").is_empty());
+
+    // Bdo answers a bad signature with an error body rather than a BDO.
+    assert!(bdo2.get_bdo(&user.uuid, hash).await.is_err());
+    assert!(bdo.get_bdo(&user.uuid, "someotherhash").await.is_err());
+
+    node.set_clock_offset(-600000);
+    let error = bdo.get_bdo(&user.uuid, hash).await.expect_err("stale timestamp");
+    assert!(error.to_string().contains("s ahead of the server"), "{}", error);
+    assert!((bdo.clock().offset() + 600000).abs() < 2000);
+
+    // The client signs with bdo's time from then on; another can ask up front.
+    bdo.get_bdo(&user.uuid, hash).await.expect("get_bdo after skew");
+    assert!(bdo2.sync_clock().await.expect("sync_clock").is_some());
+    bdo2.get_bdo(&user2.uuid, hash).await.expect("get_bdo after sync");
+}
+
+// The calls the bdo contract covers, in the order the fixture records them.
+async fn bdo_flow(bdo: &BDO, bdo2: &BDO) {
+    let hash = "hereisanexampleofahash";
+    let pub_key = bdo.sessionless.public_key().to_hex();
+
+    let user = bdo.create_user(hash, &json!({ "foo": "foo" }), &false).await.expect("create_user");
+    bdo.get_bdo(&user.uuid, hash).await.expect("get_bdo");
+    bdo.update_bdo(&user.uuid, hash, &json!({ "foo": "bop" }), &true).await.expect("update_bdo");
+    let user2 = bdo2.create_user(hash, &json!({ "bar": "bar" }), &false).await.expect("create_user2");
+    bdo2.get_public_bdo(&user2.uuid, hash, &pub_key).await.expect("get_public_bdo");
+    bdo.get_bases(&user.uuid, hash).await.expect("get_bases");
+    bdo.get_spellbooks(&user.uuid, hash).await.expect("get_spellbooks");
+}
+
+// SHARON_FIXTURES=record saves what the node answers to rust/fixtures/bdo-rs.json,
+// verify diffs a live node against it and replay (the default) plays it back.
+// Ignored until a fixture is recorded from a live node; replaying without one fails.
+#[actix_rt::test]
+#[ignore = "no fixture recorded yet; run SHARON_FIXTURES=record cargo test contract -- --ignored against a live node"]
+async fn test_bdo_contract() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../rust/fixtures/bdo-rs.json");
+    let contract = Contract::from_env(&directory().url(Service::Bdo), path).expect("contract");
+    let bdo = BDO::with_directory(&directory(), None).with_transport(contract.transport());
+    let bdo2 = BDO::with_directory(&directory(), None).with_transport(contract.transport());
+
+    bdo_flow(&bdo, &bdo2).await;
+    assert_eq!(contract.finish().expect("fixture"), vec![]);
+}
+
+#[actix_rt::test]
+async fn test_bdo_record_and_replay() {
+    let node = MockAllyabase::start().expect("mock node");
+    let url = node.directory().url(Service::Bdo);
+    let recording = Arc::new(RecordingTransport::new(&url, Arc::new(PooledTransport::default())));
+    bdo_flow(
+        &BDO::with_directory(&node.directory(), None).with_transport(recording.clone()),
+        &BDO::with_directory(&node.directory(), None).with_transport(recording.clone())
+    ).await;
+    drop(node);
+
+    let replay = Arc::new(ReplayTransport::new(&url, recording.fixture()));
+    bdo_flow(
+        &BDO::new(Some(url.clone()), None).with_transport(replay.clone()),
+        &BDO::new(Some(url.clone()), None).with_transport(replay.clone())
+    ).await;
+    assert_eq!(replay.diffs(), vec![]);
+    assert!(replay.remaining().is_empty());
+}
+
+// Clients share the runtime's connection pool unless handed another transport.
+#[actix_rt::test]
+async fn test_bdo_loopback() {
+    assert!(Arc::ptr_eq(&BDO::with_directory(&directory(), None).transport, &BDO::with_directory(&directory(), None).transport));
+    let own: Arc<dyn Transport> = Arc::new(PooledTransport::default());
+    assert!(Arc::ptr_eq(&BDO::with_directory(&directory(), None).with_transport(own.clone()).transport, &own));
+
+    let loopback = Arc::new(LoopbackTransport::new(|request| {
+        let bdo = request.body.as_ref().map(|body| body["bdo"].clone()).unwrap_or(json!({ "foo": "foo" }));
+        HttpResponse::json_body(200, &json!({ "uuid": "5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f", "bdo": bdo }))
+    }));
+    let bdo = BDO::new(Some("http://bdo.test/".to_string()), None).with_transport(loopback.clone());
+    let hash = "hereisanexampleofahash";
+
+    let user = bdo.create_user(hash, &json!({ "foo": "foo" }), &false).await.expect("create_user");
+    let updated = bdo.update_bdo(&user.uuid, hash, &json!({ "foo": "bop" }), &true).await.expect("update_bdo");
+    assert_eq!(updated.bdo, json!({ "foo": "bop" }));
+
+    let requests = loopback.requests();
+    assert_eq!((requests[0].method, requests[0].url.as_str()), (Method::Put, "http://bdo.test/user/create"));
+    assert_eq!((requests[1].method, requests[1].url.as_str()), (Method::Put, "http://bdo.test/user/5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f/bdo"));
+    assert_eq!(requests[1].body.as_ref().expect("body")["hash"], json!(hash));
+}
+
+#[actix_rt::test]
+async fn test_bdo_retry() {
+    let count = std::sync::atomic::AtomicU32::new(0);
+    let loopback = Arc::new(LoopbackTransport::new(move |_| match count.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+        0 => HttpResponse::json_body(502, &json!({ "error": "bad gateway" })),
+        _ => HttpResponse::json_body(200, &json!({ "uuid": "5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f", "bdo": { "foo": "foo" } }))
+    }));
+    let retry = RetryPolicy { base_delay: std::time::Duration::from_millis(2), ..Default::default() };
+    let bdo = BDO::new(Some("http://bdo.test/".to_string()), None).with_transport(loopback.clone()).with_retry(retry);
+
+    let user = bdo.get_bdo("5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f", "hereisanexampleofahash").await.expect("get_bdo");
+    assert_eq!(user.bdo, json!({ "foo": "foo" }));
+
+    // The retry carries a fresh timestamp and signature.
+    let requests = loopback.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].url != requests[1].url);
+
+    let loopback = Arc::new(LoopbackTransport::new(|_| HttpResponse::json_body(503, &json!({ "error": "unavailable" }))));
+    let bdo = BDO::new(Some("http://bdo.test/".to_string()), None).with_transport(loopback.clone()).with_retry(RetryPolicy::never());
+    assert!(bdo.get_bdo("5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f", "hereisanexampleofahash").await.is_err());
+    assert_eq!(loopback.requests().len(), 1);
+}
+
+// config/signature-vectors.json. Only the Rust clients are checked against it so far.
+fn signature_vectors() -> Value {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../../../config/signature-vectors.json");
+    serde_json::from_str(&std::fs::read_to_string(path).expect("signature vectors")).expect("signature vectors json")
+}
+
+#[test]
+fn test_signature_vectors() {
+    let corpus = signature_vectors();
+    let vectors: Vec<&Value> = corpus["vectors"].as_array().expect("vectors").iter().filter(|vector| vector["service"] == "bdo").collect();
+    assert!(!vectors.is_empty());
+
+    for vector in vectors {
+        let name = vector["name"].as_str().expect("name");
+        let text = |key: &str| vector["inputs"][key].as_str().unwrap_or_else(|| panic!("{} needs {}", name, key)).to_string();
+
+        let message = match name {
+            "bdo.createUser" => messages::create_user(&text("timestamp"), &text("pubKey"), &text("hash")),
+            "bdo.updateBDO" | "bdo.getBDO" | "bdo.getBases" | "bdo.saveBases" | "bdo.getSpellbooks" | "bdo.putSpellbook" | "bdo.teleport" | "bdo.deleteUser" =>
+                messages::user(&text("timestamp"), &text("uuid"), &text("hash")),
+            name => panic!("bdo-rs has no message for {}", name)
+        };
+        assert_eq!(message, vector["message"].as_str().expect("message"), "{}", name);
+
+        let key = &corpus["keys"][vector["signer"].as_str().expect("signer")];
+        let signer = Sessionless::from_private_key(PrivateKey::from_hex(key["privateKey"].as_str().expect("privateKey")).expect("private key"));
+        assert_eq!(signer.public_key().to_hex(), key["publicKey"].as_str().expect("publicKey"));
+        assert_eq!(signer.sign(&message).to_hex(), vector["signature"].as_str().expect("signature"), "{}", name);
+    }
 }
@@ -0,0 +1,185 @@
+pub mod structs;
+
+#[cfg(test)]
+mod tests;
+
+use reqwest::{Client, Response, StatusCode};
+use serde_json::json;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::option::Option;
+use fount_rs::Fount;
+pub use crate::structs::{ContinuationProof, ContinuebeeUser, ProofError, SuccessResult, ALLOWED_TIME_DIFFERENCE};
+pub use service_directory_rs::{Service, ServiceDirectory};
+
+pub struct Continuebee {
+    base_url: String,
+    client: Client,
+    pub sessionless: Sessionless,
+}
+
+impl Continuebee {
+    pub fn new(base_url: Option<String>, sessionless: Option<Sessionless>) -> Self {
+        Continuebee {
+            base_url: base_url.unwrap_or("https://dev.continuebee.allyabase.com/".to_string()),
+            client: Client::new(),
+            sessionless: sessionless.unwrap_or(Sessionless::new()),
+        }
+    }
+
+    /// Points the client at continuebee in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory, sessionless: Option<Sessionless>) -> Self {
+        Self::new(Some(directory.url(Service::Continuebee)), sessionless)
+    }
+
+    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.client.get(url).send().await
+    }
+
+    async fn post(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .post(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn put(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .put(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn delete(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .delete(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    fn get_timestamp() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis()
+            .to_string()
+    }
+
+    pub async fn create_user(&self, hash: &str) -> Result<ContinuebeeUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let pub_key = self.sessionless.public_key().to_hex();
+        let signature = self.sessionless.sign(format!("{}{}{}", timestamp, pub_key, hash)).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "pubKey": pub_key,
+            "hash": hash,
+            "signature": signature
+        });
+
+        let url = format!("{}user/create", self.base_url);
+        let res = self.post(&url, payload).await?;
+        let user: ContinuebeeUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    /// Whether `hash` is still current for the user at `uuid` and this key.
+    pub async fn check_hash(&self, uuid: &str, hash: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let proof = ContinuationProof::new(&self.sessionless, uuid, hash, &Self::get_timestamp());
+        self.check_proof(&proof).await
+    }
+
+    /// Asks continuebee whether a proof signed by someone else is still good.
+    /// The proof is checked locally first so forged or expired ones never
+    /// leave the process.
+    pub async fn check_proof(&self, proof: &ContinuationProof) -> Result<bool, Box<dyn std::error::Error>> {
+        proof.verify_at(Self::get_timestamp().parse()?)?;
+
+        let url = format!("{}user/{}?timestamp={}&hash={}&signature={}", self.base_url, proof.uuid, proof.timestamp, proof.hash, proof.signature);
+        let res = self.get(&url).await?;
+
+        match res.status() {
+            status if status.is_success() => Ok(true),
+            // Continuebee answers a stale or unknown pair with 406 or 404.
+            StatusCode::NOT_ACCEPTABLE | StatusCode::NOT_FOUND | StatusCode::FORBIDDEN => Ok(false),
+            status => Err(format!("continuebee could not check the session: {}", status).into())
+        }
+    }
+
+    /// Rotates the user's hash, e.g. at the end of each session.
+    pub async fn update_hash(&self, uuid: &str, hash: &str, new_hash: &str) -> Result<ContinuebeeUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}{}", timestamp, uuid, hash, new_hash);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "userUUID": uuid,
+            "hash": hash,
+            "newHash": new_hash,
+            "signature": signature
+        });
+
+        let url = format!("{}user/update-hash", self.base_url);
+        let res = self.put(&url, payload).await?;
+        let user: ContinuebeeUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    pub async fn delete_user(&self, uuid: &str, hash: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}", timestamp, uuid, hash);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "userUUID": uuid,
+            "hash": hash,
+            "signature": signature
+        });
+
+        let url = format!("{}user/delete", self.base_url);
+        let res = self.delete(&url, payload).await?;
+
+        Ok(SuccessResult { success: res.status().is_success() })
+    }
+
+    /// Creates a user for this client's key with the `continuebeeUserCreate`
+    /// spell. Casting it again with the same key and hash returns the same user.
+    pub async fn magic_create_user(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, hash: &str) -> Result<ContinuebeeUser, Box<dyn std::error::Error>> {
+        let components = json!({
+            "pubKey": self.sessionless.public_key().to_hex(),
+            "hash": hash
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "continuebeeUserCreate", ordinal, components).await?;
+
+        result.field("user")
+    }
+
+    pub async fn magic_update_hash(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str, hash: &str, new_hash: &str) -> Result<ContinuebeeUser, Box<dyn std::error::Error>> {
+        let components = json!({
+            "userUUID": uuid,
+            "hash": hash,
+            "newHash": new_hash
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "continuebeeUserUpdateHash", ordinal, components).await?;
+
+        result.field("user")
+    }
+
+    pub async fn magic_delete_user(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str, hash: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let components = json!({
+            "userUUID": uuid,
+            "hash": hash
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "continuebeeUserDelete", ordinal, components).await?;
+
+        Ok(SuccessResult { success: result.success })
+    }
+}
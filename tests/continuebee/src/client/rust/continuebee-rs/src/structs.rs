@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use serde_json::Value;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use sessionless_helpers_rs::{check_timestamp, parse_public_key, parse_signature, signs, TimestampError};
+
+pub use sessionless_helpers_rs::ALLOWED_TIME_DIFFERENCE;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct ContinuebeeUser {
+    pub uuid: String,
+    pub pub_key: Option<String>,
+    pub hash: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuccessResult {
+    pub success: bool
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProofError {
+    InvalidPublicKey(String),
+    InvalidSignature(String),
+    InvalidTimestamp(String),
+    StaleTimestamp { timestamp: u64, now: u64 },
+    Mismatch
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofError::InvalidPublicKey(key) => write!(f, "invalid public key: {}", key),
+            ProofError::InvalidSignature(signature) => write!(f, "invalid signature: {}", signature),
+            ProofError::InvalidTimestamp(timestamp) => write!(f, "timestamp is not a number of milliseconds: {}", timestamp),
+            ProofError::StaleTimestamp { timestamp, now } => write!(f, "stale timestamp: {} is {}ms from now ({})", timestamp, now.abs_diff(*timestamp), now),
+            ProofError::Mismatch => write!(f, "signature does not match the session")
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+/// A user's signed claim that they still hold `hash` for `uuid`. The user
+/// signs it on their side and hands it to a backend, which can check it
+/// locally with `verify` and then with continuebee via `check_proof`. The
+/// signed message is the one continuebee checks on `GET /user/:uuid`:
+/// `timestamp + uuid + hash`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct ContinuationProof {
+    pub uuid: String,
+    pub pub_key: String,
+    pub hash: String,
+    pub timestamp: String,
+    pub signature: String
+}
+
+impl ContinuationProof {
+    pub fn new(sessionless: &Sessionless, uuid: &str, hash: &str, timestamp: &str) -> Self {
+        let message = format!("{}{}{}", timestamp, uuid, hash);
+
+        ContinuationProof {
+            uuid: uuid.to_string(),
+            pub_key: sessionless.public_key().to_hex(),
+            hash: hash.to_string(),
+            timestamp: timestamp.to_string(),
+            signature: sessionless.sign(message).to_hex()
+        }
+    }
+
+    pub fn message(&self) -> String {
+        format!("{}{}{}", self.timestamp, self.uuid, self.hash)
+    }
+
+    /// Checks the signature and that the proof is recent enough for
+    /// continuebee to accept, with `now` in milliseconds.
+    pub fn verify_at(&self, now: u64) -> Result<(), ProofError> {
+        check_timestamp(&self.timestamp, now).map_err(|err| match err {
+            TimestampError::Invalid(timestamp) => ProofError::InvalidTimestamp(timestamp),
+            TimestampError::Stale { timestamp, now } => ProofError::StaleTimestamp { timestamp, now }
+        })?;
+
+        let pub_key = parse_public_key(&self.pub_key).ok_or_else(|| ProofError::InvalidPublicKey(self.pub_key.clone()))?;
+        let signature = parse_signature(&self.signature).ok_or_else(|| ProofError::InvalidSignature(self.signature.clone()))?;
+        match signs(&self.message(), &pub_key, &signature) {
+            true => Ok(()),
+            false => Err(ProofError::Mismatch)
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{ContinuationProof, Continuebee, ContinuebeeUser, ProofError, SuccessResult};
+use fount_rs::Fount;
+use fount_rs::spellbook::SpellbookRegistry;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::direct_ports("localhost")).expect("service directory")
+}
+
+// config/service-spellbook.json prices the spells these tests cast.
+fn spellbooks() -> SpellbookRegistry {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../../../config/service-spellbook.json");
+    SpellbookRegistry::from_file(path).expect("service spellbook")
+}
+
+#[actix_rt::test]
+async fn test_continuebee() {
+
+    let mut saved_user: Option<ContinuebeeUser>;
+    let continuebee = Continuebee::with_directory(&directory(), None);
+    let backend = Continuebee::with_directory(&directory(), None);
+    let hash = "firstHash";
+    let second_hash = "secondHash";
+
+    async fn create_user(continuebee: &Continuebee, hash: &str) -> Option<ContinuebeeUser> {
+        let result = continuebee.create_user(hash).await;
+
+        match result {
+            Ok(user) => {
+                assert_eq!(
+                    user.uuid.len(),
+                    36
+                );
+                Some(user)
+            },
+            Err(error) => {
+                eprintln!("Error occurred create_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn check_hash(continuebee: &Continuebee, saved_user: &ContinuebeeUser, hash: &str) -> Option<bool> {
+        let result = continuebee.check_hash(&saved_user.uuid, hash).await;
+
+        match result {
+            Ok(valid) => Some(valid),
+            Err(error) => {
+                eprintln!("Error occurred check_hash: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn update_hash(continuebee: &Continuebee, saved_user: &ContinuebeeUser, hash: &str, new_hash: &str) -> Option<ContinuebeeUser> {
+        let result = continuebee.update_hash(&saved_user.uuid, hash, new_hash).await;
+
+        match result {
+            Ok(user) => {
+                assert_eq!(user.uuid, saved_user.uuid);
+                Some(user)
+            },
+            Err(error) => {
+                eprintln!("Error occurred update_hash: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn delete_user(continuebee: &Continuebee, saved_user: &ContinuebeeUser, hash: &str) -> Option<SuccessResult> {
+        let result = continuebee.delete_user(&saved_user.uuid, hash).await;
+
+        match result {
+            Ok(success) => {
+                assert!(success.success);
+                Some(success)
+            },
+            Err(error) => {
+                eprintln!("Error occurred delete_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    saved_user = Some(create_user(&continuebee, hash).await.expect("user"));
+
+    if let Some(ref user) = saved_user {
+        assert!(check_hash(&continuebee, user, hash).await.expect("check hash"));
+        saved_user = Some(update_hash(&continuebee, user, hash, second_hash).await.expect("update hash"));
+    } else {
+        panic!("Failed to get user");
+    }
+
+    if let Some(ref user) = saved_user {
+        assert!(!check_hash(&continuebee, user, hash).await.expect("check old hash"));
+
+        // A backend checks a session the user signed without holding their key.
+        let proof = ContinuationProof::new(&continuebee.sessionless, &user.uuid, second_hash, &Continuebee::get_timestamp());
+        assert!(backend.check_proof(&proof).await.expect("check proof"));
+
+        delete_user(&continuebee, user, second_hash).await.expect("delete");
+    } else {
+        panic!("Failed to get user");
+    }
+}
+
+#[actix_rt::test]
+async fn test_continuebee_magic() {
+    let fount = Fount::with_directory(&directory(), None).with_spellbooks(spellbooks());
+    let continuebee = Continuebee::with_directory(&directory(), None);
+    let caster = fount.create_user().await.expect("fount user");
+
+    let user = continuebee.magic_create_user(&fount, &caster.uuid, 0, "magicHash").await.expect("continuebeeUserCreate");
+    assert_eq!(user.pub_key, Some(continuebee.sessionless.public_key().to_hex()));
+    let again = continuebee.magic_create_user(&fount, &caster.uuid, 1, "magicHash").await.expect("continuebeeUserCreate again");
+    assert_eq!(again.uuid, user.uuid);
+
+    let user = continuebee.magic_update_hash(&fount, &caster.uuid, 2, &user.uuid, "magicHash", "newMagicHash").await.expect("continuebeeUserUpdateHash");
+    assert_eq!(user.hash.as_deref(), Some("newMagicHash"));
+    assert!(continuebee.magic_update_hash(&fount, &caster.uuid, 3, &user.uuid, "magicHash", "otherHash").await.is_err());
+
+    let deleted = continuebee.magic_delete_user(&fount, &caster.uuid, 4, &user.uuid, "newMagicHash").await.expect("continuebeeUserDelete");
+    assert!(deleted.success);
+}
+
+#[test]
+fn test_continuation_proof() {
+    let user = Sessionless::new();
+    let now: u64 = 1700000000000;
+    let proof = ContinuationProof::new(&user, "5d2e0f3a-0000-4000-8000-000000000001", "sessionHash", &now.to_string());
+
+    assert_eq!(proof.message(), format!("{}5d2e0f3a-0000-4000-8000-000000000001sessionHash", now));
+    assert_eq!(proof.verify_at(now + 1000), Ok(()));
+    assert_eq!(proof.verify_at(now + 300001), Err(ProofError::StaleTimestamp { timestamp: now, now: now + 300001 }));
+
+    let rotated = ContinuationProof { hash: "rotatedHash".to_string(), ..proof.clone() };
+    assert_eq!(rotated.verify_at(now), Err(ProofError::Mismatch));
+
+    let impostor = ContinuationProof { pub_key: Sessionless::new().public_key().to_hex(), ..proof.clone() };
+    assert_eq!(impostor.verify_at(now), Err(ProofError::Mismatch));
+
+    let garbled = ContinuationProof { timestamp: "yesterday".to_string(), ..proof };
+    assert_eq!(garbled.verify_at(now), Err(ProofError::InvalidTimestamp("yesterday".to_string())));
+}
+
+#[actix_rt::test]
+async fn test_stale_proof_is_not_sent() {
+    // Nothing listens on port 9, so only the local check can produce this error.
+    let backend = Continuebee::new(Some("http://127.0.0.1:9/".to_string()), None);
+    let proof = ContinuationProof::new(&Sessionless::new(), "5d2e0f3a-0000-4000-8000-000000000001", "sessionHash", "1");
+
+    let error = backend.check_proof(&proof).await.expect_err("stale");
+    assert!(error.to_string().starts_with("stale timestamp"));
+}
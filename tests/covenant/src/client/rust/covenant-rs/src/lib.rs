@@ -0,0 +1,202 @@
+pub mod structs;
+pub mod workflow;
+
+#[cfg(test)]
+mod tests;
+
+use reqwest::{Client, Response};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::option::Option;
+pub use crate::structs::{Contract, ContractError, ContractStep, ContractSummary, ContractUpdate, CovenantResponse, SignResult, StepSignature, SuccessResult, SvgOptions, SvgTheme};
+pub use crate::workflow::{step_message, ContractWorkflow, NextAction, SignRequest, StepState, StepStatus, WorkflowError, WorkflowEvent};
+pub use service_directory_rs::{Service, ServiceDirectory};
+
+pub struct Covenant {
+    base_url: String,
+    client: Client,
+    pub sessionless: Sessionless,
+    /// Covenant has no users of its own; this uuid is just signed into each
+    /// request alongside the key.
+    pub user_uuid: String,
+}
+
+impl Covenant {
+    pub fn new(base_url: Option<String>, sessionless: Option<Sessionless>) -> Self {
+        Covenant {
+            base_url: base_url.unwrap_or("https://dev.covenant.allyabase.com/".to_string()),
+            client: Client::new(),
+            sessionless: sessionless.unwrap_or(Sessionless::new()),
+            user_uuid: Sessionless::generate_uuid().to_string(),
+        }
+    }
+
+    /// Points the client at covenant in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory, sessionless: Option<Sessionless>) -> Self {
+        Self::new(Some(directory.url(Service::Covenant)), sessionless)
+    }
+
+    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.client.get(url).send().await
+    }
+
+    async fn post(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .post(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn put(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .put(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn delete(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .delete(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    fn get_timestamp() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis()
+            .to_string()
+    }
+
+    async fn data<T: DeserializeOwned>(res: Response) -> Result<T, Box<dyn std::error::Error>> {
+        let response: CovenantResponse<T> = res.json().await?;
+        response.into_result()
+    }
+
+    // Every authenticated request carries the same fields; `contract_uuid` is
+    // part of the message for anything touching an existing contract.
+    fn auth_payload(&self, timestamp: &str, contract_uuid: Option<&str>) -> serde_json::Map<String, Value> {
+        let message = format!("{}{}{}", timestamp, self.user_uuid, contract_uuid.unwrap_or_default());
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let mut payload = serde_json::Map::new();
+        payload.insert("signature".to_string(), json!(signature));
+        payload.insert("timestamp".to_string(), json!(timestamp));
+        payload.insert("userUUID".to_string(), json!(self.user_uuid));
+        payload.insert("pubKey".to_string(), json!(self.sessionless.public_key().to_hex()));
+        payload
+    }
+
+    /// Creates `contract`, refusing locally if covenant would reject it.
+    pub async fn create_contract(&self, contract: &Contract) -> Result<Contract, Box<dyn std::error::Error>> {
+        contract.validate()?;
+
+        let timestamp = Self::get_timestamp();
+        let mut payload = self.auth_payload(&timestamp, None);
+        payload.insert("title".to_string(), json!(contract.title));
+        payload.insert("description".to_string(), json!(contract.description));
+        payload.insert("participants".to_string(), json!(contract.participants));
+        payload.insert("steps".to_string(), json!(contract.steps));
+        if let Some(product_uuid) = &contract.product_uuid {
+            payload.insert("productUuid".to_string(), json!(product_uuid));
+        }
+
+        let url = format!("{}contract", self.base_url);
+        let res = self.post(&url, Value::Object(payload)).await?;
+
+        Self::data(res).await
+    }
+
+    pub async fn get_contract(&self, uuid: &str) -> Result<Contract, Box<dyn std::error::Error>> {
+        let url = format!("{}contract/{}", self.base_url, uuid);
+        let res = self.get(&url).await?;
+
+        Self::data(res).await
+    }
+
+    pub async fn update_contract(&self, uuid: &str, update: &ContractUpdate) -> Result<Contract, Box<dyn std::error::Error>> {
+        if let Some(participants) = &update.participants {
+            if participants.len() < 2 {
+                return Err(ContractError::TooFewParticipants(participants.len()).into());
+            }
+        }
+        if update.steps.as_ref().is_some_and(|steps| steps.is_empty()) {
+            return Err(ContractError::NoSteps.into());
+        }
+
+        let timestamp = Self::get_timestamp();
+        let mut payload = self.auth_payload(&timestamp, Some(uuid));
+        if let Value::Object(fields) = serde_json::to_value(update)? {
+            payload.extend(fields);
+        }
+
+        let url = format!("{}contract/{}", self.base_url, uuid);
+        let res = self.put(&url, Value::Object(payload)).await?;
+
+        Self::data(res).await
+    }
+
+    pub async fn delete_contract(&self, uuid: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let payload = self.auth_payload(&timestamp, Some(uuid));
+
+        let url = format!("{}contract/{}", self.base_url, uuid);
+        let res = self.delete(&url, Value::Object(payload)).await?;
+        let _deleted: Value = Self::data(res).await?;
+
+        Ok(SuccessResult { success: true })
+    }
+
+    /// Signs `step_id` of the contract at `uuid` as this client's key. Use
+    /// `sign_workflow_step` to have the step order checked first.
+    pub async fn sign_step(&self, uuid: &str, step_id: &str) -> Result<SignResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let step_message = step_message(&timestamp, &self.user_uuid, uuid, step_id);
+        let step_signature = self.sessionless.sign(&step_message).to_hex();
+
+        let mut payload = self.auth_payload(&timestamp, Some(uuid));
+        payload.insert("stepId".to_string(), json!(step_id));
+        payload.insert("stepSignature".to_string(), json!(step_signature));
+
+        let url = format!("{}contract/{}/sign", self.base_url, uuid);
+        let res = self.put(&url, Value::Object(payload)).await?;
+
+        Self::data(res).await
+    }
+
+    pub async fn send_sign_request(&self, request: &SignRequest) -> Result<SignResult, Box<dyn std::error::Error>> {
+        let url = format!("{}contract/{}/sign", self.base_url, request.contract_uuid);
+        let res = self.put(&url, request.payload()).await?;
+
+        Self::data(res).await
+    }
+
+    /// Lists contracts, only those `participant` is part of when given.
+    pub async fn list_contracts(&self, participant: Option<&str>) -> Result<Vec<ContractSummary>, Box<dyn std::error::Error>> {
+        let url = match participant {
+            Some(participant) => format!("{}contracts?participant={}", self.base_url, urlencoding::encode(participant)),
+            None => format!("{}contracts", self.base_url)
+        };
+        let res = self.get(&url).await?;
+
+        Self::data(res).await
+    }
+
+    pub async fn get_contract_svg(&self, uuid: &str, options: &SvgOptions) -> Result<String, Box<dyn std::error::Error>> {
+        let url = format!("{}contract/{}/svg{}", self.base_url, uuid, options.query());
+        let res = self.get(&url).await?;
+        if !res.status().is_success() {
+            let response: CovenantResponse<Value> = res.json().await?;
+            return Err(response.error.unwrap_or("could not render contract".to_string()).into());
+        }
+
+        Ok(res.text().await?)
+    }
+}
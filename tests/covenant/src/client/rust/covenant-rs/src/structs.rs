@@ -0,0 +1,272 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use serde_json::Value;
+
+// Covenant's server answers in snake_case while its javascript client hands
+// back camelCase, so the dated fields accept both spellings.
+
+/// A participant's signature on a step.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct StepSignature {
+    pub signature: String,
+    pub timestamp: Option<String>,
+    #[serde(rename = "userUUID")]
+    pub user_uuid: Option<String>,
+    pub pub_key: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+// Older contracts store the bare signature string instead of an object.
+fn deserialize_signatures<'de, D>(deserializer: D) -> Result<BTreeMap<String, Option<StepSignature>>, D::Error>
+where
+    D: Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Bare(String),
+        Full(StepSignature)
+    }
+
+    let stored: BTreeMap<String, Option<Stored>> = BTreeMap::deserialize(deserializer)?;
+    Ok(stored
+        .into_iter()
+        .map(|(participant, signature)| {
+            let signature = signature.map(|signature| match signature {
+                Stored::Bare(signature) => StepSignature { signature, ..Default::default() },
+                Stored::Full(signature) => signature
+            });
+            (participant, signature)
+        })
+        .collect())
+}
+
+/// One step of a contract. `signatures` has an entry for every participant,
+/// `None` until they sign.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct ContractStep {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    pub description: String,
+    #[serde(default)]
+    pub order: usize,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default, deserialize_with = "deserialize_signatures")]
+    pub signatures: BTreeMap<String, Option<StepSignature>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magic_spell: Option<Value>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+impl ContractStep {
+    pub fn new(description: &str) -> Self {
+        ContractStep {
+            description: description.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    /// Attaches the spell covenant casts once every participant has signed.
+    pub fn with_magic_spell(mut self, magic_spell: Value) -> Self {
+        self.magic_spell = Some(magic_spell);
+        self
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct Contract {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub uuid: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub participants: Vec<String>,
+    #[serde(default)]
+    pub steps: Vec<ContractStep>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(alias = "created_at", skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<Value>,
+    #[serde(alias = "updated_at", skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<Value>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+impl Contract {
+    pub fn new(title: &str, description: &str, participants: Vec<String>, steps: Vec<ContractStep>) -> Self {
+        Contract {
+            title: title.to_string(),
+            description: description.to_string(),
+            participants,
+            steps,
+            ..Default::default()
+        }
+    }
+
+    /// Checks the rules covenant enforces on `POST /contract`.
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.title.trim().is_empty() {
+            return Err(ContractError::MissingTitle);
+        }
+        if self.participants.len() < 2 {
+            return Err(ContractError::TooFewParticipants(self.participants.len()));
+        }
+        if self.steps.is_empty() {
+            return Err(ContractError::NoSteps);
+        }
+
+        Ok(())
+    }
+
+    pub fn step(&self, step_id: &str) -> Option<&ContractStep> {
+        self.steps.iter().find(|step| step.id == step_id)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContractError {
+    MissingTitle,
+    TooFewParticipants(usize),
+    NoSteps
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractError::MissingTitle => write!(f, "contract needs a title"),
+            ContractError::TooFewParticipants(count) => write!(f, "contract needs at least 2 participants, has {}", count),
+            ContractError::NoSteps => write!(f, "contract needs at least one step")
+        }
+    }
+}
+
+impl std::error::Error for ContractError {}
+
+/// The fields `PUT /contract/:uuid` can change. Unset fields are left alone.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct ContractUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub participants: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<ContractStep>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>
+}
+
+/// A contract as `GET /contracts` lists it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct ContractSummary {
+    pub uuid: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub participants: Vec<String>,
+    pub status: Option<String>,
+    #[serde(default, alias = "step_count")]
+    pub step_count: usize,
+    #[serde(default, alias = "completed_steps")]
+    pub completed_steps: usize,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct SignResult {
+    pub contract_uuid: String,
+    pub step_id: String,
+    #[serde(default)]
+    pub step_completed: bool,
+    #[serde(default)]
+    pub magic_triggered: bool,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SvgTheme {
+    Light,
+    Dark
+}
+
+impl SvgTheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SvgTheme::Light => "light",
+            SvgTheme::Dark => "dark"
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SvgOptions {
+    pub theme: Option<SvgTheme>,
+    pub width: Option<u32>,
+    pub height: Option<u32>
+}
+
+impl SvgOptions {
+    pub(crate) fn query(&self) -> String {
+        let mut params = vec![];
+        if let Some(theme) = self.theme {
+            params.push(format!("theme={}", theme.as_str()));
+        }
+        if let Some(width) = self.width {
+            params.push(format!("width={}", width));
+        }
+        if let Some(height) = self.height {
+            params.push(format!("height={}", height));
+        }
+
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
+}
+
+/// Covenant wraps every answer as `{ success, data }` or `{ success, error }`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct CovenantResponse<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    pub error: Option<String>
+}
+
+impl<T> CovenantResponse<T> {
+    pub fn into_result(self) -> Result<T, Box<dyn std::error::Error>> {
+        match (self.success, self.data) {
+            (true, Some(data)) => Ok(data),
+            _ => Err(self.error.unwrap_or("covenant did not return any data".to_string()).into())
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuccessResult {
+    pub success: bool
+}
@@ -0,0 +1,312 @@
+use crate::{Contract, ContractError, ContractStep, ContractSummary, ContractUpdate, Covenant, SignResult, SvgOptions, SvgTheme};
+use crate::{step_message, ContractWorkflow, NextAction, StepSignature, StepStatus, WorkflowError, WorkflowEvent};
+use sessionless::hex::{FromHex, IntoHex};
+use sessionless::{PublicKey, Signature, Sessionless};
+use serde_json::json;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::direct_ports("localhost")).expect("service directory")
+}
+
+fn test_contract(covenant: &Covenant) -> Contract {
+    Contract::new(
+        "Rust Magical Contract",
+        "A contract made by the rust client",
+        vec![
+            "02a1b2c3d4e5f6789012345678901234567890123456789012345678901234567890".to_string(),
+            covenant.sessionless.public_key().to_hex()
+        ],
+        vec![
+            ContractStep::new("Complete project proposal").with_magic_spell(json!({ "type": "payment", "amount": 100 })),
+            ContractStep::new("Final delivery and review")
+        ]
+    )
+}
+
+#[actix_rt::test]
+async fn test_covenant() {
+
+    let covenant = Covenant::with_directory(&directory(), None);
+
+    async fn create_contract(covenant: &Covenant) -> Option<Contract> {
+        let contract = test_contract(covenant);
+        let result = covenant.create_contract(&contract).await;
+
+        match result {
+            Ok(created) => {
+                assert_eq!(created.uuid.len(), 36);
+                assert_eq!(created.title, contract.title);
+                assert_eq!(created.steps.len(), 2);
+                assert!(created.steps.iter().all(|step| step.signatures.len() == 2));
+                Some(created)
+            },
+            Err(error) => {
+                eprintln!("Error occurred create_contract: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn get_contract(covenant: &Covenant, saved_contract: &Contract) -> Option<Contract> {
+        let result = covenant.get_contract(&saved_contract.uuid).await;
+
+        match result {
+            Ok(contract) => {
+                assert_eq!(contract.uuid, saved_contract.uuid);
+                Some(contract)
+            },
+            Err(error) => {
+                eprintln!("Error occurred get_contract: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn update_contract(covenant: &Covenant, saved_contract: &Contract) -> Option<Contract> {
+        let update = ContractUpdate {
+            title: Some("Updated Rust Contract".to_string()),
+            ..Default::default()
+        };
+        let result = covenant.update_contract(&saved_contract.uuid, &update).await;
+
+        match result {
+            Ok(contract) => {
+                assert_eq!(contract.title, "Updated Rust Contract");
+                Some(contract)
+            },
+            Err(error) => {
+                eprintln!("Error occurred update_contract: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn sign_step(covenant: &Covenant, saved_contract: &Contract) -> Option<SignResult> {
+        let step_id = &saved_contract.steps[0].id;
+        let result = covenant.sign_step(&saved_contract.uuid, step_id).await;
+
+        match result {
+            Ok(signed) => {
+                assert_eq!(&signed.step_id, step_id);
+                assert!(!signed.step_completed);
+                Some(signed)
+            },
+            Err(error) => {
+                eprintln!("Error occurred sign_step: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn list_contracts(covenant: &Covenant) -> Option<Vec<ContractSummary>> {
+        let pub_key = covenant.sessionless.public_key().to_hex();
+        let result = covenant.list_contracts(Some(&pub_key)).await;
+
+        match result {
+            Ok(contracts) => {
+                assert!(!contracts.is_empty());
+                assert!(contracts.iter().all(|contract| contract.participants.contains(&pub_key)));
+                Some(contracts)
+            },
+            Err(error) => {
+                eprintln!("Error occurred list_contracts: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn get_contract_svg(covenant: &Covenant, saved_contract: &Contract) -> Option<String> {
+        let options = SvgOptions { theme: Some(SvgTheme::Dark), width: Some(1000), height: Some(800) };
+        let result = covenant.get_contract_svg(&saved_contract.uuid, &options).await;
+
+        match result {
+            Ok(svg) => {
+                assert!(svg.contains("<svg"));
+                assert!(svg.contains("width=\"1000\""));
+                Some(svg)
+            },
+            Err(error) => {
+                eprintln!("Error occurred get_contract_svg: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn delete_contract(covenant: &Covenant, saved_contract: &Contract) {
+        let result = covenant.delete_contract(&saved_contract.uuid).await;
+
+        match result {
+            Ok(success) => {
+                assert!(success.success);
+                assert!(covenant.get_contract(&saved_contract.uuid).await.is_err());
+            },
+            Err(error) => {
+                eprintln!("Error occurred delete_contract: {}", error);
+                println!("Error details: {:?}", error);
+                panic!("delete contract");
+            }
+        }
+    }
+
+    let saved_contract = create_contract(&covenant).await.expect("contract");
+    let saved_contract = get_contract(&covenant, &saved_contract).await.expect("get contract");
+    let saved_contract = update_contract(&covenant, &saved_contract).await.expect("update contract");
+    sign_step(&covenant, &saved_contract).await.expect("sign step");
+    list_contracts(&covenant).await.expect("list contracts");
+    get_contract_svg(&covenant, &saved_contract).await.expect("svg");
+    delete_contract(&covenant, &saved_contract).await;
+}
+
+#[test]
+fn test_contract_rules() {
+    let covenant = Covenant::new(None, None);
+    let contract = test_contract(&covenant);
+    assert_eq!(contract.validate(), Ok(()));
+
+    let untitled = Contract { title: " ".to_string(), ..contract.clone() };
+    assert_eq!(untitled.validate(), Err(ContractError::MissingTitle));
+
+    let alone = Contract { participants: vec![covenant.sessionless.public_key().to_hex()], ..contract.clone() };
+    assert_eq!(alone.validate(), Err(ContractError::TooFewParticipants(1)));
+
+    let stepless = Contract { steps: vec![], ..contract };
+    assert_eq!(stepless.validate(), Err(ContractError::NoSteps));
+}
+
+#[actix_rt::test]
+async fn test_invalid_contract_is_not_sent() {
+    // Nothing listens on port 9, so only a local refusal can produce this error.
+    let covenant = Covenant::new(Some("http://127.0.0.1:9/".to_string()), None);
+    let contract = Contract { steps: vec![], ..test_contract(&covenant) };
+
+    let error = covenant.create_contract(&contract).await.expect_err("no steps");
+    assert_eq!(error.to_string(), ContractError::NoSteps.to_string());
+}
+
+#[test]
+fn test_contract_shapes() {
+    let contract: Contract = serde_json::from_value(json!({
+        "uuid": "a9b5f1c2-0000-4000-8000-000000000001",
+        "title": "Test Magical Contract",
+        "participants": ["alice", "bob"],
+        "status": "active",
+        "created_at": 1700000000000u64,
+        "steps": [{
+            "id": "step-1",
+            "description": "Complete project proposal",
+            "order": 0,
+            "completed": false,
+            "signatures": {
+                "alice": { "signature": "abc", "timestamp": "1700000000000" },
+                "bob": null
+            }
+        }, {
+            "id": "step-2",
+            "description": "Final delivery",
+            "order": 1,
+            "completed": false,
+            "signatures": { "alice": "def", "bob": null }
+        }]
+    })).expect("contract");
+
+    assert!(contract.created_at.is_some());
+    let step = contract.step("step-1").expect("step");
+    assert_eq!(step.signatures["alice"].as_ref().map(|signature| signature.signature.as_str()), Some("abc"));
+    assert!(step.signatures["bob"].is_none());
+    assert_eq!(contract.steps[1].signatures["alice"].as_ref().map(|signature| signature.signature.as_str()), Some("def"));
+
+    let summary: ContractSummary = serde_json::from_value(json!({
+        "uuid": contract.uuid,
+        "title": contract.title,
+        "participants": contract.participants,
+        "step_count": 2,
+        "completed_steps": 0
+    })).expect("summary");
+    assert_eq!(summary.step_count, 2);
+
+    assert_eq!(SvgOptions::default().query(), "");
+    let options = SvgOptions { theme: Some(SvgTheme::Dark), width: Some(1000), height: Some(800) };
+    assert_eq!(options.query(), "?theme=dark&width=1000&height=800");
+}
+
+#[test]
+fn test_step_workflow() {
+    let alice = Sessionless::new();
+    let bob = Sessionless::new();
+    let (alice_key, bob_key) = (alice.public_key().to_hex(), bob.public_key().to_hex());
+
+    let contract: Contract = serde_json::from_value(json!({
+        "uuid": "a9b5f1c2-0000-4000-8000-000000000002",
+        "title": "Escrow",
+        "participants": [alice_key, bob_key],
+        "steps": [{
+            "id": "deliver",
+            "description": "Deliver the goods",
+            "order": 1,
+            "completed": false,
+            "signatures": { alice_key.clone(): null, bob_key.clone(): null }
+        }, {
+            "id": "deposit",
+            "description": "Deposit the payment",
+            "order": 0,
+            "completed": false,
+            "signatures": { alice_key.clone(): null, bob_key.clone(): null },
+            "magicSpell": { "type": "payment", "amount": 100 }
+        }]
+    })).expect("contract");
+    let mut workflow = ContractWorkflow::new(contract.clone());
+
+    let states = workflow.states();
+    assert_eq!(states[0].step_id, "deposit");
+    assert_eq!(states[0].status, StepStatus::Open);
+    assert_eq!(states[1].status, StepStatus::Locked);
+    assert_eq!(workflow.pending_signers(), vec![alice_key.clone(), bob_key.clone()]);
+    assert_eq!(workflow.next_action(&alice_key), NextAction::Sign { step_id: "deposit".to_string() });
+    assert_eq!(workflow.next_action("stranger"), NextAction::NotParticipant);
+
+    let out_of_order = workflow.sign_request(&alice, "alice-uuid", "deliver", "1700000000000").expect_err("locked step");
+    assert_eq!(out_of_order, WorkflowError::OutOfOrder { step_id: "deliver".to_string(), open_step: "deposit".to_string() });
+
+    let request = workflow.sign_request(&alice, "alice-uuid", "deposit", "1700000000000").expect("request");
+    assert_eq!(request.step_message, step_message("1700000000000", "alice-uuid", &contract.uuid, "deposit"));
+    assert_eq!(request.step_message, format!("1700000000000alice-uuid{}deposit", contract.uuid));
+    let signature = Signature::from_hex(&request.step_signature).expect("signature");
+    let pub_key = PublicKey::from_hex(&alice_key).expect("key");
+    assert!(alice.verify(&request.step_message, &pub_key, &signature).is_ok());
+    assert_eq!(request.payload()["stepSignature"], json!(request.step_signature));
+
+    let signed = |signature: &str| StepSignature { signature: signature.to_string(), ..Default::default() };
+    let events = workflow.record_signature(&alice_key, "deposit", signed("a")).expect("alice signs");
+    assert_eq!(events, vec![WorkflowEvent::StepSigned { step_id: "deposit".to_string(), participant: alice_key.clone() }]);
+    assert_eq!(workflow.next_action(&alice_key), NextAction::WaitForOthers { step_id: "deposit".to_string(), pending: vec![bob_key.clone()] });
+    assert!(matches!(workflow.record_signature(&alice_key, "deposit", signed("a")), Err(WorkflowError::AlreadySigned { .. })));
+
+    let events = workflow.record_signature(&bob_key, "deposit", signed("b")).expect("bob signs");
+    assert_eq!(events[1], WorkflowEvent::StepCompleted {
+        step_id: "deposit".to_string(),
+        order: 0,
+        magic_spell: Some(json!({ "type": "payment", "amount": 100 }))
+    });
+    assert_eq!(workflow.next_action(&bob_key), NextAction::Sign { step_id: "deliver".to_string() });
+    assert!(matches!(workflow.check_signable(&bob_key, "deposit"), Err(WorkflowError::AlreadyCompleted(_))));
+
+    // A refreshed copy from covenant reports what happened since.
+    let mut finished = workflow.contract().clone();
+    for step in finished.steps.iter_mut() {
+        step.completed = true;
+    }
+    let events = workflow.refresh(finished);
+    assert_eq!(events, vec![
+        WorkflowEvent::StepCompleted { step_id: "deliver".to_string(), order: 1, magic_spell: None },
+        WorkflowEvent::ContractCompleted { contract_uuid: contract.uuid.clone() }
+    ]);
+    assert_eq!(workflow.next_action(&alice_key), NextAction::Done);
+}
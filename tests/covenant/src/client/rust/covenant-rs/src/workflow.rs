@@ -0,0 +1,308 @@
+use std::fmt;
+use serde_json::{json, Value};
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use crate::structs::{Contract, ContractStep, SignResult, StepSignature};
+use crate::Covenant;
+
+/// The message a participant's `stepSignature` signs:
+/// `timestamp + userUUID + contractUUID + stepId`.
+pub fn step_message(timestamp: &str, user_uuid: &str, contract_uuid: &str, step_id: &str) -> String {
+    format!("{}{}{}{}", timestamp, user_uuid, contract_uuid, step_id)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StepStatus {
+    /// Every participant has signed.
+    Completed,
+    /// The earliest incomplete step, the only one that can be signed.
+    Open,
+    /// Waiting on an earlier step.
+    Locked
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepState {
+    pub step_id: String,
+    pub order: usize,
+    pub status: StepStatus,
+    pub signed: Vec<String>,
+    pub pending: Vec<String>
+}
+
+/// What a participant should do next.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NextAction {
+    Sign { step_id: String },
+    WaitForOthers { step_id: String, pending: Vec<String> },
+    Done,
+    NotParticipant
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorkflowEvent {
+    StepSigned { step_id: String, participant: String },
+    StepCompleted { step_id: String, order: usize, magic_spell: Option<Value> },
+    ContractCompleted { contract_uuid: String }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorkflowError {
+    UnknownStep(String),
+    NotParticipant(String),
+    OutOfOrder { step_id: String, open_step: String },
+    AlreadySigned { step_id: String, participant: String },
+    AlreadyCompleted(String)
+}
+
+impl fmt::Display for WorkflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkflowError::UnknownStep(step_id) => write!(f, "Step not found: {}", step_id),
+            WorkflowError::NotParticipant(participant) => write!(f, "{} is not a participant of this contract", participant),
+            WorkflowError::OutOfOrder { step_id, open_step } => write!(f, "step {} can't be signed before step {} completes", step_id, open_step),
+            WorkflowError::AlreadySigned { step_id, participant } => write!(f, "{} has already signed step {}", participant, step_id),
+            WorkflowError::AlreadyCompleted(step_id) => write!(f, "step {} is already completed", step_id)
+        }
+    }
+}
+
+impl std::error::Error for WorkflowError {}
+
+/// A signed `PUT /contract/:uuid/sign` body, built ahead of sending so it can
+/// be inspected or handed to another process.
+#[derive(Clone, Debug)]
+pub struct SignRequest {
+    pub timestamp: String,
+    pub user_uuid: String,
+    pub pub_key: String,
+    pub contract_uuid: String,
+    pub step_id: String,
+    pub signature: String,
+    pub step_message: String,
+    pub step_signature: String
+}
+
+impl SignRequest {
+    pub fn payload(&self) -> Value {
+        json!({
+            "signature": self.signature,
+            "timestamp": self.timestamp,
+            "userUUID": self.user_uuid,
+            "pubKey": self.pub_key,
+            "stepId": self.step_id,
+            "stepSignature": self.step_signature
+        })
+    }
+}
+
+/// Tracks where a contract's steps stand. Steps have to be signed in `order`:
+/// only the earliest incomplete step is open, and it completes once every
+/// participant has signed it.
+#[derive(Clone, Debug)]
+pub struct ContractWorkflow {
+    contract: Contract
+}
+
+impl ContractWorkflow {
+    pub fn new(mut contract: Contract) -> Self {
+        contract.steps.sort_by_key(|step| step.order);
+        ContractWorkflow { contract }
+    }
+
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    fn has_signed(step: &ContractStep, participant: &str) -> bool {
+        step.signatures.get(participant).is_some_and(|signature| signature.is_some())
+    }
+
+    fn pending_for(&self, step: &ContractStep) -> Vec<String> {
+        self.contract.participants
+            .iter()
+            .filter(|participant| !Self::has_signed(step, participant))
+            .cloned()
+            .collect()
+    }
+
+    /// The step currently accepting signatures.
+    pub fn open_step(&self) -> Option<&ContractStep> {
+        self.contract.steps.iter().find(|step| !step.completed)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.open_step().is_none()
+    }
+
+    pub fn states(&self) -> Vec<StepState> {
+        let open_id = self.open_step().map(|step| step.id.clone());
+        self.contract.steps
+            .iter()
+            .map(|step| {
+                let status = if step.completed {
+                    StepStatus::Completed
+                } else if Some(&step.id) == open_id.as_ref() {
+                    StepStatus::Open
+                } else {
+                    StepStatus::Locked
+                };
+                let pending = self.pending_for(step);
+                let signed = self.contract.participants
+                    .iter()
+                    .filter(|participant| !pending.contains(participant))
+                    .cloned()
+                    .collect();
+
+                StepState { step_id: step.id.clone(), order: step.order, status, signed, pending }
+            })
+            .collect()
+    }
+
+    /// Who still has to sign the open step.
+    pub fn pending_signers(&self) -> Vec<String> {
+        self.open_step().map(|step| self.pending_for(step)).unwrap_or_default()
+    }
+
+    pub fn next_action(&self, participant: &str) -> NextAction {
+        if !self.contract.participants.iter().any(|known| known == participant) {
+            return NextAction::NotParticipant;
+        }
+        let Some(step) = self.open_step() else {
+            return NextAction::Done;
+        };
+
+        if Self::has_signed(step, participant) {
+            NextAction::WaitForOthers { step_id: step.id.clone(), pending: self.pending_for(step) }
+        } else {
+            NextAction::Sign { step_id: step.id.clone() }
+        }
+    }
+
+    /// Checks that `participant` may sign `step_id` right now.
+    pub fn check_signable(&self, participant: &str, step_id: &str) -> Result<&ContractStep, WorkflowError> {
+        let step = self.contract.step(step_id).ok_or_else(|| WorkflowError::UnknownStep(step_id.to_string()))?;
+        if !self.contract.participants.iter().any(|known| known == participant) {
+            return Err(WorkflowError::NotParticipant(participant.to_string()));
+        }
+        if step.completed {
+            return Err(WorkflowError::AlreadyCompleted(step_id.to_string()));
+        }
+        if let Some(open) = self.open_step() {
+            if open.id != step.id {
+                return Err(WorkflowError::OutOfOrder { step_id: step_id.to_string(), open_step: open.id.clone() });
+            }
+        }
+        if Self::has_signed(step, participant) {
+            return Err(WorkflowError::AlreadySigned { step_id: step_id.to_string(), participant: participant.to_string() });
+        }
+
+        Ok(step)
+    }
+
+    /// Builds the signed request for `step_id` at `timestamp`, refusing to sign
+    /// anything but the open step.
+    pub fn sign_request(&self, sessionless: &Sessionless, user_uuid: &str, step_id: &str, timestamp: &str) -> Result<SignRequest, WorkflowError> {
+        let pub_key = sessionless.public_key().to_hex();
+        self.check_signable(&pub_key, step_id)?;
+
+        let contract_uuid = &self.contract.uuid;
+        let message = format!("{}{}{}", timestamp, user_uuid, contract_uuid);
+        let step_message = step_message(timestamp, user_uuid, contract_uuid, step_id);
+
+        Ok(SignRequest {
+            timestamp: timestamp.to_string(),
+            user_uuid: user_uuid.to_string(),
+            pub_key,
+            contract_uuid: contract_uuid.to_string(),
+            step_id: step_id.to_string(),
+            signature: sessionless.sign(&message).to_hex(),
+            step_signature: sessionless.sign(&step_message).to_hex(),
+            step_message
+        })
+    }
+
+    /// Records `participant`'s signature, completing the step when it was the
+    /// last one missing.
+    pub fn record_signature(&mut self, participant: &str, step_id: &str, signature: StepSignature) -> Result<Vec<WorkflowEvent>, WorkflowError> {
+        self.check_signable(participant, step_id)?;
+
+        let step = self.contract.steps.iter_mut().find(|step| step.id == step_id).expect("checked above");
+        step.signatures.insert(participant.to_string(), Some(signature));
+
+        let mut events = vec![WorkflowEvent::StepSigned { step_id: step_id.to_string(), participant: participant.to_string() }];
+        let all_signed = self.contract.participants.iter().all(|participant| Self::has_signed(step, participant));
+        if all_signed {
+            events.extend(self.complete_step(step_id));
+        }
+
+        Ok(events)
+    }
+
+    /// Marks `step_id` completed, e.g. when covenant reports it so before all
+    /// signatures are known locally. Does nothing for an already completed step.
+    pub fn complete_step(&mut self, step_id: &str) -> Vec<WorkflowEvent> {
+        let Some(step) = self.contract.steps.iter_mut().find(|step| step.id == step_id) else {
+            return vec![];
+        };
+        if step.completed {
+            return vec![];
+        }
+
+        step.completed = true;
+        let mut events = vec![WorkflowEvent::StepCompleted { step_id: step.id.clone(), order: step.order, magic_spell: step.magic_spell.clone() }];
+        if self.is_complete() {
+            events.push(WorkflowEvent::ContractCompleted { contract_uuid: self.contract.uuid.clone() });
+        }
+
+        events
+    }
+
+    /// Replaces the tracked contract with a freshly fetched copy, returning an
+    /// event for everything that happened in between.
+    pub fn refresh(&mut self, contract: Contract) -> Vec<WorkflowEvent> {
+        let previous = std::mem::replace(self, ContractWorkflow::new(contract));
+        let mut events = vec![];
+
+        for step in self.contract.steps.iter() {
+            let before = previous.contract.step(&step.id);
+            for participant in self.contract.participants.iter() {
+                let signed_before = before.is_some_and(|before| Self::has_signed(before, participant));
+                if Self::has_signed(step, participant) && !signed_before {
+                    events.push(WorkflowEvent::StepSigned { step_id: step.id.clone(), participant: participant.clone() });
+                }
+            }
+            if step.completed && !before.is_some_and(|before| before.completed) {
+                events.push(WorkflowEvent::StepCompleted { step_id: step.id.clone(), order: step.order, magic_spell: step.magic_spell.clone() });
+            }
+        }
+        if self.is_complete() && !previous.is_complete() {
+            events.push(WorkflowEvent::ContractCompleted { contract_uuid: self.contract.uuid.clone() });
+        }
+
+        events
+    }
+}
+
+impl Covenant {
+    /// Signs `step_id` after checking it's this key's turn, then records the
+    /// signature in `workflow`.
+    pub async fn sign_workflow_step(&self, workflow: &mut ContractWorkflow, step_id: &str) -> Result<(SignResult, Vec<WorkflowEvent>), Box<dyn std::error::Error>> {
+        let request = workflow.sign_request(&self.sessionless, &self.user_uuid, step_id, &Self::get_timestamp())?;
+        let signed = self.send_sign_request(&request).await?;
+
+        let signature = StepSignature {
+            signature: request.step_signature.clone(),
+            timestamp: Some(request.timestamp.clone()),
+            user_uuid: Some(request.user_uuid.clone()),
+            pub_key: Some(request.pub_key.clone()),
+            ..Default::default()
+        };
+        let mut events = workflow.record_signature(&request.pub_key, step_id, signature)?;
+        if signed.step_completed {
+            events.extend(workflow.complete_step(step_id));
+        }
+
+        Ok((signed, events))
+    }
+}
@@ -1,16 +1,23 @@
-use crate::{DoloresUser, Dolores, Feed, SuccessResult};
-use sessionless::hex::IntoHex;
-use sessionless::hex::FromHex;
-use sessionless::{Sessionless, PrivateKey};
-use std::collections::HashMap;
+use crate::{DoloresUser, Dolores, Feed, SuccessResult, Video};
+use crate::{range_header, tag_query};
+use std::ops::Bound;
 use serde_json::json;
-use serde_json::Value;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::path_based("http://nginx:80")).expect("service directory")
+}
+
+fn test_video_path() -> String {
+    format!("{}/../../../../mocha/test.mp4", env!("CARGO_MANIFEST_DIR"))
+}
 
 #[actix_rt::test]
 async fn test_dolores() {
 
     let mut saved_user: Option<DoloresUser>;
-    let dolores = Dolores::new(Some("http://nginx:80/dolores/".to_string()), None);
+    let dolores = Dolores::with_directory(&directory(), None);
 
     async fn create_user(dolores: &Dolores) -> Option<DoloresUser> {
     println!("creating user");
@@ -55,16 +62,13 @@ async fn test_dolores() {
 
     async fn put_mp4_video(dolores: &Dolores, saved_user: &DoloresUser) -> Option<SuccessResult> {
         let title = "My rust video".to_string();
-        let file_uri = "/Users/zachbabb/Work/planet-nine/dolores/test/mocha/test.mp4".to_string();
+        let file_uri = test_video_path();
 
         let result = dolores.put_video(&saved_user.uuid, &title, &file_uri).await;
 
         match result {
             Ok(success) => {
-                assert_eq!(
-                    success.success,
-                    true
-                );
+                assert!(success.success);
                 Some(success)
             }
             Err(error) => {
@@ -77,16 +81,15 @@ async fn test_dolores() {
 
     async fn put_mov_video(dolores: &Dolores, saved_user: &DoloresUser) -> Option<SuccessResult> {
         let title = "My rust mov".to_string();
-        let file_uri = "/Users/zachbabb/Work/planet-nine/dolores/test/mocha/test.mov".to_string();
+        // There's no mov fixture, so the mp4 is streamed from memory under a mov name.
+        let bytes = std::fs::read(test_video_path()).expect("test video");
+        let reader = std::io::Cursor::new(bytes);
 
-        let result = dolores.put_video(&saved_user.uuid, &title, &file_uri).await;
+        let result = dolores.put_video_from_reader(&saved_user.uuid, &title, "test.mov", reader).await;
 
         match result {
             Ok(success) => {
-                assert_eq!(
-                    success.success,
-                    true
-                );
+                assert!(success.success);
                 Some(success)
             }
             Err(error) => {
@@ -97,21 +100,47 @@ async fn test_dolores() {
         }
     }
 
-/*    async fn get_video(dolores: &Dolores, saved_user: &DoloresUser) -> Option<Video> {
-        let title = "My rust mov".to_string();
-        
-        let result = dolores.get_video(&saved_user.uuid, &title, ???).await;
+    async fn get_latest_feed(dolores: &Dolores, saved_user: &DoloresUser) -> Option<Feed> {
+        let result = dolores.get_feed(&saved_user.uuid, &[]).await;
+
+        match result {
+            Ok(feed) => {
+                assert!(feed.videos.len() >= 2, "{} videos in the feed", feed.videos.len());
+                Some(feed)
+            }
+            Err(error) => {
+                eprintln!("Error occurred getting latest feed: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
 
-        // this depends on storing the vide
-    }*/
+    async fn get_video_start(dolores: &Dolores, saved_user: &DoloresUser, feed: &Feed) -> Option<Video> {
+        let item = feed.videos.iter().find(|item| item.video_uuid().len() == 36)?;
+        let result = dolores.get_video_range(&saved_user.uuid, item.video_uuid(), 0..1024).await;
+
+        match result {
+            Ok(video) => {
+                assert!(!video.bytes.is_empty());
+                assert!(video.bytes.len() <= 1024);
+                Some(video)
+            }
+            Err(error) => {
+                eprintln!("Error occurred getting video: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
 
     async fn get_feed(dolores: &Dolores, saved_user: &DoloresUser) -> Option<Feed> {
-        let tags = "foo+bar";
+        let tags = ["foo", "bar"];
         let result = dolores.get_feed(&saved_user.uuid, &tags).await;
 
         match result {
             Ok(feed) => {
-                assert!(feed.videos.len() == 0);
+                assert!(feed.videos.is_empty());
                 Some(feed)
             }
             Err(error) => {
@@ -131,12 +160,55 @@ async fn test_dolores() {
     }
 
     if let Some(ref user) = saved_user {
-	Some(put_mp4_video(&dolores, user).await.expect("put mp4"));
-	Some(put_mov_video(&dolores, user).await.expect("put mov"));
-	Some(get_feed(&dolores, user).await.expect("get feed"));
+        put_mp4_video(&dolores, user).await.expect("put mp4");
+        put_mov_video(&dolores, user).await.expect("put mov");
+        let latest = get_latest_feed(&dolores, user).await.expect("get latest feed");
+        get_video_start(&dolores, user, &latest).await.expect("get video");
+        get_feed(&dolores, user).await.expect("get feed");
     } else {
         panic!("Failed to get user");
     }
 
 }
 
+
+#[test]
+fn test_range_header() {
+    assert_eq!(range_header(&(..)), Ok(None));
+    assert_eq!(range_header(&(0..)), Ok(None));
+    assert_eq!(range_header(&(1024..)), Ok(Some("bytes=1024-".to_string())));
+    assert_eq!(range_header(&(0..1024)), Ok(Some("bytes=0-1023".to_string())));
+    assert_eq!(range_header(&(10..=20)), Ok(Some("bytes=10-20".to_string())));
+    assert!(range_header(&(0..0)).is_err());
+    assert!(range_header(&(Bound::Included(20), Bound::Excluded(10))).is_err());
+    assert!(range_header(&(Bound::Excluded(u64::MAX), Bound::Unbounded)).is_err());
+}
+
+#[test]
+fn test_tag_query() {
+    assert_eq!(tag_query(&[]), "");
+    assert_eq!(tag_query(&["foo", "bar"]), "foo+bar");
+    assert_eq!(tag_query(&["rock & roll", "a+b", "c/d"]), "rock%20%26%20roll+a%2Bb+c%2Fd");
+}
+
+#[test]
+fn test_feed_shapes() {
+    let latest: Feed = serde_json::from_value(json!({
+        "videoPosts": [
+            { "uuid": "video-1", "post": { "uuid": "0f1d3b52-0000-4000-8000-000000000001" } }
+        ]
+    })).expect("latest feed");
+    assert_eq!(latest.videos.len(), 1);
+    assert_eq!(latest.videos[0].video_uuid(), "video-1");
+
+    let tagged: Feed = serde_json::from_value(json!({
+        "videos": [
+            { "post": { "uuid": "0f1d3b52-0000-4000-8000-000000000002" }, "tags": ["foo"] }
+        ]
+    })).expect("tagged feed");
+    assert_eq!(tagged.videos[0].video_uuid(), "0f1d3b52-0000-4000-8000-000000000002");
+    assert_eq!(tagged.videos[0].tags, vec!["foo".to_string()]);
+
+    let video = Video { content_range: Some("bytes 0-1023/1404548".to_string()), ..Default::default() };
+    assert_eq!(video.total_length(), Some(1404548));
+}
@@ -0,0 +1,260 @@
+pub mod structs;
+
+#[cfg(test)]
+mod tests;
+
+use reqwest::header::{CONTENT_RANGE, CONTENT_TYPE, RANGE};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Body, Client, Response};
+use serde_json::json;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::ops::{Bound, RangeBounds};
+use std::option::Option;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+pub use crate::structs::{DoloresUser, Feed, FeedItem, Post, SuccessResult, Video};
+pub use service_directory_rs::{Service, ServiceDirectory};
+
+pub struct Dolores {
+    base_url: String,
+    client: Client,
+    pub sessionless: Sessionless,
+}
+
+impl Dolores {
+    pub fn new(base_url: Option<String>, sessionless: Option<Sessionless>) -> Self {
+        Dolores {
+            base_url: base_url.unwrap_or("https://dev.dolores.allyabase.com/".to_string()),
+            client: Client::new(),
+            sessionless: sessionless.unwrap_or(Sessionless::new()),
+        }
+    }
+
+    /// Points the client at dolores in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory, sessionless: Option<Sessionless>) -> Self {
+        Self::new(Some(directory.url(Service::Dolores)), sessionless)
+    }
+
+    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.client.get(url).send().await
+    }
+
+    async fn put(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .put(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn delete(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .delete(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    fn get_timestamp() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis()
+            .to_string()
+    }
+
+    pub async fn create_user(&self) -> Result<DoloresUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let pub_key = self.sessionless.public_key().to_hex();
+        let signature = self.sessionless.sign(format!("{}{}", timestamp, pub_key)).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "pubKey": pub_key,
+            "signature": signature
+        });
+
+        let url = format!("{}user/create", self.base_url);
+        let res = self.put(&url, payload).await?;
+        let user: DoloresUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    pub async fn get_user_by_uuid(&self, uuid: &str) -> Result<DoloresUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let url = format!("{}user/{}?timestamp={}&signature={}", self.base_url, uuid, timestamp, signature);
+        let res = self.get(&url).await?;
+        let user: DoloresUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    /// Uploads the video at `file_uri`, streaming it from disk.
+    pub async fn put_video(&self, uuid: &str, title: &str, file_uri: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let path = Path::new(file_uri);
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| format!("{} has no file name", file_uri))?
+            .to_string();
+        let file = tokio::fs::File::open(path).await?;
+
+        self.put_video_from_reader(uuid, title, &file_name, file).await
+    }
+
+    /// Uploads a video read from `reader` without buffering it in memory. The
+    /// extension of `file_name` decides the content type dolores sees. Dolores
+    /// takes nothing but the video on this route, so `title` isn't sent.
+    pub async fn put_video_from_reader<R>(&self, uuid: &str, _title: &str, file_name: &str, reader: R) -> Result<SuccessResult, Box<dyn std::error::Error>>
+    where
+        R: AsyncRead + Send + Sync + 'static
+    {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let video = Part::stream(Body::wrap_stream(ReaderStream::new(reader)))
+            .file_name(file_name.to_string())
+            .mime_str(video_mime_type(file_name))?;
+        let form = Form::new().part("video", video);
+
+        let url = format!("{}user/{}/short-form/video", self.base_url, uuid);
+        let res = self.client
+            .put(&url)
+            .header("x-pn-timestamp", timestamp)
+            .header("x-pn-signature", signature)
+            .multipart(form)
+            .send()
+            .await?;
+        let success: SuccessResult = res.json().await?;
+
+        Ok(success)
+    }
+
+    pub async fn get_video(&self, uuid: &str, video_uuid: &str) -> Result<Video, Box<dyn std::error::Error>> {
+        self.get_video_range(uuid, video_uuid, ..).await
+    }
+
+    /// Downloads part of a video, e.g. `0..1024` for the first kilobyte or
+    /// `offset..` to resume an interrupted download.
+    pub async fn get_video_range(&self, uuid: &str, video_uuid: &str, range: impl RangeBounds<u64>) -> Result<Video, Box<dyn std::error::Error>> {
+        let url = format!("{}user/{}/short-form/video/{}", self.base_url, uuid, video_uuid);
+        let mut req = self.client.get(&url);
+        if let Some(range) = range_header(&range)? {
+            req = req.header(RANGE, range);
+        }
+
+        let res = req.send().await?;
+        if !res.status().is_success() {
+            return Err(format!("getting video {} failed with {}", video_uuid, res.status()).into());
+        }
+
+        let header = |name| res.headers().get(name).and_then(|value| value.to_str().ok()).map(|value| value.to_string());
+        let video_uuid = header("x-pn-video-uuid");
+        let content_type = header(CONTENT_TYPE.as_str());
+        let content_range = header(CONTENT_RANGE.as_str());
+
+        Ok(Video {
+            video_uuid,
+            content_type,
+            content_range,
+            bytes: res.bytes().await?.to_vec()
+        })
+    }
+
+    pub async fn add_video_tags(&self, uuid: &str, video_uuid: &str, tags: &[&str]) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}{}", timestamp, uuid, video_uuid, tags.concat());
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "uuid": uuid,
+            "videoUUID": video_uuid,
+            "tags": tags,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}/video/{}/tags", self.base_url, uuid, video_uuid);
+        let res = self.put(&url, payload).await?;
+        let success: SuccessResult = res.json().await?;
+
+        Ok(success)
+    }
+
+    /// Gets the videos tagged with any of `tags`, or the latest videos when
+    /// `tags` is empty.
+    pub async fn get_feed(&self, uuid: &str, tags: &[&str]) -> Result<Feed, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}", timestamp, uuid, tags.concat());
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let url = format!("{}user/{}/feed?timestamp={}&tags={}&signature={}", self.base_url, uuid, timestamp, tag_query(tags), signature);
+        let res = self.get(&url).await?;
+        let feed: Feed = res.json().await?;
+
+        Ok(feed)
+    }
+
+    pub async fn delete_user(&self, uuid: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}", self.base_url, uuid);
+        let res = self.delete(&url, payload).await?;
+        let success: SuccessResult = res.json().await?;
+
+        Ok(success)
+    }
+}
+
+fn video_mime_type(file_name: &str) -> &'static str {
+    match Path::new(file_name).extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_ascii_lowercase()).as_deref() {
+        Some("mp4") => "video/mp4",
+        Some("mov") => "video/quicktime",
+        Some("webm") => "video/webm",
+        _ => "application/octet-stream"
+    }
+}
+
+// Joins `tags` with `+` for the feed query, encoding each one so a tag can't
+// break into the rest of the URL.
+pub(crate) fn tag_query(tags: &[&str]) -> String {
+    tags.iter().map(|tag| urlencoding::encode(tag)).collect::<Vec<_>>().join("+")
+}
+
+// Turns a range of byte offsets into a `Range` header value, or `None` when
+// the whole video is wanted.
+pub(crate) fn range_header(range: &impl RangeBounds<u64>) -> Result<Option<String>, String> {
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => start.checked_add(1).ok_or_else(|| format!("empty byte range after {}", start))?,
+        Bound::Unbounded => 0
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => Some(*end),
+        Bound::Excluded(0) => return Err("empty byte range".to_string()),
+        Bound::Excluded(end) => Some(end - 1),
+        Bound::Unbounded => None
+    };
+
+    match end {
+        None if start == 0 => Ok(None),
+        None => Ok(Some(format!("bytes={}-", start))),
+        Some(end) if end < start => Err(format!("empty byte range {}..={}", start, end)),
+        Some(end) => Ok(Some(format!("bytes={}-{}", start, end)))
+    }
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct DoloresUser {
+    pub uuid: String,
+    pub pub_key: Option<String>,
+    #[serde(rename = "fountUUID")]
+    pub fount_uuid: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// What a feed entry points at. Videos uploaded to dolores have 36 character
+/// uuids; posts pulled in from atproto have longer ones.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct Post {
+    #[serde(default)]
+    pub uuid: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct FeedItem {
+    #[serde(default)]
+    pub uuid: String,
+    pub post: Option<Post>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+impl FeedItem {
+    /// The uuid to pass to `get_video`, wherever the server put it.
+    pub fn video_uuid(&self) -> &str {
+        match &self.post {
+            Some(post) if self.uuid.is_empty() => &post.uuid,
+            _ => &self.uuid
+        }
+    }
+}
+
+/// A user's feed. Dolores answers an untagged request with the latest
+/// `videoPosts` and a tagged one with the matching `videos`; both land in
+/// `videos`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct Feed {
+    #[serde(default, alias = "videoPosts")]
+    pub videos: Vec<FeedItem>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// The bytes of a video, or of the slice of it that was asked for.
+#[derive(Clone, Debug, Default)]
+pub struct Video {
+    pub video_uuid: Option<String>,
+    pub content_type: Option<String>,
+    pub content_range: Option<String>,
+    pub bytes: Vec<u8>
+}
+
+impl Video {
+    /// The full size of the video, when the server answered with a `Content-Range`.
+    pub fn total_length(&self) -> Option<u64> {
+        self.content_range
+            .as_ref()?
+            .rsplit('/')
+            .next()?
+            .parse()
+            .ok()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuccessResult {
+    pub success: bool
+}
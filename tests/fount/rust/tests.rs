@@ -1,19 +1,37 @@
-use crate::{FountUser, Fount, Gateway, Nineum, Spell, SpellResult, SuccessResult};
+use crate::{FountUser, Fount, Nineum, Spell, SpellResult, SuccessResult};
+use crate::structs::{Gateway, SpellFailed};
+use crate::magic::{VerifyError, ALLOWED_TIME_DIFFERENCE};
+use crate::spellbook::{SpellbookError, SpellbookRegistry};
+use crate::ledger::{EntryKind, Ledger, Reconciliation};
+use crate::structs::Transfer;
+use crate::consensus::{ConsensusError, SimulatedDevices, SpellProposal};
+use crate::nineum::{DecodedNineum, NineumError};
 use sessionless::hex::IntoHex;
 use sessionless::hex::FromHex;
 use sessionless::{Sessionless, PrivateKey};
 use std::collections::HashMap;
 use serde_json::json;
 use serde_json::Value;
+use crate::{Service, ServiceDirectory};
+use crate::messages;
+use allyabase_mock_rs::MockAllyabase;
+use allyabase_transport_rs::{Clock, Contract, HttpResponse, LoopbackTransport, Method, PooledTransport, RecordingTransport, ReplayTransport, RetryPolicy, Transport, TransportError};
+use std::sync::Arc;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::path_based("http://nginx:80")).expect("service directory")
+}
 
 #[actix_rt::test]
+#[ignore = "needs a running node; sharon runs it, or cargo test -- --ignored"]
 async fn test_fount() {
 
     let mut saved_user: Option<FountUser>;
     let mut saved_user2: Option<FountUser>; 
-    let fount = Fount::new(Some("http://nginx:80/fount/".to_string()), None);
-    let fount2 = Fount::new(Some("http://nginx:80/fount/".to_string()), None);
-    let fount3 = Fount::new(Some("http://nginx:80/fount/".to_string()), Some(Sessionless::from_private_key(PrivateKey::from_hex("a29435a4fb1a27a284a60b3409efeebbe6a64db606ff38aeead579ccf2262dc4").expect("private key"))));
+    let fount = Fount::with_directory(&directory(), None);
+    let fount2 = Fount::with_directory(&directory(), None);
+    let fount3 = Fount::with_directory(&directory(), Some(Sessionless::from_private_key(PrivateKey::from_hex("a29435a4fb1a27a284a60b3409efeebbe6a64db606ff38aeead579ccf2262dc4").expect("private key"))));
 
     async fn create_user(fount: &Fount) -> Option<FountUser> {
     println!("creating user");
@@ -292,3 +310,551 @@ async fn test_fount() {
     }
 
 }
+
+fn test_sessionless() -> Sessionless {
+    Sessionless::from_private_key(PrivateKey::from_hex("a29435a4fb1a27a284a60b3409efeebbe6a64db606ff38aeead579ccf2262dc4").expect("private key"))
+}
+
+fn test_spell(sessionless: &Sessionless) -> Spell {
+    let mut spell = Spell {
+        timestamp: Fount::get_timestamp(),
+        spell: "test".to_string(),
+        caster_uuid: "2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a".to_string(),
+        total_cost: 400,
+        mp: true,
+        ordinal: 1,
+        ..Spell::default()
+    };
+    spell.caster_signature = sessionless.sign(spell.message()).to_hex();
+    spell
+}
+
+#[test]
+fn test_spell_verify() {
+    let sessionless = test_sessionless();
+    let pub_key = sessionless.public_key().to_hex();
+    let spell = test_spell(&sessionless);
+
+    assert_eq!(spell.verify(&pub_key), Ok(()));
+    assert!(matches!(spell.verify(&Sessionless::new().public_key().to_hex()), Err(VerifyError::Mismatch { .. })));
+    assert!(matches!(spell.verify("nope"), Err(VerifyError::InvalidPublicKey(_))));
+
+    let now: u64 = spell.timestamp.parse().unwrap();
+    assert!(matches!(spell.verify_at(&pub_key, now + ALLOWED_TIME_DIFFERENCE + 1), Err(VerifyError::StaleTimestamp { .. })));
+
+    let mut mp_as_int = spell.clone();
+    mp_as_int.caster_signature = sessionless.sign(format!("{}{}{}{}{}{}", spell.timestamp, spell.spell, spell.caster_uuid, spell.total_cost, 1, spell.ordinal)).to_hex();
+    assert!(matches!(mp_as_int.verify(&pub_key), Err(VerifyError::Mismatch { .. })));
+    assert_eq!(
+        mp_as_int.diagnose(&pub_key),
+        Err(VerifyError::MpFormatting { expected: "true".to_string(), signed: "1".to_string() })
+    );
+
+    let mut reordered = spell.clone();
+    reordered.caster_signature = sessionless.sign(format!("{}{}{}{}{}{}", spell.timestamp, spell.caster_uuid, spell.spell, spell.total_cost, spell.mp, spell.ordinal)).to_hex();
+    assert!(matches!(reordered.verify(&pub_key), Err(VerifyError::Mismatch { .. })));
+    match reordered.diagnose(&pub_key) {
+        Err(VerifyError::FieldOrder { signed, .. }) => assert_eq!(signed, vec!["timestamp", "casterUUID", "spell", "totalCost", "mp", "ordinal"]),
+        other => panic!("expected field order error, got {:?}", other)
+    }
+}
+
+#[test]
+fn test_gateway_verify() {
+    let sessionless = test_sessionless();
+    let pub_key = sessionless.public_key().to_hex();
+    let mut gateway = Gateway {
+        timestamp: Fount::get_timestamp(),
+        uuid: "2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a".to_string(),
+        pub_key: pub_key.clone(),
+        minimum_cost: 20,
+        ordinal: 1,
+        ..Gateway::default()
+    };
+
+    assert_eq!(gateway.verify(&pub_key), Err(VerifyError::Unsigned));
+
+    gateway.signature = sessionless.sign(gateway.message()).to_hex();
+    assert_eq!(gateway.verify(&pub_key), Ok(()));
+
+    gateway.signature = sessionless.sign(format!("{}{}{}{}", gateway.uuid, gateway.timestamp, gateway.minimum_cost, gateway.ordinal)).to_hex();
+    assert!(matches!(gateway.verify(&pub_key), Err(VerifyError::Mismatch { .. })));
+    assert!(matches!(gateway.diagnose(&pub_key), Err(VerifyError::FieldOrder { .. })));
+}
+
+fn test_spellbook() -> Value {
+    let directory = directory();
+    json!({
+        "spellbookName": "allyabase",
+        "spellTest": {
+            "cost": 400,
+            "destinations": [
+                { "stopName": "test-server", "stopURL": "http://127.0.0.1:3456/magic/spell/" },
+                { "stopName": "fount", "stopURL": "http://127.0.0.1:5117/resolve/" }
+            ],
+            "resolver": "fount",
+            "mp": true
+        },
+        "createBDO": {
+            "cost": 50,
+            "destinations": [
+                { "stopName": "julia", "stopURL": format!("{}magic/spell/", directory.url(Service::Julia)) },
+                { "stopName": "fount", "stopURL": format!("{}resolve/", directory.url(Service::Fount)) },
+                { "stopName": "bdo", "stopURL": format!("{}magic/spell/", directory.url(Service::Bdo)) }
+            ],
+            "resolver": "fount",
+            "mp": true,
+            "requiredNineum": { "galaxy": "01", "system": "28880014", "flavor": "010101020301" }
+        }
+    })
+}
+
+#[test]
+fn test_spellbook_registry() {
+    let path = std::env::temp_dir().join(format!("fount-rs-spellbook-{}.json", Fount::get_timestamp()));
+    std::fs::write(&path, test_spellbook().to_string()).expect("write spellbook");
+    let registry = SpellbookRegistry::from_file(&path).expect("registry");
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(registry.total_cost("createBDO").unwrap(), 50);
+    let gateways: Vec<String> = registry.required_gateways("createBDO").unwrap().into_iter().map(|d| d.stop_name).collect();
+    assert_eq!(gateways, vec!["julia", "bdo"]);
+    assert!(matches!(registry.total_cost("nope"), Err(SpellbookError::UnknownSpell(_))));
+
+    let sessionless = test_sessionless();
+    let mut spell = Spell {
+        timestamp: Fount::get_timestamp(),
+        spell: "spellTest".to_string(),
+        caster_uuid: "2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a".to_string(),
+        ..Spell::default()
+    };
+    let route: Vec<String> = registry.prepare(&mut spell).expect("prepare").into_iter().map(|d| d.stop_name).collect();
+    spell.caster_signature = sessionless.sign(spell.message()).to_hex();
+
+    assert_eq!(spell.total_cost, 400);
+    assert_eq!(route, vec!["test-server"]);
+    assert!(spell.gateways.is_empty());
+    assert_eq!(spell.verify(&sessionless.public_key().to_hex()), Ok(()));
+}
+
+#[actix_rt::test]
+async fn test_cast_from_spellbook() {
+    let uuid = "2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a";
+    let loopback = Arc::new(LoopbackTransport::new(|_| HttpResponse::json_body(200, &json!({ "success": true, "echo": "hi" }))));
+    let fount = Fount::new(Some("http://fount.test/".to_string()), None).with_transport(loopback.clone());
+    assert!(fount.cast_from_spellbook(uuid, "spellTest", 0, json!({})).await.is_err());
+
+    let mut registry = SpellbookRegistry::new();
+    registry.add_json(&test_spellbook()).expect("spellbook");
+    let fount = fount.with_spellbooks(registry);
+    let result = fount.cast_from_spellbook(uuid, "spellTest", 0, json!({})).await.expect("cast");
+    assert_eq!(result.field::<String>("echo").expect("echo"), "hi");
+    assert!(fount.cast_from_spellbook(uuid, "nope", 0, json!({})).await.is_err());
+
+    let requests = loopback.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].body.as_ref().expect("body")["totalCost"], json!(400));
+}
+
+#[test]
+fn test_spell_result() {
+    let result = |value: Value| serde_json::from_value::<SpellResult>(value).expect("spell result");
+
+    let failed = result(json!({ "success": false, "error": "insufficient mp" })).succeeded().expect_err("failed");
+    assert_eq!(failed, SpellFailed("insufficient mp".to_string()));
+    assert_eq!(failed.to_string(), "spell failed: insufficient mp");
+    assert!(result(json!({ "success": false, "user": {} })).field::<Value>("user").is_err());
+    assert!(result(json!({ "success": true })).field::<Value>("user").is_err());
+
+    let fields: HashMap<String, u32> = result(json!({ "success": true, "mp": 900 })).fields().expect("fields");
+    assert_eq!(fields["mp"], 900);
+}
+
+#[actix_rt::test]
+async fn test_uuid_cache() {
+    let fount = Fount::new(Some("http://127.0.0.1:9/".to_string()), None);
+    let pub_key = test_sessionless().public_key().to_hex();
+
+    assert_eq!(fount.cached_uuid(&pub_key), None);
+    assert!(fount.uuid_for_public_key(&pub_key).await.is_err());
+
+    fount.cache_uuid(&pub_key, "2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a");
+    assert_eq!(fount.uuid_for_public_key(&pub_key).await.unwrap(), "2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a");
+
+    let uuids = fount.uuids_for_public_keys(std::slice::from_ref(&pub_key)).await.unwrap();
+    assert_eq!(uuids.get(&pub_key).map(String::as_str), Some("2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a"));
+}
+
+#[test]
+fn test_ledger() {
+    let path = std::env::temp_dir().join(format!("fount-rs-ledger-{}.jsonl", Fount::get_timestamp()));
+    let ledger = Ledger::open(&path).expect("ledger");
+    let transfer = Transfer {
+        timestamp: "1700000000000".to_string(),
+        sender_uuid: "sender".to_string(),
+        receiver_uuid: "receiver".to_string(),
+        message: "1700000000000senderreceiver200Testing, \"quoted\"".to_string()
+    };
+    let reconciliation = Reconciliation {
+        user: None,
+        nineum: Some(Vec::new()),
+        discrepancies: vec!["grant reported 586 MP but fount now has 600".to_string()]
+    };
+    ledger.record(EntryKind::Grant, transfer.clone(), "abc", json!({"amount": 200}), json!({"mp": 586}), Some(reconciliation)).expect("record");
+    ledger.record(EntryKind::Transfer, transfer, "def", json!({}), json!({}), None).expect("record");
+
+    let reopened = Ledger::open(&path).expect("reopen");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(reopened.len(), 2);
+    assert_eq!(reopened.entries()[1].sequence, 1);
+    assert_eq!(reopened.entries()[0].kind, EntryKind::Grant);
+    assert!(!reopened.entries()[0].reconciliation.as_ref().unwrap().reconciled());
+
+    let csv = reopened.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("sequence,kind,timestamp,senderUUID,receiverUUID"));
+    assert!(lines[1].starts_with("0,grant,1700000000000,sender,receiver,\"1700000000000senderreceiver200Testing, \"\"quoted\"\"\",abc,false,"));
+
+    let exported: Value = serde_json::from_str(&reopened.to_json().unwrap()).unwrap();
+    assert_eq!(exported[0]["senderUUID"], "sender");
+    assert_eq!(exported[1]["signature"], "def");
+}
+
+#[test]
+fn test_two_of_three_consensus() {
+    let caster = test_sessionless();
+    let phones = SimulatedDevices::new(3);
+    let policy = phones.policy(2).expect("policy");
+    assert!(matches!(phones.policy(4), Err(ConsensusError::InvalidThreshold { .. })));
+
+    let mut proposal = SpellProposal::new(test_spell(&caster), policy);
+    assert!(matches!(proposal.finalize(&caster, &Clock::new()), Err(ConsensusError::BelowThreshold { approvals: 0, threshold: 2 })));
+
+    assert_eq!(phones.approve(&mut proposal, &[0]), Ok(1));
+    assert!(matches!(phones.approve(&mut proposal, &[0]), Err(ConsensusError::AlreadyApproved(_))));
+    assert!(matches!(proposal.approve(&Sessionless::new()), Err(ConsensusError::UnknownDevice(_))));
+    assert_eq!(phones.approve(&mut proposal, &[3]), Err(ConsensusError::NoSuchDevice(3)));
+
+    let forged = Sessionless::new().sign(proposal.message()).to_hex();
+    let device = proposal.pending()[0].clone();
+    assert!(matches!(proposal.add_signature(&device, &forged), Err(ConsensusError::BadSignature { .. })));
+
+    assert_eq!(phones.approve(&mut proposal, &[2]), Ok(2));
+    assert!(proposal.is_ready());
+
+    let spell = proposal.finalize(&caster, &Clock::new()).expect("finalize");
+    assert_eq!(spell.verify(&caster.public_key().to_hex()), Ok(()));
+    assert_eq!(spell.extra["coSignatures"].as_object().unwrap().len(), 2);
+    assert_eq!(spell.extra["proposedAt"], json!(proposal.spell.timestamp));
+
+    // Approvals collected over longer than fount's window still finalize to a fresh spell.
+    let mut stale = proposal.clone();
+    stale.spell.timestamp = (proposal.spell.timestamp.parse::<u64>().unwrap() - ALLOWED_TIME_DIFFERENCE - 60000).to_string();
+    stale.co_signatures.clear();
+    phones.approve(&mut stale, &[0, 1]).expect("approve");
+    assert_eq!(stale.finalize(&caster, &Clock::new()).expect("finalize stale").verify(&caster.public_key().to_hex()), Ok(()));
+
+    // A proposal passed between devices is checked again when it comes back.
+    let mut tampered: Value = serde_json::to_value(&proposal).unwrap();
+    tampered["coSignatures"][phones.devices[1].public_key().to_hex()] = json!(forged);
+    let tampered: SpellProposal = serde_json::from_value(tampered).unwrap();
+    assert!(!tampered.is_ready());
+    assert!(matches!(tampered.finalize(&caster, &Clock::new()), Err(ConsensusError::BadSignature { .. })));
+
+    let mut replayed = proposal.clone();
+    replayed.co_signatures.insert(Sessionless::new().public_key().to_hex(), forged);
+    assert!(matches!(replayed.finalize(&caster, &Clock::new()), Err(ConsensusError::UnknownDevice(_))));
+
+    let mut loose: Value = serde_json::to_value(&proposal).unwrap();
+    loose["policy"]["threshold"] = json!(0);
+    assert!(serde_json::from_value::<SpellProposal>(loose.clone()).is_err());
+    loose["policy"]["threshold"] = json!(2);
+    loose["policy"]["devices"] = json!([device, device]);
+    assert!(serde_json::from_value::<SpellProposal>(loose).is_err());
+}
+
+#[test]
+fn test_decode_nineum() {
+    let ticket = DecodedNineum::parse("01288800140103020405060200000042").expect("nineum");
+    assert_eq!(ticket.universe, "01");
+    assert_eq!(ticket.galaxy, "28880014");
+    assert_eq!(ticket.flavor(), "010302040506");
+    assert_eq!(ticket.year, "02");
+    assert_eq!(ticket.ordinal, "00000042");
+    assert_eq!(ticket.charge_name(), Some("positive"));
+    assert_eq!(ticket.direction_name(), Some("east"));
+    assert_eq!(ticket.rarity_name(), Some("uncommon"));
+    assert_eq!(ticket.shape_name(), Some("dodecahedron"));
+    assert!(!ticket.is_galactic());
+
+    let galactic = DecodedNineum::parse("0101234567010bff0101010100000001").expect("galactic");
+    assert!(galactic.is_galactic());
+    assert_eq!(galactic.direction_name(), None);
+
+    assert_eq!(DecodedNineum::parse("0128880014"), Err(NineumError::TooShort("0128880014".to_string())));
+    let held = Nineum { nineum: vec![ticket.id.clone(), "this is not a nineum id at all".to_string()] };
+    assert!(matches!(held.decode(), Err(NineumError::NotHex(_))));
+}
+
+// Runs against an in-process mock node, so it needs no Docker environment.
+#[actix_rt::test]
+async fn test_fount_offline() {
+    let node = MockAllyabase::start().expect("mock node");
+    let fount = Fount::with_directory(&node.directory(), None);
+    let fount2 = Fount::with_directory(&node.directory(), None);
+
+    let user = fount.create_user().await.expect("create_user");
+    let user2 = fount2.create_user().await.expect("create_user2");
+    assert_eq!(user.uuid.len(), 36);
+    assert_eq!(user.mp, 1000);
+    assert_eq!(fount.get_user_by_uuid(&user.uuid).await.expect("get_user_by_uuid").uuid, user.uuid);
+    assert_eq!(fount.get_user_by_public_key().await.expect("get_user_by_public_key").uuid, user.uuid);
+
+    let granted = fount.grant(&user.uuid, &user2.uuid, &150, "mock grant").await.expect("grant");
+    assert_eq!(granted.mp, 990);
+    assert_eq!(fount2.get_user_by_uuid(&user2.uuid).await.expect("get_user_by_uuid").experience_pool, 150);
+
+    let minted = node.mint_nineum(&user.uuid, "010203040506", 2).expect("mint");
+    let transferred = fount.transfer_nineum(&user.uuid, &user2.uuid, &vec![minted[0].clone()], &0, "USD").await.expect("transfer_nineum");
+    assert_eq!(transferred.nineum_count, 1);
+    assert_eq!(fount2.get_nineum(&user2.uuid).await.expect("get_nineum").nineum, vec![minted[0].clone()]);
+    assert_eq!(DecodedNineum::parse(&minted[1]).expect("decode").rarity, "03");
+
+    node.on_spell("mockSpell", |spell| json!({ "echo": spell["components"]["message"] }));
+    let result = fount.cast(&user.uuid, "mockSpell", 50, user.ordinal, json!({ "message": "hello" })).await.expect("cast");
+    assert!(result.success);
+    assert_eq!(result.extra.get("echo"), Some(&json!("hello")));
+    assert!(!fount2.cast(&user.uuid, "mockSpell", 50, user.ordinal, json!({})).await.expect("forged cast").success);
+    assert!(!fount.cast(&user.uuid, "mockSpell", 5000, user.ordinal, json!({})).await.expect("expensive cast").success);
+
+    // Fount refuses signatures from the wrong key and requests from too far in the past.
+    assert!(fount2.get_user_by_uuid(&user.uuid).await.is_err());
+    assert!(fount2.uuid_for_public_key(&user.pub_key).await.is_err());
+    fount2.cache_uuid(&user.pub_key, &user.uuid);
+    assert_eq!(fount2.uuid_for_public_key(&user.pub_key).await.expect("cached uuid"), user.uuid);
+    node.set_clock_offset(ALLOWED_TIME_DIFFERENCE as i64 + 1000);
+    let error = fount.get_user_by_uuid(&user.uuid).await.expect_err("stale timestamp");
+    match error.downcast_ref::<TransportError>() {
+        Some(TransportError::ClockSkew { drift: Some(drift) }) => assert!((drift - ALLOWED_TIME_DIFFERENCE as i64 - 1000).abs() < 2000, "{}", drift),
+        _ => panic!("expected clock skew, got {}", error)
+    }
+
+    // The client signs with fount's time from then on; another can ask up front.
+    fount.get_user_by_uuid(&user.uuid).await.expect("get_user_by_uuid after skew");
+    assert!(fount2.sync_clock().await.expect("sync_clock").is_some());
+    assert!((fount2.clock().offset() - ALLOWED_TIME_DIFFERENCE as i64 - 1000).abs() < 2000);
+    fount2.get_user_by_uuid(&user2.uuid).await.expect("get_user_by_uuid after sync");
+}
+
+#[actix_rt::test]
+async fn test_fount_ledger_offline() {
+    let node = MockAllyabase::start().expect("mock node");
+    let fount = Fount::with_directory(&node.directory(), None).with_ledger(Ledger::new());
+    let user = fount.create_user().await.expect("create_user");
+
+    fount.grant(&user.uuid, &user.uuid, &150, "self grant").await.expect("grant");
+    fount.grant(&user.uuid, &user.uuid, &1_000_000, "too much").await.expect("unaffordable grant");
+    let user2 = Fount::with_directory(&node.directory(), None).create_user().await.expect("create_user2");
+    let minted = node.mint_nineum(&user.uuid, "010203040506", 2).expect("mint");
+    fount.transfer_nineum(&user.uuid, &user2.uuid, &vec![minted[0].clone()], &0, "USD").await.expect("transfer_nineum");
+
+    let entries = fount.ledger().expect("ledger").entries();
+    let discrepancies: Vec<Vec<String>> = entries.iter().map(|entry| entry.reconciliation.clone().expect("reconciliation").discrepancies).collect();
+    assert_eq!(discrepancies, vec![
+        vec![],
+        vec!["grant of 1000000 experience needs 66667 MP but only 990 was available".to_string()],
+        vec![]
+    ]);
+    assert_eq!(entries[0].reconciliation.as_ref().unwrap().user.as_ref().unwrap().experience_pool, 150);
+    assert_eq!(entries[2].reconciliation.as_ref().unwrap().nineum, Some(vec![minted[1].clone()]));
+
+    // A ledger that can't be written doesn't turn a grant that went through into an error.
+    let unwritable = Ledger::open(std::env::temp_dir().join("fount-rs-missing-dir").join("ledger.jsonl")).expect("ledger");
+    let fount = Fount::with_directory(&node.directory(), None).with_ledger(unwritable);
+    let user = fount.create_user().await.expect("create_user");
+    assert_eq!(fount.grant(&user.uuid, &user.uuid, &15, "unrecorded").await.expect("grant").mp, 999);
+    assert!(fount.ledger().expect("ledger").is_empty());
+}
+
+// The calls the fount contract covers, in the order the fixture records them.
+async fn fount_flow(fount: &Fount, fount2: &Fount) {
+    let user = fount.create_user().await.expect("create_user");
+    let user2 = fount2.create_user().await.expect("create_user2");
+    fount.get_user_by_uuid(&user.uuid).await.expect("get_user_by_uuid");
+    fount.get_user_by_public_key().await.expect("get_user_by_public_key");
+    fount.grant(&user.uuid, &user2.uuid, &150, "contract grant").await.expect("grant");
+    fount2.get_nineum(&user2.uuid).await.expect("get_nineum");
+}
+
+// SHARON_FIXTURES=record saves what the node answers to rust/fixtures/fount-rs.json,
+// verify diffs a live node against it and replay (the default) plays it back.
+// Ignored until a fixture is recorded from a live node; replaying without one fails.
+#[actix_rt::test]
+#[ignore = "no fixture recorded yet; run SHARON_FIXTURES=record cargo test contract -- --ignored against a live node"]
+async fn test_fount_contract() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../rust/fixtures/fount-rs.json");
+    let contract = Contract::from_env(&directory().url(Service::Fount), path).expect("contract");
+    let fount = Fount::with_directory(&directory(), None).with_transport(contract.transport());
+    let fount2 = Fount::with_directory(&directory(), None).with_transport(contract.transport());
+
+    fount_flow(&fount, &fount2).await;
+    assert_eq!(contract.finish().expect("fixture"), vec![]);
+}
+
+#[actix_rt::test]
+async fn test_fount_record_and_replay() {
+    let node = MockAllyabase::start().expect("mock node");
+    let url = node.directory().url(Service::Fount);
+    let recording = Arc::new(RecordingTransport::new(&url, Arc::new(PooledTransport::default())));
+    fount_flow(
+        &Fount::with_directory(&node.directory(), None).with_transport(recording.clone()),
+        &Fount::with_directory(&node.directory(), None).with_transport(recording.clone())
+    ).await;
+    drop(node);
+
+    let replay = Arc::new(ReplayTransport::new(&url, recording.fixture()));
+    fount_flow(
+        &Fount::new(Some(url.clone()), None).with_transport(replay.clone()),
+        &Fount::new(Some(url.clone()), None).with_transport(replay.clone())
+    ).await;
+    assert_eq!(replay.diffs(), vec![]);
+    assert!(replay.remaining().is_empty());
+}
+
+// Clients share the runtime's connection pool unless handed another transport.
+#[actix_rt::test]
+async fn test_fount_loopback() {
+    assert!(Arc::ptr_eq(&Fount::with_directory(&directory(), None).transport, &Fount::with_directory(&directory(), None).transport));
+    let own: Arc<dyn Transport> = Arc::new(PooledTransport::default());
+    assert!(Arc::ptr_eq(&Fount::with_directory(&directory(), None).with_transport(own.clone()).transport, &own));
+
+    let loopback = Arc::new(LoopbackTransport::new(|request| match request.url.contains("/user/create") {
+        true => HttpResponse::json_body(200, &json!({
+            "pubKey": "", "mp": 1000, "maxMP": 1000, "lastMPUsed": 0, "experience": 0,
+            "lastExperienceCalculated": 0, "experiencePool": 0, "nineumCount": 0, "ordinal": 0,
+            "uuid": "5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f"
+        })),
+        false => HttpResponse::json_body(403, &json!({ "error": "auth error" }))
+    }));
+    let fount = Fount::new(Some("http://fount.test/".to_string()), None).with_transport(loopback.clone());
+
+    let user = fount.create_user().await.expect("create_user");
+    assert_eq!(user.mp, 1000);
+    assert!(fount.get_user_by_uuid(&user.uuid).await.is_err());
+
+    let requests = loopback.requests();
+    assert_eq!((requests[0].method, requests[0].url.as_str()), (Method::Put, "http://fount.test/user/create"));
+    assert_eq!(requests[0].body.as_ref().expect("body")["pubKey"], json!(fount.sessionless.public_key().to_hex()));
+    assert!(requests[1].url.starts_with("http://fount.test/user/5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f?timestamp="));
+}
+
+// A fount that answers 503 to the first `failures` requests, then a user.
+fn flaky_fount(failures: u32) -> Arc<LoopbackTransport> {
+    let count = std::sync::atomic::AtomicU32::new(0);
+    Arc::new(LoopbackTransport::new(move |_| match count.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < failures {
+        true => HttpResponse::json_body(503, &json!({ "error": "unavailable" })),
+        false => HttpResponse::json_body(200, &json!({
+            "pubKey": "", "mp": 990, "maxMP": 1000, "lastMPUsed": 0, "experience": 0,
+            "lastExperienceCalculated": 0, "experiencePool": 0, "nineumCount": 0, "ordinal": 0,
+            "uuid": "5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f"
+        }))
+    }))
+}
+
+#[actix_rt::test]
+async fn test_fount_retry() {
+    let uuid = "5d1d4a0b-6b44-4a2e-9f0c-1a2b3c4d5e6f";
+    let retry = RetryPolicy { base_delay: std::time::Duration::from_millis(2), ..Default::default() };
+
+    // Each attempt is signed with a fresh timestamp.
+    let loopback = flaky_fount(2);
+    let fount = Fount::new(Some("http://fount.test/".to_string()), None).with_transport(loopback.clone()).with_retry(retry.clone());
+    fount.get_user_by_uuid(uuid).await.expect("get_user_by_uuid");
+    let urls: Vec<String> = loopback.requests().into_iter().map(|request| request.url).collect();
+    assert_eq!(urls.len(), 3);
+    assert!(urls[0] != urls[1] && urls[1] != urls[2]);
+
+    // Grants are only retried when the policy opts in.
+    let loopback = flaky_fount(1);
+    let fount = Fount::new(Some("http://fount.test/".to_string()), None).with_transport(loopback.clone()).with_retry(retry.clone());
+    let error = fount.grant(uuid, uuid, &10, "retry").await.expect_err("grant");
+    assert!(error.to_string().contains("not safe to retry"), "{}", error);
+    assert_eq!(loopback.requests().len(), 1);
+
+    let loopback = flaky_fount(1);
+    let fount = Fount::new(Some("http://fount.test/".to_string()), None).with_transport(loopback.clone()).with_retry(retry.clone().with_non_idempotent());
+    assert_eq!(fount.grant(uuid, uuid, &10, "retry").await.expect("grant").mp, 990);
+    let bodies: Vec<Value> = loopback.requests().into_iter().map(|request| request.body.expect("body")).collect();
+    assert_eq!(bodies.len(), 2);
+    assert!(bodies[0]["timestamp"] != bodies[1]["timestamp"]);
+    let message = messages::grant(bodies[1]["timestamp"].as_str().expect("timestamp"), uuid, uuid, 10, "retry");
+    assert_eq!(bodies[1]["signature"], json!(fount.sessionless.sign(&message).to_hex()));
+
+    // So is each attempt at a cast spell.
+    let count = std::sync::atomic::AtomicU32::new(0);
+    let loopback = Arc::new(LoopbackTransport::new(move |_| match count.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+        0 => HttpResponse::json_body(503, &json!({ "error": "unavailable" })),
+        _ => HttpResponse::json_body(200, &json!({ "success": true }))
+    }));
+    let fount = Fount::new(Some("http://fount.test/".to_string()), None).with_transport(loopback.clone()).with_retry(retry.with_non_idempotent());
+    assert!(fount.cast(uuid, "test", 400, 0, json!({})).await.expect("cast").success);
+    let spells: Vec<Spell> = loopback.requests().into_iter().map(|request| serde_json::from_value(request.body.expect("body")).expect("spell")).collect();
+    assert_eq!(spells.len(), 2);
+    assert!(spells[0].timestamp != spells[1].timestamp);
+    assert_eq!(spells[1].caster_signature, fount.sessionless.sign(spells[1].message()).to_hex());
+}
+
+// config/signature-vectors.json. Only the Rust clients are checked against it so far.
+fn signature_vectors() -> Value {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../../../config/signature-vectors.json");
+    serde_json::from_str(&std::fs::read_to_string(path).expect("signature vectors")).expect("signature vectors json")
+}
+
+#[test]
+fn test_signature_vectors() {
+    let corpus = signature_vectors();
+    let vectors: Vec<&Value> = corpus["vectors"].as_array().expect("vectors").iter().filter(|vector| vector["service"] == "fount").collect();
+    assert!(!vectors.is_empty());
+
+    for vector in vectors {
+        let name = vector["name"].as_str().expect("name");
+        let inputs = &vector["inputs"];
+        let text = |key: &str| inputs[key].as_str().unwrap_or_else(|| panic!("{} needs {}", name, key)).to_string();
+        let number = |key: &str| inputs[key].as_u64().unwrap_or_else(|| panic!("{} needs {}", name, key)) as u32;
+
+        let message = match name {
+            "fount.createUser" => messages::create_user(&text("timestamp"), &text("pubKey")),
+            "fount.getUser" | "fount.getNineum" | "fount.deleteUser" => messages::user(&text("timestamp"), &text("uuid")),
+            "fount.getUserByPublicKey" => messages::user_by_public_key(&text("timestamp"), &text("pubKey")),
+            "fount.grant" => messages::grant(&text("timestamp"), &text("uuid"), &text("destinationUUID"), number("amount"), &text("description")),
+            "fount.transfer" => {
+                let ids: Vec<String> = serde_json::from_value(inputs["nineumUniqueIds"].clone()).expect("nineumUniqueIds");
+                messages::transfer(&text("timestamp"), &text("uuid"), &text("destinationUUID"), &ids, number("price"), &text("currency"))
+            },
+            "fount.resolve" => Spell {
+                timestamp: text("timestamp"),
+                spell: text("spell"),
+                caster_uuid: text("casterUUID"),
+                total_cost: number("totalCost"),
+                mp: inputs["mp"].as_bool().expect("mp"),
+                ordinal: number("ordinal"),
+                ..Default::default()
+            }.message(),
+            "fount.resolve.gateway" => Gateway {
+                timestamp: text("timestamp"),
+                uuid: text("uuid"),
+                minimum_cost: number("minimumCost"),
+                ordinal: number("ordinal") as u64,
+                ..Default::default()
+            }.message(),
+            name => panic!("fount-rs has no message for {}", name)
+        };
+        assert_eq!(message, vector["message"].as_str().expect("message"), "{}", name);
+
+        let key = &corpus["keys"][vector["signer"].as_str().expect("signer")];
+        let signer = Sessionless::from_private_key(PrivateKey::from_hex(key["privateKey"].as_str().expect("privateKey")).expect("private key"));
+        assert_eq!(signer.public_key().to_hex(), key["publicKey"].as_str().expect("publicKey"));
+        assert_eq!(signer.sign(&message).to_hex(), vector["signature"].as_str().expect("signature"), "{}", name);
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use allyabase_transport_rs::Clock;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use crate::magic::{parse_public_key, parse_signature, signs, VerifyError};
+use crate::structs::{Spell, SpellResult};
+use crate::Fount;
+
+/// The device keys allowed to approve spells for one fount user, and how many
+/// of them have to agree. A policy read back from JSON is held to the same
+/// rules as one built with `DevicePolicy::new`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase", try_from = "UncheckedPolicy")]
+pub struct DevicePolicy {
+    devices: Vec<String>,
+    threshold: usize
+}
+
+#[derive(Deserialize)]
+struct UncheckedPolicy {
+    devices: Vec<String>,
+    threshold: usize
+}
+
+impl TryFrom<UncheckedPolicy> for DevicePolicy {
+    type Error = ConsensusError;
+
+    fn try_from(policy: UncheckedPolicy) -> Result<Self, Self::Error> {
+        DevicePolicy::new(policy.devices, policy.threshold)
+    }
+}
+
+impl DevicePolicy {
+    pub fn new(devices: Vec<String>, threshold: usize) -> Result<Self, ConsensusError> {
+        for (i, device) in devices.iter().enumerate() {
+            if devices[..i].contains(device) {
+                return Err(ConsensusError::DuplicateDevice(device.clone()));
+            }
+        }
+        if threshold == 0 || threshold > devices.len() {
+            return Err(ConsensusError::InvalidThreshold { threshold, devices: devices.len() });
+        }
+
+        Ok(DevicePolicy { devices, threshold })
+    }
+
+    pub fn devices(&self) -> &[String] {
+        &self.devices
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConsensusError {
+    InvalidThreshold { threshold: usize, devices: usize },
+    DuplicateDevice(String),
+    UnknownDevice(String),
+    AlreadyApproved(String),
+    BadSignature { device: String, reason: VerifyError },
+    BelowThreshold { approvals: usize, threshold: usize },
+    NoSuchDevice(usize)
+}
+
+impl fmt::Display for ConsensusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsensusError::InvalidThreshold { threshold, devices } => write!(f, "threshold {} is not possible with {} devices", threshold, devices),
+            ConsensusError::DuplicateDevice(device) => write!(f, "{} is listed more than once", device),
+            ConsensusError::UnknownDevice(device) => write!(f, "{} is not one of the configured devices", device),
+            ConsensusError::AlreadyApproved(device) => write!(f, "{} has already approved this spell", device),
+            ConsensusError::BadSignature { device, reason } => write!(f, "approval from {} does not verify: {}", device, reason),
+            ConsensusError::BelowThreshold { approvals, threshold } => write!(f, "{} of {} required approvals collected", approvals, threshold),
+            ConsensusError::NoSuchDevice(index) => write!(f, "there is no simulated device {}", index)
+        }
+    }
+}
+
+impl std::error::Error for ConsensusError {}
+
+/// A spell waiting on approvals from a user's devices. Each device co-signs the
+/// spell's message as proposed, so a proposal can be serialized, passed to
+/// each device in turn and finalized wherever the caster key lives.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct SpellProposal {
+    pub spell: Spell,
+    pub policy: DevicePolicy,
+    pub co_signatures: BTreeMap<String, String>
+}
+
+impl SpellProposal {
+    /// Starts collecting approvals for `spell`. Fill in everything that is
+    /// part of the caster's message before proposing, since approvals sign it.
+    pub fn new(spell: Spell, policy: DevicePolicy) -> Self {
+        SpellProposal {
+            spell,
+            policy,
+            co_signatures: BTreeMap::new()
+        }
+    }
+
+    pub fn message(&self) -> String {
+        self.spell.message()
+    }
+
+    pub fn approvals(&self) -> usize {
+        self.co_signatures.len()
+    }
+
+    /// Whether every co-signature checks out and there are enough of them.
+    pub fn is_ready(&self) -> bool {
+        self.verified().is_ok_and(|approvals| approvals >= self.policy.threshold)
+    }
+
+    /// The configured devices that haven't approved yet.
+    pub fn pending(&self) -> Vec<&String> {
+        self.policy.devices
+            .iter()
+            .filter(|device| !self.co_signatures.contains_key(*device))
+            .collect()
+    }
+
+    /// Checks that `signature` is `device`'s signature of this proposal and
+    /// that `device` is one of the policy's.
+    fn check(&self, device: &str, signature: &str) -> Result<(), ConsensusError> {
+        if !self.policy.devices.iter().any(|known| known == device) {
+            return Err(ConsensusError::UnknownDevice(device.to_string()));
+        }
+
+        let bad_signature = |reason| ConsensusError::BadSignature { device: device.to_string(), reason };
+        let pub_key = parse_public_key(device).map_err(bad_signature)?;
+        let parsed = parse_signature(signature).map_err(bad_signature)?;
+        if !signs(&self.message(), &pub_key, &parsed) {
+            return Err(bad_signature(VerifyError::Mismatch { message: self.message() }));
+        }
+
+        Ok(())
+    }
+
+    /// Re-checks every co-signature, since a proposal that has been passed
+    /// between devices may have been tampered with on the way, returning how
+    /// many distinct devices approved.
+    pub fn verified(&self) -> Result<usize, ConsensusError> {
+        for (device, signature) in self.co_signatures.iter() {
+            self.check(device, signature)?;
+        }
+
+        Ok(self.approvals())
+    }
+
+    /// Adds a co-signature received from `device`, returning the approval count.
+    pub fn add_signature(&mut self, device: &str, signature: &str) -> Result<usize, ConsensusError> {
+        if self.co_signatures.contains_key(device) {
+            return Err(ConsensusError::AlreadyApproved(device.to_string()));
+        }
+        self.check(device, signature)?;
+
+        self.co_signatures.insert(device.to_string(), signature.to_string());
+
+        Ok(self.approvals())
+    }
+
+    /// Approves the proposal with a device's own key.
+    pub fn approve(&mut self, device: &Sessionless) -> Result<usize, ConsensusError> {
+        let signature = device.sign(self.message()).to_hex();
+        self.add_signature(&device.public_key().to_hex(), &signature)
+    }
+
+    /// Signs the spell as the caster once the threshold is met, stamped by
+    /// `clock`. Pass the client's, `Fount::clock`, so the spell is corrected
+    /// for this machine's drift from fount.
+    pub fn finalize(&self, caster: &Sessionless, clock: &Clock) -> Result<Spell, ConsensusError> {
+        self.finalize_at(caster, &clock.timestamp())
+    }
+
+    /// Like `finalize`, but stamps the spell with `timestamp`. Collecting
+    /// approvals can take longer than fount's time window, so the caster
+    /// signs the spell afresh rather than sending the proposal's timestamp.
+    /// The co-signatures travel with the spell as `coSignatures`, along with
+    /// the `proposedAt` timestamp they signed, so downstream stops can check
+    /// them; fount itself only checks the caster signature.
+    pub fn finalize_at(&self, caster: &Sessionless, timestamp: &str) -> Result<Spell, ConsensusError> {
+        let approvals = self.verified()?;
+        if approvals < self.policy.threshold {
+            return Err(ConsensusError::BelowThreshold { approvals, threshold: self.policy.threshold });
+        }
+
+        let mut spell = self.spell.clone();
+        spell.timestamp = timestamp.to_string();
+        spell.caster_signature = caster.sign(spell.message()).to_hex();
+        spell.extra.insert("coSignatures".to_string(), serde_json::to_value(&self.co_signatures).unwrap_or(Value::Null));
+        spell.extra.insert("proposedAt".to_string(), Value::String(self.spell.timestamp.clone()));
+
+        Ok(spell)
+    }
+}
+
+impl Fount {
+    /// Finalizes `proposal` with this client's key and resolves it, signing
+    /// each attempt with a fresh timestamp.
+    pub async fn resolve_with_consensus(&self, proposal: &SpellProposal) -> Result<SpellResult, Box<dyn std::error::Error>> {
+        proposal.finalize_at(&self.sessionless, &self.clock.timestamp())?;
+        self.resolve_stamped(|timestamp| {
+            proposal.finalize_at(&self.sessionless, timestamp).expect("finalized above")
+        }).await
+    }
+}
+
+/// A set of in-process device keys for exercising approval flows without real
+/// devices.
+pub struct SimulatedDevices {
+    pub devices: Vec<Sessionless>
+}
+
+impl SimulatedDevices {
+    pub fn new(count: usize) -> Self {
+        SimulatedDevices {
+            devices: (0..count).map(|_| Sessionless::new()).collect()
+        }
+    }
+
+    pub fn policy(&self, threshold: usize) -> Result<DevicePolicy, ConsensusError> {
+        DevicePolicy::new(self.devices.iter().map(|device| device.public_key().to_hex()).collect(), threshold)
+    }
+
+    /// Has the devices at `indices` approve `proposal`, stopping at the first refusal.
+    pub fn approve(&self, proposal: &mut SpellProposal, indices: &[usize]) -> Result<usize, ConsensusError> {
+        let mut approvals = proposal.approvals();
+        for index in indices.iter() {
+            let device = self.devices.get(*index).ok_or(ConsensusError::NoSuchDevice(*index))?;
+            approvals = proposal.approve(device)?;
+        }
+
+        Ok(approvals)
+    }
+}
@@ -0,0 +1,176 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::FountUser;
+use crate::structs::Transfer;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub enum EntryKind {
+    Grant,
+    Transfer,
+    Spell
+}
+
+/// What the caster's account looked like right after a call, and anything that
+/// didn't line up with what the call should have done.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct Reconciliation {
+    pub user: Option<FountUser>,
+    pub nineum: Option<Vec<String>>,
+    pub discrepancies: Vec<String>
+}
+
+impl Reconciliation {
+    pub fn reconciled(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct LedgerEntry {
+    pub sequence: u64,
+    pub kind: EntryKind,
+    #[serde(flatten)]
+    pub transfer: Transfer,
+    pub signature: String,
+    pub request: Value,
+    pub response: Value,
+    pub reconciliation: Option<Reconciliation>
+}
+
+/// An append-only record of the signed calls a `Fount` client has made. Entries
+/// are kept in memory and, for a ledger opened with `Ledger::open`, appended to
+/// a JSON lines file as they are recorded.
+#[derive(Debug, Default)]
+pub struct Ledger {
+    entries: Mutex<Vec<LedgerEntry>>,
+    path: Option<PathBuf>
+}
+
+const CSV_HEADER: [&str; 11] = [
+    "sequence", "kind", "timestamp", "senderUUID", "receiverUUID", "message",
+    "signature", "reconciled", "discrepancies", "request", "response"
+];
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Ledger::default()
+    }
+
+    /// Opens (or creates) a ledger file, loading any entries already in it.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref().to_path_buf();
+        let mut entries = Vec::<LedgerEntry>::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    entries.push(serde_json::from_str(&line)?);
+                }
+            }
+        }
+
+        Ok(Ledger {
+            entries: Mutex::new(entries),
+            path: Some(path)
+        })
+    }
+
+    pub fn record(
+        &self,
+        kind: EntryKind,
+        transfer: Transfer,
+        signature: &str,
+        request: Value,
+        response: Value,
+        reconciliation: Option<Reconciliation>
+    ) -> Result<LedgerEntry, Box<dyn std::error::Error>> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = LedgerEntry {
+            sequence: entries.len() as u64,
+            kind,
+            transfer,
+            signature: signature.to_string(),
+            request,
+            response,
+            reconciliation
+        };
+
+        if let Some(ref path) = self.path {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        }
+        entries.push(entry.clone());
+
+        Ok(entry)
+    }
+
+    pub fn entries(&self) -> Vec<LedgerEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&*self.entries.lock().unwrap())
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = CSV_HEADER.join(",");
+        csv.push('\n');
+        for entry in self.entries.lock().unwrap().iter() {
+            let (reconciled, discrepancies) = match entry.reconciliation {
+                Some(ref reconciliation) => (reconciliation.reconciled().to_string(), reconciliation.discrepancies.join("; ")),
+                None => (String::new(), String::new())
+            };
+            let kind = serde_json::to_value(&entry.kind).ok().and_then(|kind| kind.as_str().map(String::from)).unwrap_or_default();
+            let row = [
+                entry.sequence.to_string(),
+                kind,
+                entry.transfer.timestamp.clone(),
+                entry.transfer.sender_uuid.clone(),
+                entry.transfer.receiver_uuid.clone(),
+                entry.transfer.message.clone(),
+                entry.signature.clone(),
+                reconciled,
+                discrepancies,
+                entry.request.to_string(),
+                entry.response.to_string()
+            ];
+            csv.push_str(&row.iter().map(|field| csv_field(field)).collect::<Vec<String>>().join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    pub fn export_json(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn export_csv(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_csv())?;
+        Ok(())
+    }
+}
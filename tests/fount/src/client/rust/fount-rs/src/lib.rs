@@ -1,25 +1,41 @@
 pub mod structs;
+pub mod magic;
+pub mod spellbook;
+pub mod ledger;
+pub mod consensus;
+pub mod nineum;
+pub mod messages;
 
 #[cfg(test)]
 mod tests;
 
-use reqwest::{Client, Response};
+use allyabase_transport_rs::{send_signed, Clock, HttpRequest, HttpResponse, Idempotency, Method, Payloads, PooledTransport, RetryPolicy, TracingTransport, Transport, TransportError};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use serde_json::Value;
 use sessionless::hex::IntoHex;
-use sessionless::{Sessionless, Signature};
-use std::time::{SystemTime, UNIX_EPOCH};
+use sessionless::Sessionless;
 use std::collections::HashMap;
 use std::option::Option;
-use crate::structs::{Gateway, Nineum, Spell, SpellResult, SuccessResult, Transfer};
+use std::sync::{Arc, Mutex};
+use crate::structs::{Nineum, Spell, SpellResult, SuccessResult, Transfer};
+use crate::ledger::{EntryKind, Ledger, Reconciliation};
+use crate::spellbook::{SpellbookError, SpellbookRegistry};
+pub use service_directory_rs::{Service, ServiceDirectory};
 
 pub struct Fount {
     base_url: String,
-    client: Client,
+    transport: Arc<dyn Transport>,
+    retry: RetryPolicy,
+    clock: Clock,
+    payloads: Payloads,
     pub sessionless: Sessionless,
+    uuids: Mutex<HashMap<String, String>>,
+    ledger: Option<Ledger>,
+    spellbooks: Option<SpellbookRegistry>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all="camelCase")]
 pub struct FountUser {
     pub pub_key: String,
@@ -41,170 +57,466 @@ impl Fount {
     pub fn new(base_url: Option<String>, sessionless: Option<Sessionless>) -> Self {
         Fount {
             base_url: base_url.unwrap_or("https://dev.fount.allyabase.com/".to_string()),
-            client: Client::new(),
+            transport: PooledTransport::shared(),
+            retry: RetryPolicy::default(),
+            clock: Clock::new(),
+            payloads: Payloads::default(),
             sessionless: sessionless.unwrap_or(Sessionless::new()),
+            uuids: Mutex::new(HashMap::new()),
+            ledger: None,
+            spellbooks: None,
         }
     }
 
-    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
-        self.client.get(url).send().await
+    /// Points the client at fount in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory, sessionless: Option<Sessionless>) -> Self {
+        Self::new(Some(directory.url(Service::Fount)), sessionless)
     }
 
-    async fn post(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
-        self.client
-            .post(url)
-            .json(&payload)
-            .send()
-            .await
+    /// Records every signed grant, transfer and spell this client sends in
+    /// `ledger`, reconciling the caller's account after each one.
+    pub fn with_ledger(mut self, ledger: Ledger) -> Self {
+        self.ledger = Some(ledger);
+        self
     }
 
-    async fn put(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
-        self.client
-            .put(url)
-            .json(&payload)
-            .send()
-            .await
+    pub fn ledger(&self) -> Option<&Ledger> {
+        self.ledger.as_ref()
     }
 
-    async fn delete(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
-        self.client
-            .delete(url)
-            .json(&payload)
-            .send()
-            .await
+    /// Prices spells cast with `cast_from_spellbook` from `spellbooks`.
+    pub fn with_spellbooks(mut self, spellbooks: SpellbookRegistry) -> Self {
+        self.spellbooks = Some(spellbooks);
+        self
     }
 
+    pub fn spellbooks(&self) -> Option<&SpellbookRegistry> {
+        self.spellbooks.as_ref()
+    }
+
+    /// Sends requests through `transport` instead of the connection pool the
+    /// client shares with others on its runtime, e.g. a `PooledTransport` of its own.
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Retries failed requests as `retry` says. Grants, transfers and spells
+    /// are only retried if it opts into non-idempotent retries.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// How much of each request and response to log at debug level.
+    /// `Payloads::Full` shows signatures and BDOs, so leave it off in production.
+    pub fn with_payloads(mut self, payloads: Payloads) -> Self {
+        self.payloads = payloads;
+        self
+    }
+
+    /// The transport wrapped in a `tracing` span per request.
+    fn traced(&self) -> TracingTransport<'_> {
+        TracingTransport::new("fount", &self.base_url, self.payloads, &*self.transport)
+    }
+
+    /// The clock this client signs with, corrected by every response fount
+    /// sends back.
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Measures how far this machine's clock is from fount's before anything
+    /// is signed, returning the drift in milliseconds if fount says.
+    pub async fn sync_clock(&self) -> Result<Option<i64>, Box<dyn std::error::Error>> {
+        Ok(self.clock.probe(&self.traced(), &self.base_url).await?)
+    }
+
+    /// Sends the request `sign` builds from a fresh timestamp, again for
+    /// every retry.
+    async fn send(&self, idempotency: Idempotency, sign: impl FnMut(&str) -> HttpRequest) -> Result<HttpResponse, TransportError> {
+        send_signed(&self.traced(), &self.retry, &self.clock, idempotency, sign).await
+    }
+
+    // Requests are stamped by `clock`; the tests still build spells by hand.
+    #[cfg(test)]
     fn get_timestamp() -> String {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
             .expect("Time went backwards")
             .as_millis()
             .to_string()
     }
 
+    /// Remembers which fount uuid belongs to `pub_key`, so later pubKey based
+    /// calls can skip the lookup.
+    pub fn cache_uuid(&self, pub_key: &str, uuid: &str) {
+        self.uuids.lock().unwrap().insert(pub_key.to_string(), uuid.to_string());
+    }
+
+    pub fn cached_uuid(&self, pub_key: &str) -> Option<String> {
+        self.uuids.lock().unwrap().get(pub_key).cloned()
+    }
+
+    fn remember(&self, user: &FountUser) {
+        self.cache_uuid(&user.pub_key, &user.uuid);
+    }
+
+    /// Reconciles the account a call left behind. `after` is the user the
+    /// call returned, fetched only if it returned none, and the nineum are
+    /// fetched only when `with_nineum` is set.
+    async fn reconcile(
+        &self,
+        uuid: &str,
+        after: Option<FountUser>,
+        with_nineum: bool,
+        check: impl FnOnce(&FountUser, &[String]) -> Vec<String>
+    ) -> Reconciliation {
+        let user = match after {
+            Some(user) => Ok(user),
+            None => self.get_user_by_uuid(uuid).await.map_err(|err| err.to_string())
+        };
+        let nineum = match with_nineum {
+            true => Some(self.get_nineum(uuid).await.map(|nineum| nineum.nineum).map_err(|err| err.to_string())),
+            false => None
+        };
+
+        let mut discrepancies = Vec::<String>::new();
+        if let Ok(ref user) = user {
+            let held = match nineum {
+                Some(Ok(ref nineum)) => nineum.as_slice(),
+                _ => &[]
+            };
+            discrepancies = check(user, held);
+            if let Some(Ok(ref nineum)) = nineum {
+                if user.nineum_count != nineum.len() as u64 {
+                    discrepancies.push(format!("nineumCount is {} but {} nineum were returned", user.nineum_count, nineum.len()));
+                }
+            }
+        }
+        if let Err(ref err) = user {
+            discrepancies.push(format!("could not fetch user {}: {}", uuid, err));
+        }
+        if let Some(Err(ref err)) = nineum {
+            discrepancies.push(format!("could not fetch nineum for {}: {}", uuid, err));
+        }
+
+        Reconciliation {
+            user: user.ok(),
+            nineum: nineum.and_then(Result::ok),
+            discrepancies
+        }
+    }
+
+    /// Appends a call that already went through to the ledger. The call's
+    /// result stands even if the ledger can't be written, since failing it
+    /// would invite a retry that spends twice, so a failed write is logged.
+    fn record(
+        &self,
+        kind: EntryKind,
+        transfer: Transfer,
+        signature: &str,
+        request: Value,
+        response: &Value,
+        reconciliation: Reconciliation
+    ) {
+        let Some(ref ledger) = self.ledger else {
+            return;
+        };
+
+        let timestamp = transfer.timestamp.clone();
+        if let Err(err) = ledger.record(kind, transfer, signature, request, response.clone(), Some(reconciliation)) {
+            tracing::error!(target: "allyabase", service = "fount", %timestamp, %signature, "could not record ledger entry: {}", err);
+        }
+    }
+
     pub async fn create_user(&self) -> Result<FountUser, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
         let pub_key = self.sessionless.public_key().to_hex();
-        let signature = self.sessionless.sign(&format!("{}{}", timestamp, pub_key)).to_hex();
-        
-        let payload = json!({
-            "timestamp": timestamp,
-            "pubKey": pub_key,
-            "signature": signature
-        }).as_object().unwrap().clone();
-
         let url = format!("{}user/create", self.base_url);
-        let res = self.put(&url, serde_json::Value::Object(payload)).await?;
-        let user: FountUser = res.json().await?;
+        let res = self.send(Idempotency::Idempotent, |timestamp| {
+            let signature = self.sessionless.sign(messages::create_user(timestamp, &pub_key)).to_hex();
+            let payload = json!({
+                "timestamp": timestamp,
+                "pubKey": pub_key,
+                "signature": signature
+            });
+            HttpRequest::new(Method::Put, &url, Some(payload))
+        }).await?;
+        let user: FountUser = res.json()?;
+        self.remember(&user);
 
         Ok(user)
     }
 
     pub async fn get_user_by_uuid(&self, uuid: &str) -> Result<FountUser, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}", timestamp, uuid);
-        let signature = self.sessionless.sign(&message).to_hex();
-
-        let url = format!("{}user/{}?timestamp={}&signature={}", self.base_url, uuid, timestamp, signature);
-        let res = self.get(&url).await?;
-        let user: FountUser = res.json().await?;
+        let res = self.send(Idempotency::Idempotent, |timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid)).to_hex();
+            let url = format!("{}user/{}?timestamp={}&signature={}", self.base_url, uuid, timestamp, signature);
+            HttpRequest::new(Method::Get, &url, None)
+        }).await?;
+        let user: FountUser = res.json()?;
+        self.remember(&user);
 
         Ok(user)
     }
 
     pub async fn get_user_by_public_key(&self) -> Result<FountUser, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
         let public_key = self.sessionless.public_key().to_hex();
-        let message = format!("{}{}", timestamp, public_key);
-        let signature = self.sessionless.sign(&message).to_hex();
-
-        let url = format!("{}user/pubKey/{}?timestamp={}&signature={}", self.base_url, public_key, timestamp, signature);
-        let res = self.get(&url).await?;
-        let user: FountUser = res.json().await?;
+        let res = self.send(Idempotency::Idempotent, |timestamp| {
+            let signature = self.sessionless.sign(messages::user_by_public_key(timestamp, &public_key)).to_hex();
+            let url = format!("{}user/pubKey/{}?timestamp={}&signature={}", self.base_url, public_key, timestamp, signature);
+            HttpRequest::new(Method::Get, &url, None)
+        }).await?;
+        let user: FountUser = res.json()?;
+        self.remember(&user);
 
         Ok(user)
     }
 
+    /// Resolves `pub_key` to a fount uuid. Fount only looks a key up for the
+    /// key's owner, so this client's own key is fetched, and any other key
+    /// must already be cached, from a user fount returned or from `cache_uuid`
+    /// with a uuid the counterparty shared.
+    pub async fn uuid_for_public_key(&self, pub_key: &str) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(uuid) = self.cached_uuid(pub_key) {
+            return Ok(uuid);
+        }
+        if pub_key != self.sessionless.public_key().to_hex() {
+            return Err(format!("no fount uuid known for {}; cache it with cache_uuid", pub_key).into());
+        }
+
+        Ok(self.get_user_by_public_key().await?.uuid)
+    }
+
+    pub async fn uuids_for_public_keys(&self, pub_keys: &[String]) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        let mut uuids = HashMap::<String, String>::new();
+        for pub_key in pub_keys.iter() {
+            uuids.insert(pub_key.clone(), self.uuid_for_public_key(pub_key).await?);
+        }
+
+        Ok(uuids)
+    }
+
+    /// Signs and resolves `spell_name` as the fount user at `caster_uuid`, the
+    /// way the allyabase services' MAGIC routes expect. `components` carries
+    /// the spell's arguments for the destination stops.
+    pub async fn cast(&self, caster_uuid: &str, spell_name: &str, total_cost: u32, ordinal: u32, components: Value) -> Result<SpellResult, Box<dyn std::error::Error>> {
+        self.resolve_stamped(|timestamp| {
+            let mut spell = Spell {
+                timestamp: timestamp.to_string(),
+                spell: spell_name.to_string(),
+                caster_uuid: caster_uuid.to_string(),
+                total_cost,
+                mp: true,
+                ordinal,
+                ..Default::default()
+            };
+            spell.extra.insert("components".to_string(), components.clone());
+            spell.caster_signature = self.sessionless.sign(spell.message()).to_hex();
+            spell
+        }).await
+    }
+
+    /// Like `cast`, but takes `total_cost` from the spellbooks this client was
+    /// given with `with_spellbooks`.
+    pub async fn cast_from_spellbook(&self, caster_uuid: &str, spell_name: &str, ordinal: u32, components: Value) -> Result<SpellResult, Box<dyn std::error::Error>> {
+        let spellbooks = self.spellbooks.as_ref().ok_or(SpellbookError::UnknownSpell(spell_name.to_string()))?;
+        let total_cost = spellbooks.total_cost(spell_name)?;
+
+        self.cast(caster_uuid, spell_name, total_cost, ordinal, components).await
+    }
 
+    /// Sends `spell` as it was signed: its caster and gateways signed it, so a
+    /// retry, if the retry policy allows one, repeats their timestamps. `cast`
+    /// and `resolve_with_consensus` sign each attempt afresh instead.
     pub async fn resolve(&self, spell: &Spell) -> Result<SpellResult, Box<dyn std::error::Error>> {
-        let spell_name = spell.spell.clone();
-        let url = format!("{}resolve/{}", self.base_url, spell_name);
-        let res = self.post(&url, serde_json::to_value(&spell)?).await?;
-        let spell_result: SpellResult = res.json().await?;
+        self.resolve_stamped(|_| spell.clone()).await
+    }
+
+    /// Resolves the spell `stamp` builds from a fresh timestamp, again for
+    /// every retry.
+    pub(crate) async fn resolve_stamped(&self, mut stamp: impl FnMut(&str) -> Spell) -> Result<SpellResult, Box<dyn std::error::Error>> {
+        let mut sent = None;
+        let res = self.send(Idempotency::NonIdempotent, |timestamp| {
+            let spell = stamp(timestamp);
+            let url = format!("{}resolve/{}", self.base_url, spell.spell);
+            let request = serde_json::to_value(&spell).expect("spell serializes");
+            sent = Some((spell, request.clone()));
+            HttpRequest::new(Method::Post, &url, Some(request))
+        }).await?;
+        let (spell, request) = sent.expect("signed before sending");
+        let body: Value = res.json()?;
+
+        let transfer = Transfer {
+            timestamp: spell.timestamp.clone(),
+            sender_uuid: spell.caster_uuid.clone(),
+            receiver_uuid: "fount".to_string(),
+            message: spell.message()
+        };
+        if self.ledger.is_some() {
+            let reconciliation = self.reconcile(&spell.caster_uuid, None, false, |_, _| Vec::new()).await;
+            self.record(EntryKind::Spell, transfer, &spell.caster_signature, request, &body, reconciliation);
+        }
+
+        let spell_result: SpellResult = serde_json::from_value(body)?;
 
         Ok(spell_result)
     }
 
     pub async fn grant(&self, uuid: &str, destination_uuid: &str, amount: &u32, description: &str) -> Result<FountUser, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}{}{}{}", timestamp, uuid, destination_uuid, amount, description);
-        let signature = self.sessionless.sign(&message).to_hex();
-
-        let payload = json!({
-            "timestamp": timestamp,
-            "uuid": uuid,
-            "destinationUUID": destination_uuid,
-            "amount": amount,
-            "description": description,
-            "signature": signature
-        }).as_object().unwrap().clone();
-
         let url = format!("{}user/{}/grant", self.base_url, uuid);
-        let res = self.post(&url, serde_json::Value::Object(payload)).await?;
-        let user: FountUser = res.json().await?;
+        let before = match self.ledger {
+            Some(_) => Some(self.get_user_by_uuid(uuid).await),
+            None => None
+        };
+        let mut signed = None;
+        let res = self.send(Idempotency::NonIdempotent, |timestamp| {
+            let message = messages::grant(timestamp, uuid, destination_uuid, *amount, description);
+            let signature = self.sessionless.sign(&message).to_hex();
+            let payload = json!({
+                "timestamp": timestamp,
+                "uuid": uuid,
+                "destinationUUID": destination_uuid,
+                "amount": amount,
+                "description": description,
+                "signature": signature
+            });
+            signed = Some((timestamp.to_string(), message, signature, payload.clone()));
+            HttpRequest::new(Method::Post, &url, Some(payload))
+        }).await?;
+        let (timestamp, message, signature, request) = signed.expect("signed before sending");
+        let body: Value = res.json()?;
+
+        let granted = serde_json::from_value::<FountUser>(body.clone()).ok();
+        let transfer = Transfer {
+            timestamp,
+            sender_uuid: uuid.to_string(),
+            receiver_uuid: destination_uuid.to_string(),
+            message
+        };
+        if let Some(before) = before {
+            let returned_user = granted.is_some();
+            let reconciliation = self.reconcile(uuid, granted, false, |after, _| {
+                let mut discrepancies = Vec::<String>::new();
+                if !returned_user {
+                    discrepancies.push("grant did not return a user".to_string());
+                }
+                let before = match before {
+                    Ok(before) => before,
+                    Err(err) => {
+                        discrepancies.push(format!("could not fetch user {} before the grant: {}", uuid, err));
+                        return discrepancies;
+                    }
+                };
+
+                // Fount spends a fifteenth of the experience in MP, and
+                // quietly grants nothing if the caster can't afford it.
+                let cost = amount.div_ceil(15);
+                if *amount > before.mp * 15 {
+                    discrepancies.push(format!("grant of {} experience needs {} MP but only {} was available", amount, cost, before.mp));
+                } else if before.mp.saturating_sub(after.mp) != cost {
+                    discrepancies.push(format!("grant of {} experience should have spent {} MP but spent {}", amount, cost, before.mp as i64 - after.mp as i64));
+                }
+
+                // Only this client's own account can be read back, so the
+                // destination is checked when it grants to itself.
+                if destination_uuid == uuid && *amount <= before.mp * 15 {
+                    let (was, is) = (before.experience + before.experience_pool as u64, after.experience + after.experience_pool as u64);
+                    if is != was + *amount as u64 {
+                        discrepancies.push(format!("grant of {} experience moved experience + experiencePool from {} to {}", amount, was, is));
+                    }
+                }
+                discrepancies
+            }).await;
+            self.record(EntryKind::Grant, transfer, &signature, request, &body, reconciliation);
+        }
+
+        let user: FountUser = serde_json::from_value(body)?;
 
         Ok(user)
     }
 
     pub async fn get_nineum(&self, uuid: &str) -> Result<Nineum, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}", timestamp, uuid);
-        let signature = self.sessionless.sign(&message).to_hex();
-
-        let url = format!("{}user/{}/nineum?timestamp={}&signature={}", self.base_url, uuid, timestamp, signature);
-        let res = self.get(&url).await?;
-        let nineum: Nineum = res.json().await?;
+        let res = self.send(Idempotency::Idempotent, |timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid)).to_hex();
+            let url = format!("{}user/{}/nineum?timestamp={}&signature={}", self.base_url, uuid, timestamp, signature);
+            HttpRequest::new(Method::Get, &url, None)
+        }).await?;
+        let nineum: Nineum = res.json()?;
 
         Ok(nineum)
     }
 
     pub async fn transfer_nineum(&self, uuid: &str, destination_uuid: &str, nineum_unique_ids: &Vec<String>, price: &u32, currency: &str) -> Result<FountUser, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let nineum_ids = nineum_unique_ids.join("");
-        let message = format!("{}{}{}{}{}{}", timestamp, uuid, destination_uuid, nineum_ids, price, currency);
-println!("{}", message);
-        let signature = self.sessionless.sign(&message).to_hex();
-
-        let payload = json!({
-            "timestamp": timestamp,
-            "destinationUUID": destination_uuid,
-            "nineumUniqueIds": nineum_unique_ids,
-            "price": price,
-            "currency": currency, 
-            "signature": signature
-        }).as_object().unwrap().clone();
-
         let url = format!("{}user/{}/transfer", self.base_url, uuid);
-        let res = self.post(&url, serde_json::Value::Object(payload)).await?;
-        let user: FountUser = res.json().await?;
+        let mut signed = None;
+        let res = self.send(Idempotency::NonIdempotent, |timestamp| {
+            let message = messages::transfer(timestamp, uuid, destination_uuid, nineum_unique_ids, *price, currency);
+            let signature = self.sessionless.sign(&message).to_hex();
+            let payload = json!({
+                "timestamp": timestamp,
+                "destinationUUID": destination_uuid,
+                "nineumUniqueIds": nineum_unique_ids,
+                "price": price,
+                "currency": currency, 
+                "signature": signature
+            });
+            signed = Some((timestamp.to_string(), message, signature, payload.clone()));
+            HttpRequest::new(Method::Post, &url, Some(payload))
+        }).await?;
+        let (timestamp, message, signature, request) = signed.expect("signed before sending");
+        let body: Value = res.json()?;
+
+        let transferred = serde_json::from_value::<FountUser>(body.clone()).ok();
+        let transfer = Transfer {
+            timestamp,
+            sender_uuid: uuid.to_string(),
+            receiver_uuid: destination_uuid.to_string(),
+            message
+        };
+        if self.ledger.is_some() {
+            let returned_user = transferred.is_some();
+            let reconciliation = self.reconcile(uuid, transferred, true, |_, nineum| {
+                let mut discrepancies: Vec<String> = nineum_unique_ids
+                    .iter()
+                    .filter(|id| nineum.contains(id))
+                    .map(|id| format!("{} is still held after transfer", id))
+                    .collect();
+                if !returned_user {
+                    discrepancies.insert(0, "transfer did not return a user".to_string());
+                }
+                discrepancies
+            }).await;
+            self.record(EntryKind::Transfer, transfer, &signature, request, &body, reconciliation);
+        }
+
+        let user: FountUser = serde_json::from_value(body)?;
  
         Ok(user)
     }
 
-    pub async fn delete_user(&self, uuid: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
-        let timestamp = Self::get_timestamp();
-        let message = format!("{}{}", timestamp, uuid);
-        let signature = self.sessionless.sign(&message).to_hex();
+    pub async fn grant_to_public_key(&self, uuid: &str, destination_pub_key: &str, amount: &u32, description: &str) -> Result<FountUser, Box<dyn std::error::Error>> {
+        let destination_uuid = self.uuid_for_public_key(destination_pub_key).await?;
+        self.grant(uuid, &destination_uuid, amount, description).await
+    }
 
-        let payload = json!({
-          "timestamp": timestamp,
-          "uuid": uuid,
-          "signature": signature
-        }).as_object().unwrap().clone();
+    pub async fn transfer_nineum_to_public_key(&self, uuid: &str, destination_pub_key: &str, nineum_unique_ids: &Vec<String>, price: &u32, currency: &str) -> Result<FountUser, Box<dyn std::error::Error>> {
+        let destination_uuid = self.uuid_for_public_key(destination_pub_key).await?;
+        self.transfer_nineum(uuid, &destination_uuid, nineum_unique_ids, price, currency).await
+    }
 
+    pub async fn delete_user(&self, uuid: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
         let url = format!("{}user/{}", self.base_url, uuid);
-        let res = self.delete(&url, serde_json::Value::Object(payload)).await?;
-        let success: SuccessResult = res.json().await?;
+        let res = self.send(Idempotency::Idempotent, |timestamp| {
+            let signature = self.sessionless.sign(messages::user(timestamp, uuid)).to_hex();
+            let payload = json!({
+              "timestamp": timestamp,
+              "uuid": uuid,
+              "signature": signature
+            });
+            HttpRequest::new(Method::Delete, &url, Some(payload))
+        }).await?;
+        let success: SuccessResult = res.json()?;
 
         Ok(success)
     }
@@ -0,0 +1,229 @@
+use std::fmt;
+use sessionless::{PublicKey, Signature};
+use sessionless_helpers_rs::{check_timestamp as check_window, now, TimestampError};
+use crate::structs::{Gateway, Spell};
+
+pub use sessionless_helpers_rs::ALLOWED_TIME_DIFFERENCE;
+pub(crate) use sessionless_helpers_rs::signs;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VerifyError {
+    InvalidPublicKey(String),
+    InvalidSignature(String),
+    Unsigned,
+    InvalidTimestamp(String),
+    StaleTimestamp { timestamp: u64, now: u64 },
+    MpFormatting { expected: String, signed: String },
+    FieldOrder { expected: Vec<&'static str>, signed: Vec<&'static str> },
+    Mismatch { message: String },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::InvalidPublicKey(key) => write!(f, "invalid public key: {}", key),
+            VerifyError::InvalidSignature(signature) => write!(f, "invalid signature: {}", signature),
+            VerifyError::Unsigned => write!(f, "gateway is unsigned and will be skipped by the resolver"),
+            VerifyError::InvalidTimestamp(timestamp) => write!(f, "timestamp is not a number of milliseconds: {}", timestamp),
+            VerifyError::StaleTimestamp { timestamp, now } => write!(
+                f,
+                "stale timestamp: {} is {}ms from now ({}), allowed difference is {}ms",
+                timestamp,
+                now.abs_diff(*timestamp),
+                now,
+                ALLOWED_TIME_DIFFERENCE
+            ),
+            VerifyError::MpFormatting { expected, signed } => write!(
+                f,
+                "mp was signed as `{}` but the resolver formats it as `{}`",
+                signed,
+                expected
+            ),
+            VerifyError::FieldOrder { expected, signed } => write!(
+                f,
+                "fields were signed in the order {} but the resolver expects {}",
+                signed.join(" + "),
+                expected.join(" + ")
+            ),
+            VerifyError::Mismatch { message } => write!(f, "signature does not match message {}", message),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+const SPELL_FIELDS: [&str; 6] = ["timestamp", "spell", "casterUUID", "totalCost", "mp", "ordinal"];
+const GATEWAY_FIELDS: [&str; 4] = ["timestamp", "uuid", "minimumCost", "ordinal"];
+
+// Other spellings of a bool that clients in other languages have been known to sign.
+const MP_VARIANTS: [(&str, &str); 4] = [("1", "0"), ("True", "False"), ("TRUE", "FALSE"), ("yes", "no")];
+
+pub(crate) fn parse_public_key(pub_key: &str) -> Result<PublicKey, VerifyError> {
+    sessionless_helpers_rs::parse_public_key(pub_key).ok_or_else(|| VerifyError::InvalidPublicKey(pub_key.to_string()))
+}
+
+pub(crate) fn parse_signature(signature: &str) -> Result<Signature, VerifyError> {
+    sessionless_helpers_rs::parse_signature(signature).ok_or_else(|| VerifyError::InvalidSignature(signature.to_string()))
+}
+
+fn check_timestamp(timestamp: &str, now: u64) -> Result<(), VerifyError> {
+    match check_window(timestamp, now) {
+        Ok(_) => Ok(()),
+        Err(TimestampError::Invalid(timestamp)) => Err(VerifyError::InvalidTimestamp(timestamp)),
+        Err(TimestampError::Stale { timestamp, now }) => Err(VerifyError::StaleTimestamp { timestamp, now })
+    }
+}
+
+// Tries every other ordering of `values` to explain a failed signature.
+fn find_field_order(
+    names: &[&'static str],
+    values: &[String],
+    pub_key: &PublicKey,
+    signature: &Signature
+) -> Option<Vec<&'static str>> {
+    fn permute(
+        order: &mut Vec<usize>,
+        remaining: &mut Vec<usize>,
+        check: &mut dyn FnMut(&[usize]) -> bool
+    ) -> Option<Vec<usize>> {
+        if remaining.is_empty() {
+            return if check(order) { Some(order.clone()) } else { None };
+        }
+        for i in 0..remaining.len() {
+            let next = remaining.remove(i);
+            order.push(next);
+            let found = permute(order, remaining, check);
+            order.pop();
+            remaining.insert(i, next);
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+
+    let mut check = |order: &[usize]| {
+        let in_order = order.iter().enumerate().all(|(i, index)| i == *index);
+        !in_order && signs(&order.iter().map(|i| values[*i].as_str()).collect::<String>(), pub_key, signature)
+    };
+
+    permute(&mut Vec::new(), &mut (0..values.len()).collect(), &mut check)
+        .map(|order| order.iter().map(|i| names[*i]).collect())
+}
+
+impl Spell {
+    /// The message the resolver expects `caster_signature` to sign:
+    /// `timestamp + spell + casterUUID + totalCost + mp + ordinal`.
+    pub fn message(&self) -> String {
+        format!("{}{}{}{}{}{}", self.timestamp, self.spell, self.caster_uuid, self.total_cost, self.mp, self.ordinal)
+    }
+
+    /// Checks the caster's signature the same way fount's `/resolve` does.
+    pub fn verify(&self, caster_pub_key: &str) -> Result<(), VerifyError> {
+        self.verify_at(caster_pub_key, now())
+    }
+
+    /// Like `verify`, but checks the timestamp window against `now` (in milliseconds).
+    pub fn verify_at(&self, caster_pub_key: &str, now: u64) -> Result<(), VerifyError> {
+        check_timestamp(&self.timestamp, now)?;
+
+        let pub_key = parse_public_key(caster_pub_key)?;
+        let signature = parse_signature(&self.caster_signature)?;
+
+        let message = self.message();
+        if signs(&message, &pub_key, &signature) {
+            return Ok(());
+        }
+        Err(VerifyError::Mismatch { message })
+    }
+
+    /// Like `verify`, but when the signature doesn't match, works out whether
+    /// the caster signed `mp` in another spelling or the fields in another
+    /// order. That can take hundreds of verifies, so it's for debugging a
+    /// client, not for checking spells.
+    pub fn diagnose(&self, caster_pub_key: &str) -> Result<(), VerifyError> {
+        let message = match self.verify(caster_pub_key) {
+            Err(VerifyError::Mismatch { message }) => message,
+            other => return other
+        };
+
+        let pub_key = parse_public_key(caster_pub_key)?;
+        let signature = parse_signature(&self.caster_signature)?;
+
+        let mut values = vec![
+            self.timestamp.clone(),
+            self.spell.clone(),
+            self.caster_uuid.clone(),
+            self.total_cost.to_string(),
+            self.mp.to_string(),
+            self.ordinal.to_string()
+        ];
+
+        for (truthy, falsy) in MP_VARIANTS {
+            let signed = if self.mp { truthy } else { falsy };
+            values[4] = signed.to_string();
+            if signs(&values.concat(), &pub_key, &signature) {
+                return Err(VerifyError::MpFormatting { expected: self.mp.to_string(), signed: signed.to_string() });
+            }
+        }
+        values[4] = self.mp.to_string();
+
+        if let Some(signed) = find_field_order(&SPELL_FIELDS, &values, &pub_key, &signature) {
+            return Err(VerifyError::FieldOrder { expected: SPELL_FIELDS.to_vec(), signed });
+        }
+
+        Err(VerifyError::Mismatch { message })
+    }
+}
+
+impl Gateway {
+    /// The message the resolver expects `signature` to sign:
+    /// `timestamp + uuid + minimumCost + ordinal`.
+    pub fn message(&self) -> String {
+        format!("{}{}{}{}", self.timestamp, self.uuid, self.minimum_cost, self.ordinal)
+    }
+
+    /// Checks the gateway's signature the same way fount's `/resolve` does.
+    /// The resolver skips gateways whose signature is shorter than five
+    /// characters, so those are reported as `Unsigned`. Gateway timestamps are
+    /// not held to the time window.
+    pub fn verify(&self, gateway_pub_key: &str) -> Result<(), VerifyError> {
+        if self.signature.len() < 5 {
+            return Err(VerifyError::Unsigned);
+        }
+
+        let pub_key = parse_public_key(gateway_pub_key)?;
+        let signature = parse_signature(&self.signature)?;
+
+        let message = self.message();
+        if signs(&message, &pub_key, &signature) {
+            return Ok(());
+        }
+        Err(VerifyError::Mismatch { message })
+    }
+
+    /// Like `verify`, but when the signature doesn't match, works out whether
+    /// the gateway signed its fields in another order. For debugging only.
+    pub fn diagnose(&self, gateway_pub_key: &str) -> Result<(), VerifyError> {
+        let message = match self.verify(gateway_pub_key) {
+            Err(VerifyError::Mismatch { message }) => message,
+            other => return other
+        };
+
+        let pub_key = parse_public_key(gateway_pub_key)?;
+        let signature = parse_signature(&self.signature)?;
+
+        let values = vec![
+            self.timestamp.clone(),
+            self.uuid.clone(),
+            self.minimum_cost.to_string(),
+            self.ordinal.to_string()
+        ];
+
+        if let Some(signed) = find_field_order(&GATEWAY_FIELDS, &values, &pub_key, &signature) {
+            return Err(VerifyError::FieldOrder { expected: GATEWAY_FIELDS.to_vec(), signed });
+        }
+
+        Err(VerifyError::Mismatch { message })
+    }
+}
@@ -0,0 +1,31 @@
+//! The messages fount checks signatures against, one per signed route. Spells
+//! and gateways sign `Spell::message` and `Gateway::message`.
+//!
+//! config/signature-vectors.json pins these down, and `test_signature_vectors`
+//! checks them.
+
+/// `PUT /user/create`: `timestamp + pubKey`.
+pub fn create_user(timestamp: &str, pub_key: &str) -> String {
+    format!("{}{}", timestamp, pub_key)
+}
+
+/// `GET /user/:uuid`, `GET /user/:uuid/nineum` and `DELETE /user/:uuid`: `timestamp + uuid`.
+pub fn user(timestamp: &str, uuid: &str) -> String {
+    format!("{}{}", timestamp, uuid)
+}
+
+/// `GET /user/pubKey/:pubKey`: `timestamp + pubKey`, checked against that key.
+pub fn user_by_public_key(timestamp: &str, pub_key: &str) -> String {
+    format!("{}{}", timestamp, pub_key)
+}
+
+/// `POST /user/:uuid/grant`: `timestamp + uuid + destinationUUID + amount + description`.
+pub fn grant(timestamp: &str, uuid: &str, destination_uuid: &str, amount: u32, description: &str) -> String {
+    format!("{}{}{}{}{}", timestamp, uuid, destination_uuid, amount, description)
+}
+
+/// `POST /user/:uuid/transfer`:
+/// `timestamp + uuid + destinationUUID + nineumUniqueIds.join('') + price + currency`.
+pub fn transfer(timestamp: &str, uuid: &str, destination_uuid: &str, nineum_unique_ids: &[String], price: u32, currency: &str) -> String {
+    format!("{}{}{}{}{}{}", timestamp, uuid, destination_uuid, nineum_unique_ids.join(""), price, currency)
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::structs::Nineum;
+
+// The name tables from fount's nineum.js, by their two digit codes.
+const CHARGES: [(&str, &str); 2] = [("01", "positive"), ("02", "negative")];
+const DIRECTIONS: [(&str, &str); 6] = [("01", "north"), ("02", "south"), ("03", "east"), ("04", "west"), ("05", "up"), ("06", "down")];
+const RARITIES: [(&str, &str); 9] = [
+    ("01", "common"), ("02", "uncommon"), ("03", "rare"), ("04", "epic"), ("05", "legendary"),
+    ("06", "mythical"), ("09", "nine"), ("fe", "administrative"), ("ff", "galactic")
+];
+const SIZES: [(&str, &str); 8] = [("01", "miniscule"), ("02", "tiny"), ("03", "small"), ("04", "medium"), ("05", "standard"), ("06", "big"), ("07", "large"), ("08", "huge")];
+const TEXTURES: [(&str, &str); 8] = [("01", "soft"), ("02", "bumpy"), ("03", "satin"), ("04", "rough"), ("05", "gritty"), ("06", "metalic"), ("07", "plush"), ("08", "woolen")];
+const SHAPES: [(&str, &str); 8] = [("01", "sphere"), ("02", "cylinder"), ("03", "tetrahedron"), ("04", "cube"), ("05", "octahedron"), ("06", "dodecahedron"), ("07", "cone"), ("08", "torus")];
+
+fn name(table: &[(&str, &'static str)], code: &str) -> Option<&'static str> {
+    table.iter().find(|(known, _)| *known == code).map(|(_, name)| *name)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NineumError {
+    TooShort(String),
+    NotHex(String)
+}
+
+impl fmt::Display for NineumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NineumError::TooShort(id) => write!(f, "{} is too short to be a nineum id", id),
+            NineumError::NotHex(id) => write!(f, "{} is not a hex nineum id", id)
+        }
+    }
+}
+
+impl std::error::Error for NineumError {}
+
+/// A nineum unique id split into its parts:
+/// `universe(2) galaxy(8) charge direction rarity size texture shape(2 each) year(2) ordinal`.
+/// The ordinal is normally eight digits but is read to the end of the id.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct DecodedNineum {
+    pub id: String,
+    pub universe: String,
+    pub galaxy: String,
+    pub charge: String,
+    pub direction: String,
+    pub rarity: String,
+    pub size: String,
+    pub texture: String,
+    pub shape: String,
+    pub year: String,
+    pub ordinal: String
+}
+
+impl DecodedNineum {
+    pub fn parse(id: &str) -> Result<Self, NineumError> {
+        if id.len() < 25 {
+            return Err(NineumError::TooShort(id.to_string()));
+        }
+        if !id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(NineumError::NotHex(id.to_string()));
+        }
+
+        let part = |start: usize, len: usize| id[start..start + len].to_lowercase();
+        Ok(DecodedNineum {
+            id: id.to_string(),
+            universe: part(0, 2),
+            galaxy: part(2, 8),
+            charge: part(10, 2),
+            direction: part(12, 2),
+            rarity: part(14, 2),
+            size: part(16, 2),
+            texture: part(18, 2),
+            shape: part(20, 2),
+            year: part(22, 2),
+            ordinal: id[24..].to_string()
+        })
+    }
+
+    /// The twelve digit flavor: charge, direction, rarity, size, texture and shape.
+    pub fn flavor(&self) -> String {
+        format!("{}{}{}{}{}{}", self.charge, self.direction, self.rarity, self.size, self.texture, self.shape)
+    }
+
+    pub fn charge_name(&self) -> Option<&'static str> {
+        name(&CHARGES, &self.charge)
+    }
+
+    pub fn direction_name(&self) -> Option<&'static str> {
+        name(&DIRECTIONS, &self.direction)
+    }
+
+    pub fn rarity_name(&self) -> Option<&'static str> {
+        name(&RARITIES, &self.rarity)
+    }
+
+    pub fn size_name(&self) -> Option<&'static str> {
+        name(&SIZES, &self.size)
+    }
+
+    pub fn texture_name(&self) -> Option<&'static str> {
+        name(&TEXTURES, &self.texture)
+    }
+
+    pub fn shape_name(&self) -> Option<&'static str> {
+        name(&SHAPES, &self.shape)
+    }
+
+    /// Galactic nineum gives its holder control of a galaxy.
+    pub fn is_galactic(&self) -> bool {
+        self.rarity == "ff"
+    }
+
+    pub fn is_administrative(&self) -> bool {
+        self.rarity == "fe"
+    }
+}
+
+impl Nineum {
+    /// Decodes every id, failing on the first malformed one.
+    pub fn decode(&self) -> Result<Vec<DecodedNineum>, NineumError> {
+        self.nineum.iter().map(|id| DecodedNineum::parse(id)).collect()
+    }
+}
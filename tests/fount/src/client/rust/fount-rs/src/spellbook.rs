@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use bdo_rs::{Spellbook, BDO};
+use crate::structs::Spell;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct Destination {
+    pub stop_name: String,
+    #[serde(rename = "stopURL")]
+    pub stop_url: String
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct RequiredNineum {
+    pub galaxy: String,
+    pub system: String,
+    pub flavor: String
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct SpellbookEntry {
+    pub cost: Option<u32>,
+    #[serde(default)]
+    pub destinations: Vec<Destination>,
+    pub resolver: Option<String>,
+    pub mp: Option<bool>,
+    pub required_nineum: Option<RequiredNineum>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+impl SpellbookEntry {
+    /// Every stop on the route except the resolver, in the order the spell visits them.
+    pub fn gateways(&self) -> Vec<&Destination> {
+        self.destinations
+            .iter()
+            .filter(|destination| Some(&destination.stop_name) != self.resolver.as_ref())
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum SpellbookError {
+    Io(std::io::Error),
+    Parse(String),
+    UnknownSpell(String),
+    MissingCost(String)
+}
+
+impl fmt::Display for SpellbookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpellbookError::Io(err) => write!(f, "could not read spellbook: {}", err),
+            SpellbookError::Parse(reason) => write!(f, "could not parse spellbook: {}", reason),
+            SpellbookError::UnknownSpell(spell) => write!(f, "no spellbook defines {}", spell),
+            SpellbookError::MissingCost(spell) => write!(f, "spellbook entry for {} has no cost", spell)
+        }
+    }
+}
+
+impl std::error::Error for SpellbookError {}
+
+/// Spell definitions keyed by spellbook name, in the shape fount keeps in
+/// `spellbooks/spellbook.js`.
+#[derive(Clone, Debug, Default)]
+pub struct SpellbookRegistry {
+    spellbooks: Vec<(String, HashMap<String, SpellbookEntry>)>
+}
+
+impl SpellbookRegistry {
+    pub fn new() -> Self {
+        SpellbookRegistry::default()
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SpellbookError> {
+        let contents = std::fs::read_to_string(path).map_err(SpellbookError::Io)?;
+        let json: Value = serde_json::from_str(&contents).map_err(|err| SpellbookError::Parse(err.to_string()))?;
+        let mut registry = SpellbookRegistry::new();
+        match json {
+            Value::Array(spellbooks) => {
+                for spellbook in spellbooks.iter() {
+                    registry.add_json(spellbook)?;
+                }
+            },
+            spellbook => registry.add_json(&spellbook)?
+        }
+
+        Ok(registry)
+    }
+
+    pub async fn from_bdo(bdo: &BDO, uuid: &str, hash: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut registry = SpellbookRegistry::new();
+        for spellbook in bdo.get_spellbooks(uuid, hash).await?.iter() {
+            registry.add_spellbook(spellbook)?;
+        }
+
+        Ok(registry)
+    }
+
+    pub fn add_spellbook(&mut self, spellbook: &Spellbook) -> Result<(), SpellbookError> {
+        let json = serde_json::to_value(spellbook).map_err(|err| SpellbookError::Parse(err.to_string()))?;
+        self.add_json(&json)
+    }
+
+    /// Adds a spellbook given as `{ spellbookName, <spellName>: { cost, destinations, resolver, ... } }`.
+    /// A spellbook with the same name as one already loaded replaces it.
+    pub fn add_json(&mut self, spellbook: &Value) -> Result<(), SpellbookError> {
+        let Some(fields) = spellbook.as_object() else {
+            return Err(SpellbookError::Parse("spellbook is not an object".to_string()));
+        };
+        let name = fields.get("spellbookName").and_then(Value::as_str).unwrap_or_default().to_string();
+
+        let mut spells = HashMap::<String, SpellbookEntry>::new();
+        for (spell_name, entry) in fields.iter() {
+            if spell_name == "spellbookName" || !entry.is_object() {
+                continue;
+            }
+            let entry: SpellbookEntry = serde_json::from_value(entry.clone())
+                .map_err(|err| SpellbookError::Parse(format!("{}: {}", spell_name, err)))?;
+            spells.insert(spell_name.clone(), entry);
+        }
+
+        self.spellbooks.retain(|(existing, _)| *existing != name);
+        self.spellbooks.push((name, spells));
+
+        Ok(())
+    }
+
+    /// Looks a spell up across all spellbooks. As with the services' own
+    /// lookups, the most recently added spellbook wins.
+    pub fn get(&self, spell_name: &str) -> Option<&SpellbookEntry> {
+        self.spellbooks
+            .iter()
+            .rev()
+            .find_map(|(_, spells)| spells.get(spell_name))
+    }
+
+    pub fn total_cost(&self, spell_name: &str) -> Result<u32, SpellbookError> {
+        let entry = self.get(spell_name).ok_or_else(|| SpellbookError::UnknownSpell(spell_name.to_string()))?;
+        entry.cost.ok_or_else(|| SpellbookError::MissingCost(spell_name.to_string()))
+    }
+
+    pub fn required_gateways(&self, spell_name: &str) -> Result<Vec<Destination>, SpellbookError> {
+        let entry = self.get(spell_name).ok_or_else(|| SpellbookError::UnknownSpell(spell_name.to_string()))?;
+        Ok(entry.gateways().into_iter().cloned().collect())
+    }
+
+    /// Fills in `total_cost` and `mp` for `spell.spell` and returns the stops
+    /// the spell still has to pass through, in order. Sign the spell after
+    /// calling this, since `total_cost` is part of the caster's message. Each
+    /// stop adds its own signed `Gateway` to `spell.gateways` as the spell
+    /// reaches it, so none are added here.
+    pub fn prepare(&self, spell: &mut Spell) -> Result<Vec<Destination>, SpellbookError> {
+        let entry = self.get(&spell.spell).ok_or_else(|| SpellbookError::UnknownSpell(spell.spell.clone()))?;
+
+        spell.total_cost = self.total_cost(&spell.spell)?;
+        if let Some(mp) = entry.mp {
+            spell.mp = mp;
+        }
+
+        Ok(entry.gateways().into_iter().cloned().collect())
+    }
+}
@@ -1,6 +1,7 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 use std::collections::HashMap;
+use std::fmt;
 use serde_json::Value;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -66,9 +67,52 @@ impl Default for Spell {
 #[serde(rename_all="camelCase")]
 pub struct SpellResult {
     pub success: bool,
-    // arbitrary json somehow?
+    // Whatever the spell's stops sent back, e.g. `user` or `error`.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
 }
 
+impl SpellResult {
+    /// The result if the spell succeeded, otherwise the `error` its stops
+    /// sent back.
+    pub fn succeeded(self) -> Result<SpellResult, SpellFailed> {
+        if self.success {
+            return Ok(self);
+        }
+        let error = self.extra.get("error").map(|error| error.as_str().map(String::from).unwrap_or_else(|| error.to_string()));
+
+        Err(SpellFailed(error.unwrap_or_default()))
+    }
+
+    /// Reads `name` from a successful result, e.g. the `user` a create spell
+    /// sends back.
+    pub fn field<T: DeserializeOwned>(self, name: &str) -> Result<T, Box<dyn std::error::Error>> {
+        let mut result = self.succeeded()?;
+        let value = result.extra.remove(name).ok_or_else(|| format!("spell result has no {}", name))?;
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Reads a successful result's fields as one `T`, for spells whose stops
+    /// send back a whole record.
+    pub fn fields<T: DeserializeOwned>(self) -> Result<T, Box<dyn std::error::Error>> {
+        let fields = self.succeeded()?.extra.into_iter().collect();
+
+        Ok(serde_json::from_value(Value::Object(fields))?)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpellFailed(pub String);
+
+impl fmt::Display for SpellFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "spell failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for SpellFailed {}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SuccessResult {
     pub success: bool
@@ -80,7 +124,7 @@ pub struct Nineum {
     pub nineum: Vec<String>
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all="camelCase")]
 pub struct Transfer {
     pub timestamp: String,
@@ -0,0 +1,179 @@
+pub mod structs;
+pub mod recovery;
+
+#[cfg(test)]
+mod tests;
+
+use reqwest::{Client, Response};
+use serde_json::json;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::option::Option;
+use fount_rs::Fount;
+pub use crate::recovery::{KeyHolder, Recovery};
+pub use crate::structs::{JoanUser, SuccessResult};
+pub use service_directory_rs::{Service, ServiceDirectory};
+
+pub struct Joan {
+    base_url: String,
+    client: Client,
+    pub sessionless: Sessionless,
+}
+
+impl Joan {
+    pub fn new(base_url: Option<String>, sessionless: Option<Sessionless>) -> Self {
+        Joan {
+            base_url: base_url.unwrap_or("https://dev.joan.allyabase.com/".to_string()),
+            client: Client::new(),
+            sessionless: sessionless.unwrap_or(Sessionless::new()),
+        }
+    }
+
+    /// Points the client at joan in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory, sessionless: Option<Sessionless>) -> Self {
+        Self::new(Some(directory.url(Service::Joan)), sessionless)
+    }
+
+    async fn put(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .put(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn delete(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .delete(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    fn get_timestamp() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis()
+            .to_string()
+    }
+
+    pub async fn create_user(&self, hash: &str) -> Result<JoanUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let pub_key = self.sessionless.public_key().to_hex();
+        let signature = self.sessionless.sign(format!("{}{}{}", timestamp, hash, pub_key)).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "hash": hash,
+            "pubKey": pub_key,
+            "signature": signature
+        });
+
+        let url = format!("{}user/create", self.base_url);
+        let res = self.put(&url, payload).await?;
+        let user: JoanUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    pub async fn update_hash(&self, uuid: &str, hash: &str, new_hash: &str) -> Result<JoanUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}{}", timestamp, uuid, hash, new_hash);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "uuid": uuid,
+            "hash": hash,
+            "newHash": new_hash,
+            "signature": signature
+        });
+
+        let url = format!("{}user/update-hash", self.base_url);
+        let res = self.put(&url, payload).await?;
+        let user: JoanUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    /// Points the account behind `hash` at a freshly generated key. The old
+    /// key is never needed, so this works after it is lost; the returned
+    /// `Recovery` holds the new key for installing into other clients.
+    pub async fn recover(&self, hash: &str) -> Result<Recovery, Box<dyn std::error::Error>> {
+        let recovered = Sessionless::new();
+        let timestamp = Self::get_timestamp();
+        let pub_key = recovered.public_key().to_hex();
+        let signature = recovered.sign(format!("{}{}{}", timestamp, hash, pub_key)).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "hash": hash,
+            "pubKey": pub_key,
+            "signature": signature
+        });
+
+        let url = format!("{}user/recover", self.base_url);
+        let res = self.put(&url, payload).await?;
+        if !res.status().is_success() {
+            return Err(format!("joan refused to recover the account: {}", res.status()).into());
+        }
+        let user: JoanUser = res.json().await?;
+
+        Ok(Recovery::new(user, *recovered.private_key()))
+    }
+
+    pub async fn delete_user(&self, uuid: &str, hash: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}", timestamp, uuid, hash);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "uuid": uuid,
+            "hash": hash,
+            "signature": signature
+        });
+
+        let url = format!("{}user/delete", self.base_url);
+        let res = self.delete(&url, payload).await?;
+        let success: SuccessResult = res.json().await?;
+
+        Ok(success)
+    }
+
+    /// Creates a joan user for this client's key with the `joanUserCreate`
+    /// spell. The fount user at `caster_uuid` pays for it in MP; `fount` needs
+    /// spellbooks that price joan's spells.
+    pub async fn magic_create_user(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, hash: &str) -> Result<JoanUser, Box<dyn std::error::Error>> {
+        let components = json!({
+            "pubKey": self.sessionless.public_key().to_hex(),
+            "hash": hash
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "joanUserCreate", ordinal, components).await?;
+
+        result.field("user")
+    }
+
+    pub async fn magic_update_hash(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str, hash: &str, new_hash: &str) -> Result<JoanUser, Box<dyn std::error::Error>> {
+        let components = json!({
+            "uuid": uuid,
+            "hash": hash,
+            "newHash": new_hash
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "joanUserUpdateHash", ordinal, components).await?;
+
+        result.field("user")
+    }
+
+    pub async fn magic_delete_user(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str, hash: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let components = json!({
+            "uuid": uuid,
+            "hash": hash
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "joanUserDelete", ordinal, components).await?;
+
+        Ok(SuccessResult { success: result.success })
+    }
+}
@@ -0,0 +1,61 @@
+use sessionless::hex::IntoHex;
+use sessionless::{PrivateKey, Sessionless};
+use bdo_rs::BDO;
+use fount_rs::Fount;
+use crate::structs::JoanUser;
+use crate::Joan;
+
+/// A client that signs with a sessionless key that can be swapped out.
+pub trait KeyHolder {
+    fn install_key(&mut self, sessionless: Sessionless);
+}
+
+impl KeyHolder for Sessionless {
+    fn install_key(&mut self, sessionless: Sessionless) {
+        *self = sessionless;
+    }
+}
+
+impl KeyHolder for Joan {
+    fn install_key(&mut self, sessionless: Sessionless) {
+        self.sessionless = sessionless;
+    }
+}
+
+impl KeyHolder for BDO {
+    fn install_key(&mut self, sessionless: Sessionless) {
+        self.sessionless = sessionless;
+    }
+}
+
+impl KeyHolder for Fount {
+    fn install_key(&mut self, sessionless: Sessionless) {
+        self.sessionless = sessionless;
+    }
+}
+
+/// The key joan now holds for a recovered account.
+pub struct Recovery {
+    pub user: JoanUser,
+    private_key: PrivateKey
+}
+
+impl Recovery {
+    pub(crate) fn new(user: JoanUser, private_key: PrivateKey) -> Self {
+        Recovery { user, private_key }
+    }
+
+    pub fn pub_key(&self) -> String {
+        self.sessionless().public_key().to_hex()
+    }
+
+    pub fn sessionless(&self) -> Sessionless {
+        Sessionless::from_private_key(self.private_key)
+    }
+
+    /// Has `client` sign with the recovered key from now on. Call it once for
+    /// every client that should act as the recovered user.
+    pub fn install(&self, client: &mut impl KeyHolder) {
+        client.install_key(self.sessionless());
+    }
+}
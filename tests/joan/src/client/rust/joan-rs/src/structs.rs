@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use serde_json::Value;
+
+/// A joan account: a key that can be recovered by anyone who knows `hash`,
+/// usually a digest of the user's email and password.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct JoanUser {
+    pub uuid: String,
+    pub pub_key: Option<String>,
+    pub hash: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuccessResult {
+    pub success: bool
+}
@@ -0,0 +1,163 @@
+use crate::{Joan, JoanUser, KeyHolder, Recovery, SuccessResult};
+use bdo_rs::BDO;
+use fount_rs::Fount;
+use fount_rs::spellbook::SpellbookRegistry;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::collections::HashMap;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::direct_ports("localhost")).expect("service directory")
+}
+
+// config/service-spellbook.json prices the spells these tests cast.
+fn spellbooks() -> SpellbookRegistry {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../../../config/service-spellbook.json");
+    SpellbookRegistry::from_file(path).expect("service spellbook")
+}
+
+#[actix_rt::test]
+async fn test_joan() {
+
+    let mut saved_user: Option<JoanUser>;
+    let joan = Joan::with_directory(&directory(), None);
+    let hash = "firstHash";
+    let new_hash = "secondHash";
+
+    async fn create_user(joan: &Joan, hash: &str) -> Option<JoanUser> {
+        let result = joan.create_user(hash).await;
+
+        match result {
+            Ok(user) => {
+                assert_eq!(
+                    user.uuid.len(),
+                    36
+                );
+                Some(user)
+            },
+            Err(error) => {
+                eprintln!("Error occurred create_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn update_hash(joan: &Joan, saved_user: &JoanUser, hash: &str, new_hash: &str) -> Option<JoanUser> {
+        let result = joan.update_hash(&saved_user.uuid, hash, new_hash).await;
+
+        match result {
+            Ok(user) => {
+                assert_eq!(user.uuid, saved_user.uuid);
+                Some(user)
+            },
+            Err(error) => {
+                eprintln!("Error occurred update_hash: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn recover(joan: &Joan, saved_user: &JoanUser, hash: &str) -> Option<Recovery> {
+        let result = joan.recover(hash).await;
+
+        match result {
+            Ok(recovery) => {
+                assert_eq!(recovery.user.uuid, saved_user.uuid);
+                assert_ne!(recovery.pub_key(), joan.sessionless.public_key().to_hex());
+                Some(recovery)
+            },
+            Err(error) => {
+                eprintln!("Error occurred recover: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn delete_user(joan: &Joan, saved_user: &JoanUser, hash: &str) -> Option<SuccessResult> {
+        let result = joan.delete_user(&saved_user.uuid, hash).await;
+
+        match result {
+            Ok(success) => {
+                assert!(success.success);
+                Some(success)
+            },
+            Err(error) => {
+                eprintln!("Error occurred delete_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    saved_user = Some(create_user(&joan, hash).await.expect("user"));
+
+    if let Some(ref user) = saved_user {
+        saved_user = Some(update_hash(&joan, user, hash, new_hash).await.expect("update hash"));
+    } else {
+        panic!("Failed to get user");
+    }
+
+    let mut joan = joan;
+    if let Some(ref user) = saved_user {
+        let recovery = recover(&joan, user, new_hash).await.expect("recover");
+        recovery.install(&mut joan);
+        delete_user(&joan, user, new_hash).await.expect("delete");
+    } else {
+        panic!("Failed to get user");
+    }
+}
+
+#[actix_rt::test]
+async fn test_joan_magic() {
+    let fount = Fount::with_directory(&directory(), None).with_spellbooks(spellbooks());
+    let joan = Joan::with_directory(&directory(), None);
+    let caster = fount.create_user().await.expect("fount user");
+
+    let user = joan.magic_create_user(&fount, &caster.uuid, 0, "magicHash").await.expect("joanUserCreate");
+    assert_eq!(user.uuid.len(), 36);
+
+    let user = joan.magic_update_hash(&fount, &caster.uuid, 1, &user.uuid, "magicHash", "newMagicHash").await.expect("joanUserUpdateHash");
+    assert_eq!(user.hash.as_deref(), Some("newMagicHash"));
+
+    let deleted = joan.magic_delete_user(&fount, &caster.uuid, 2, &user.uuid, "newMagicHash").await.expect("joanUserDelete");
+    assert!(deleted.success);
+}
+
+#[test]
+fn test_install_recovered_key() {
+    let lost = Sessionless::new();
+    let found = Sessionless::new();
+    let user = JoanUser {
+        uuid: "4c7b1d3e-0000-4000-8000-000000000001".to_string(),
+        pub_key: Some(found.public_key().to_hex()),
+        hash: None,
+        extra: HashMap::new()
+    };
+    let recovery = Recovery::new(user, *found.private_key());
+    let recovered_key = found.public_key().to_hex();
+    assert_eq!(recovery.pub_key(), recovered_key);
+
+    let mut joan = Joan::new(None, Some(Sessionless::from_private_key(*lost.private_key())));
+    let mut bdo = BDO::new(None, Some(Sessionless::from_private_key(*lost.private_key())));
+    let mut fount = Fount::new(None, Some(Sessionless::from_private_key(*lost.private_key())));
+    let mut sessionless = Sessionless::from_private_key(*lost.private_key());
+
+    recovery.install(&mut joan);
+    recovery.install(&mut bdo);
+    recovery.install(&mut fount);
+    recovery.install(&mut sessionless);
+
+    assert_eq!(joan.sessionless.public_key().to_hex(), recovered_key);
+    assert_eq!(bdo.sessionless.public_key().to_hex(), recovered_key);
+    assert_eq!(fount.sessionless.public_key().to_hex(), recovered_key);
+    assert_eq!(sessionless.public_key().to_hex(), recovered_key);
+
+    let mut installed = Sessionless::new();
+    installed.install_key(recovery.sessionless());
+    assert_eq!(installed.public_key().to_hex(), recovered_key);
+}
@@ -1,15 +1,21 @@
 use crate::{JuliaUser, Julia, Prompt};
 use sessionless::hex::IntoHex;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::path_based("http://nginx:80")).expect("service directory")
+}
 
 #[actix_rt::test]
 async fn test_julia() {
 
     let mut saved_user: Option<JuliaUser> = Some(JuliaUser::new("foo".to_string(), "bar".to_string()));
     let mut saved_user2: Option<JuliaUser> = Some(JuliaUser::new("foo".to_string(), "bar".to_string()));
-    let julia = Julia::new(Some("http://nginx:80/julia/".to_string()));
-    let julia2 = Julia::new(Some("http://nginx:80/julia/".to_string()));
+    let julia = Julia::with_directory(&directory());
+    let julia2 = Julia::with_directory(&directory());
 
-    async fn create_user(julia: &Julia, saved_user: &JuliaUser) -> Option<JuliaUser> {
+    async fn create_user(julia: &Julia, _saved_user: &JuliaUser) -> Option<JuliaUser> {
     println!("creating user");
         let public_key = julia.sessionless.public_key().to_hex();
 	let handle = "handle1".to_string();
@@ -34,7 +40,7 @@ async fn test_julia() {
 	}
     }
 
-    async fn create_user2(julia: &Julia, saved_user2: &JuliaUser) -> Option<JuliaUser> {
+    async fn create_user2(julia: &Julia, _saved_user2: &JuliaUser) -> Option<JuliaUser> {
     println!("creating user2");
 	let handle = "handle2".to_string();
 	let julia_user = JuliaUser::new(julia.sessionless.public_key().to_hex(), handle);
@@ -99,6 +105,7 @@ async fn test_julia() {
     async fn sign_prompt(julia: &Julia, julia2: &Julia, saved_user: &JuliaUser, saved_user2: &JuliaUser) -> Option<JuliaUser> {
         let pending_prompts: Vec<Prompt> = saved_user.pending_prompts.values().cloned().collect();
 	let result = julia2.sign_prompt(&saved_user2.uuid, &pending_prompts[0]).await; 
+        assert!(result.expect("signed prompt").success);
         let updated_user = julia.get_user(&saved_user.uuid).await;
      
 	match updated_user {
@@ -144,10 +151,7 @@ async fn test_julia() {
      
 	match result {
 	    Ok(success) => {
-		assert_eq!(
-		    success.success,
-		    true
-		);
+		assert!(success.success);
 	    }
 	    Err(error) => {
 		eprintln!("Error occurred post_message: {}", error);
@@ -181,10 +185,7 @@ async fn test_julia() {
 
 	match result {
 	    Ok(success) => {
-	       assert_eq!(
-		    success.success,
-		    true
-		); 
+	       assert!(success.success); 
 	    }
 	    Err(error) => {
 		eprintln!("Error occurred delete_user: {}", error);
@@ -218,7 +219,7 @@ async fn test_julia() {
     }
 
     if let (Some(ref user), Some(ref user2)) = (saved_user, saved_user2) {
-        Some(sign_prompt(&julia, &julia2, user, user2).await);
+        sign_prompt(&julia, &julia2, user, user2).await;
         saved_user = Some(get_user(&julia, user).await.expect("get user after signing prompt"));
         saved_user2 = Some(get_user(&julia2, user2).await.expect("get user2"));
     } else { 
@@ -241,7 +242,7 @@ async fn test_julia() {
 	} 
 
         if let Some(ref user) = saved_user {
-            delete_user(&julia, &user).await;
+            delete_user(&julia, user).await;
         } else {
 	    panic!("Failed to delete user");
 	} 
@@ -251,3 +252,27 @@ async fn test_julia() {
     }
 
 }
+
+#[test]
+fn test_pending_prompts() {
+    let user: JuliaUser = serde_json::from_value(serde_json::json!({
+        "uuid": "2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a",
+        "pubKey": "031c4d981632f6d2d1171c0a8af6242b521954ae64d10528fd74fa4a9aeb9419ea",
+        "handle": "handle1",
+        "keys": {
+            "interactingKeys": { "6a1e5e0c-1d3b-4c8e-9a4f-2f1b7c3d8e90": "026fa1a11b1810b44e1dacaadcd2a5d080e43654a5ac96af04e9d2af2358d38ccd" },
+            "coordinatingKeys": {}
+        },
+        "pendingPrompts": {
+            "ab12": { "timestamp": "1700000000000", "newUUID": "6a1e5e0c-1d3b-4c8e-9a4f-2f1b7c3d8e90" }
+        }
+    })).unwrap();
+    let user = user.fill_prompts();
+
+    assert_eq!(user.keys["interactingKeys"].len(), 1);
+    assert_eq!(user.keys["coordinatingKeys"].len(), 0);
+    assert_eq!(user.keys.get("coordinatingKeys").map(|keys| keys.len()), Some(0));
+    assert_eq!(user.keys.get("keys"), None);
+    assert_eq!(user.pending_prompts["ab12"].prompt, "ab12");
+    assert_eq!(user.pending_prompts["ab12"].new_signature, None);
+}
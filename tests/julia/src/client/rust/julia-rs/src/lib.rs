@@ -0,0 +1,235 @@
+pub mod structs;
+
+#[cfg(test)]
+mod tests;
+
+use reqwest::{Client, Response};
+use serde_json::json;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::option::Option;
+pub use crate::structs::{JuliaUser, KeySet, Message, Messages, Prompt, SuccessResult};
+pub use service_directory_rs::{Service, ServiceDirectory};
+
+pub struct Julia {
+    base_url: String,
+    client: Client,
+    pub sessionless: Sessionless,
+}
+
+impl Julia {
+    pub fn new(base_url: Option<String>) -> Self {
+        Self::from_sessionless(base_url, Sessionless::new())
+    }
+
+    /// Points the client at julia in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory) -> Self {
+        Self::new(Some(directory.url(Service::Julia)))
+    }
+
+    pub fn from_sessionless(base_url: Option<String>, sessionless: Sessionless) -> Self {
+        Julia {
+            base_url: base_url.unwrap_or("https://dev.julia.allyabase.com/".to_string()),
+            client: Client::new(),
+            sessionless,
+        }
+    }
+
+    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.client.get(url).send().await
+    }
+
+    async fn post(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .post(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn put(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .put(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn delete(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .delete(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    fn get_timestamp() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis()
+            .to_string()
+    }
+
+    pub async fn create_user(&self, julia_user: JuliaUser) -> Result<JuliaUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let pub_key = self.sessionless.public_key().to_hex();
+        let signature = self.sessionless.sign(format!("{}{}", timestamp, pub_key)).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "pubKey": pub_key,
+            "user": julia_user,
+            "signature": signature
+        });
+
+        let url = format!("{}user/create", self.base_url);
+        let res = self.put(&url, payload).await?;
+        let user: JuliaUser = res.json().await?;
+
+        Ok(user.fill_prompts())
+    }
+
+    pub async fn get_user(&self, uuid: &str) -> Result<JuliaUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let url = format!("{}user/{}?timestamp={}&signature={}", self.base_url, uuid, timestamp, signature);
+        let res = self.get(&url).await?;
+        let user: JuliaUser = res.json().await?;
+
+        Ok(user.fill_prompts())
+    }
+
+    /// Asks julia for a new association prompt, returned in `pending_prompts`.
+    pub async fn get_prompt(&self, uuid: &str) -> Result<JuliaUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let url = format!("{}user/{}/associate/prompt?timestamp={}&signature={}", self.base_url, uuid, timestamp, signature);
+        let res = self.get(&url).await?;
+        let user: JuliaUser = res.json().await?;
+
+        Ok(user.fill_prompts())
+    }
+
+    /// Signs another user's prompt as the user at `uuid`, the second half of the handshake.
+    pub async fn sign_prompt(&self, uuid: &str, prompt: &Prompt) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let pub_key = self.sessionless.public_key().to_hex();
+        let message = format!("{}{}{}{}", timestamp, uuid, pub_key, prompt.prompt);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "uuid": uuid,
+            "pubKey": pub_key,
+            "prompt": prompt.prompt,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}/associate/signedPrompt", self.base_url, uuid);
+        let res = self.post(&url, payload).await?;
+        let success: SuccessResult = res.json().await?;
+
+        Ok(success)
+    }
+
+    /// Confirms a prompt the other user has signed, adding their key to
+    /// `keys.interacting_keys`.
+    pub async fn associate(&self, uuid: &str, prompt: &Prompt) -> Result<JuliaUser, Box<dyn std::error::Error>> {
+        let (Some(new_timestamp), Some(new_uuid), Some(new_pub_key), Some(new_signature)) =
+            (&prompt.new_timestamp, &prompt.new_uuid, &prompt.new_pub_key, &prompt.new_signature) else {
+            return Err(format!("prompt {} has not been signed yet", prompt.prompt).into());
+        };
+
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}{}", new_timestamp, new_uuid, new_pub_key, prompt.prompt);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "newTimestamp": new_timestamp,
+            "newUUID": new_uuid,
+            "newPubKey": new_pub_key,
+            "prompt": prompt.prompt,
+            "newSignature": new_signature,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}/associate", self.base_url, uuid);
+        let res = self.post(&url, payload).await?;
+        let user: JuliaUser = res.json().await?;
+
+        Ok(user.fill_prompts())
+    }
+
+    pub async fn delete_key(&self, uuid: &str, associated_uuid: &str) -> Result<JuliaUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}", timestamp, associated_uuid, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "signature": signature
+        });
+
+        let url = format!("{}associated/{}/user/{}", self.base_url, associated_uuid, uuid);
+        let res = self.delete(&url, payload).await?;
+        let user: JuliaUser = res.json().await?;
+
+        Ok(user.fill_prompts())
+    }
+
+    pub async fn post_message(&self, uuid: &str, receiver_uuid: &str, contents: String) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}{}", timestamp, uuid, receiver_uuid, contents);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "senderUUID": uuid,
+            "receiverUUID": receiver_uuid,
+            "message": contents,
+            "signature": signature
+        });
+
+        let url = format!("{}message", self.base_url);
+        let res = self.post(&url, payload).await?;
+        let success: SuccessResult = res.json().await?;
+
+        Ok(success)
+    }
+
+    pub async fn get_messages(&self, uuid: &str) -> Result<Messages, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let url = format!("{}messages/user/{}?timestamp={}&signature={}", self.base_url, uuid, timestamp, signature);
+        let res = self.get(&url).await?;
+        let messages: Messages = res.json().await?;
+
+        Ok(messages)
+    }
+
+    pub async fn delete_user(&self, uuid: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}", self.base_url, uuid);
+        let res = self.delete(&url, payload).await?;
+        let success: SuccessResult = res.json().await?;
+
+        Ok(success)
+    }
+}
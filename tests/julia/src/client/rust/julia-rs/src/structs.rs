@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Index;
+use serde_json::Value;
+
+/// The keys a julia user trusts, each keyed by the other user's uuid.
+/// Interacting keys may message the user; coordinating keys act on its behalf.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct KeySet {
+    #[serde(default)]
+    pub interacting_keys: HashMap<String, String>,
+    #[serde(default)]
+    pub coordinating_keys: HashMap<String, String>
+}
+
+impl KeySet {
+    /// Looks a key set up by the name julia gives it, `interactingKeys` or
+    /// `coordinatingKeys`.
+    pub fn get(&self, key_type: &str) -> Option<&HashMap<String, String>> {
+        match key_type {
+            "interactingKeys" => Some(&self.interacting_keys),
+            "coordinatingKeys" => Some(&self.coordinating_keys),
+            _ => None
+        }
+    }
+}
+
+/// `keys["interactingKeys"]`, panicking on an unknown name the way a
+/// `HashMap` does on a missing key.
+impl Index<&str> for KeySet {
+    type Output = HashMap<String, String>;
+
+    fn index(&self, key_type: &str) -> &Self::Output {
+        self.get(key_type).unwrap_or_else(|| panic!("no key set named {}", key_type))
+    }
+}
+
+/// A pending association. The requesting user fetches `prompt`, the other
+/// user signs it, and julia fills in the `new_*` fields for the requester to
+/// confirm with `associate`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct Prompt {
+    #[serde(default)]
+    pub prompt: String,
+    pub timestamp: Option<String>,
+    pub new_timestamp: Option<String>,
+    #[serde(rename = "newUUID")]
+    pub new_uuid: Option<String>,
+    pub new_pub_key: Option<String>,
+    pub new_signature: Option<String>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct JuliaUser {
+    #[serde(default)]
+    pub uuid: String,
+    #[serde(default)]
+    pub pub_key: String,
+    #[serde(default)]
+    pub handle: String,
+    #[serde(default)]
+    pub keys: KeySet,
+    #[serde(default)]
+    pub pending_prompts: HashMap<String, Prompt>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+impl JuliaUser {
+    pub fn new(pub_key: String, handle: String) -> Self {
+        JuliaUser {
+            uuid: String::new(),
+            pub_key,
+            handle,
+            keys: KeySet::default(),
+            pending_prompts: HashMap::new(),
+            extra: HashMap::new()
+        }
+    }
+
+    // Julia keys pending prompts by the prompt itself.
+    pub(crate) fn fill_prompts(mut self) -> Self {
+        for (prompt, pending) in self.pending_prompts.iter_mut() {
+            if pending.prompt.is_empty() {
+                pending.prompt = prompt.clone();
+            }
+        }
+        self
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct Message {
+    pub timestamp: Option<String>,
+    #[serde(rename = "senderUUID")]
+    pub sender_uuid: String,
+    #[serde(rename = "receiverUUID")]
+    pub receiver_uuid: String,
+    pub message: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Messages {
+    pub messages: Vec<Message>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuccessResult {
+    pub success: bool
+}
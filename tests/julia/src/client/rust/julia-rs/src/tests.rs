@@ -0,0 +1,278 @@
+use crate::{JuliaUser, Julia, Prompt};
+use sessionless::hex::IntoHex;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::direct_ports("localhost")).expect("service directory")
+}
+
+#[actix_rt::test]
+async fn test_julia() {
+
+    let mut saved_user: Option<JuliaUser> = Some(JuliaUser::new("foo".to_string(), "bar".to_string()));
+    let mut saved_user2: Option<JuliaUser> = Some(JuliaUser::new("foo".to_string(), "bar".to_string()));
+    let julia = Julia::with_directory(&directory());
+    let julia2 = Julia::with_directory(&directory());
+
+    async fn create_user(julia: &Julia, _saved_user: &JuliaUser) -> Option<JuliaUser> {
+    println!("creating user");
+        let public_key = julia.sessionless.public_key().to_hex();
+	let handle = "handle1".to_string();
+	let julia_user = JuliaUser::new(public_key, handle);
+	let result = julia.create_user(julia_user).await;
+    println!("got to here");
+
+	match result {
+	    Ok(user) => {
+		println!("Successfully got JuliaUser: {}", user.uuid);
+		assert_eq!(
+		    user.uuid.len(),
+		    36
+		);
+                Some(user)
+	    },
+	    Err(error) => {
+		eprintln!("Error occurred create_user: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	}
+    }
+
+    async fn create_user2(julia: &Julia, _saved_user2: &JuliaUser) -> Option<JuliaUser> {
+    println!("creating user2");
+	let handle = "handle2".to_string();
+	let julia_user = JuliaUser::new(julia.sessionless.public_key().to_hex(), handle);
+	let result = julia.create_user(julia_user).await;
+    println!("got to here");
+
+	match result {
+	    Ok(user) => {
+		println!("Successfully got JuliaUser: {}", user.uuid);
+		assert_eq!(
+		    user.uuid.len(),
+		    36
+		);
+                Some(user)
+	    },
+	    Err(error) => {
+		eprintln!("Error occurred create_user2: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	}
+    }
+
+    async fn get_user(julia: &Julia, saved_user: &JuliaUser) -> Option<JuliaUser> {
+	let result = julia.get_user(&saved_user.uuid).await; 
+     
+	match result {
+	    Ok(user) => {
+		assert_eq!(
+		    user.uuid.len(),
+		    36
+		);
+                Some(user)
+	    }
+	    Err(error) => {
+		eprintln!("Error occurred get_user: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	} 
+    }
+
+    async fn get_prompt(julia: &Julia, saved_user: &JuliaUser) -> Option<JuliaUser> {
+	let result = julia.get_prompt(&saved_user.uuid).await;         
+     
+	match result {
+	    Ok(user) => {
+		assert_eq!(
+		    user.pending_prompts.len(),
+		    1
+		);
+                Some(user)
+	    }
+	    Err(error) => {
+		eprintln!("Error occurred get_prompt: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	} 
+    }
+
+    async fn sign_prompt(julia: &Julia, julia2: &Julia, saved_user: &JuliaUser, saved_user2: &JuliaUser) -> Option<JuliaUser> {
+        let pending_prompts: Vec<Prompt> = saved_user.pending_prompts.values().cloned().collect();
+	let result = julia2.sign_prompt(&saved_user2.uuid, &pending_prompts[0]).await; 
+        assert!(result.expect("signed prompt").success);
+        let updated_user = julia.get_user(&saved_user.uuid).await;
+     
+	match updated_user {
+	    Ok(user) => {
+                let updated_prompts: Vec<Prompt> = user.pending_prompts.values().cloned().collect();
+                let formatted = format!("{:?}", updated_prompts[0].new_uuid);
+		assert_eq!(
+		    formatted.len(),
+		    44
+		);
+                Some(user)
+	    }
+	    Err(error) => {
+		eprintln!("Error occurred sign_prompt: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	} 
+    }
+
+    async fn associate(julia: &Julia, saved_user: &JuliaUser) -> Option<JuliaUser> {
+        let pending_prompts: Vec<Prompt> = saved_user.pending_prompts.values().cloned().collect();
+	let result = julia.associate(&saved_user.uuid, &pending_prompts[0]).await;
+     
+	match result {
+	    Ok(user) => {
+		assert_eq!(
+		    user.keys["interactingKeys"].len(),
+		    2
+		);   
+                Some(user)
+	    }
+	    Err(error) => {
+		eprintln!("Error occurred associate: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	}
+    }
+
+    async fn post_message(julia: &Julia, saved_user: &JuliaUser, saved_user2: &JuliaUser) {
+	let result = julia.post_message(&saved_user.uuid, &saved_user2.uuid, "Here is a test message".to_string()).await;
+     
+	match result {
+	    Ok(success) => {
+		assert!(success.success);
+	    }
+	    Err(error) => {
+		eprintln!("Error occurred post_message: {}", error);
+		println!("Error details: {:?}", error);
+	    }
+	}
+    }
+
+    async fn delete_key(julia: &Julia, saved_user: &JuliaUser, saved_user2: &JuliaUser) -> Option<JuliaUser> {
+	let result = julia.delete_key(&saved_user.uuid, &saved_user2.uuid).await;
+     
+	match result {
+	    Ok(user) => {
+		assert_eq!(
+		    user.keys["interactingKeys"].len(),
+		    1
+		);
+                Some(user)
+	    }
+	    Err(error) => {
+		eprintln!("Error occurred delete_key: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	}
+    }
+
+
+    async fn delete_user(julia: &Julia, saved_user: &JuliaUser) {
+	let result = julia.delete_user(&saved_user.uuid).await;
+
+	match result {
+	    Ok(success) => {
+	       assert!(success.success); 
+	    }
+	    Err(error) => {
+		eprintln!("Error occurred delete_user: {}", error);
+		println!("Error details: {:?}", error);
+	    }
+	}
+    }
+
+    if let Some(ref user) = saved_user {
+        saved_user = Some(create_user(&julia, user).await.expect("user"));
+    } else {    
+        panic!("Failed to create user to begin with"); 
+    }           
+            
+    if let Some(ref user) = saved_user2 {
+        saved_user2 = Some(create_user2(&julia2, user).await.expect("user2"));
+    } else {
+        panic!("Failed to create user2");
+    }
+
+    if let Some(ref user) = saved_user2 {
+        saved_user2 = Some(get_user(&julia2, user).await.expect("get user2 1"));
+    } else {
+        panic!("Failed to get user");
+    }
+
+    if let Some(ref user) = saved_user {
+	saved_user = Some(get_prompt(&julia, user).await.expect("get prompt"));
+    } else {
+	panic!("Failed to get prompt");
+    }
+
+    if let (Some(ref user), Some(ref user2)) = (saved_user, saved_user2) {
+        sign_prompt(&julia, &julia2, user, user2).await;
+        saved_user = Some(get_user(&julia, user).await.expect("get user after signing prompt"));
+        saved_user2 = Some(get_user(&julia2, user2).await.expect("get user2"));
+    } else { 
+        panic!("Failed to sign prompt");
+    } 
+
+    if let (Some(ref user), Some(ref user2)) = (saved_user, saved_user2) {
+        saved_user = Some(associate(&julia, user).await.expect("associate"));
+
+        if let Some(ref user) = saved_user {
+            post_message(&julia, user, user2).await;
+        } else {
+	    panic!("Failed to post message");
+	} 
+        
+        if let Some(ref user) = saved_user {
+            saved_user = Some(delete_key(&julia, user, user2).await.expect("delete_key"));
+        } else {
+	    panic!("Failed to delete key");
+	} 
+
+        if let Some(ref user) = saved_user {
+            delete_user(&julia, user).await;
+        } else {
+	    panic!("Failed to delete user");
+	} 
+
+    } else {
+        panic!("Failed on associate");
+    }
+
+}
+
+#[test]
+fn test_pending_prompts() {
+    let user: JuliaUser = serde_json::from_value(serde_json::json!({
+        "uuid": "2b4f1c3e-6f6e-4d8a-9c1b-3e2a1d0c9b8a",
+        "pubKey": "031c4d981632f6d2d1171c0a8af6242b521954ae64d10528fd74fa4a9aeb9419ea",
+        "handle": "handle1",
+        "keys": {
+            "interactingKeys": { "6a1e5e0c-1d3b-4c8e-9a4f-2f1b7c3d8e90": "026fa1a11b1810b44e1dacaadcd2a5d080e43654a5ac96af04e9d2af2358d38ccd" },
+            "coordinatingKeys": {}
+        },
+        "pendingPrompts": {
+            "ab12": { "timestamp": "1700000000000", "newUUID": "6a1e5e0c-1d3b-4c8e-9a4f-2f1b7c3d8e90" }
+        }
+    })).unwrap();
+    let user = user.fill_prompts();
+
+    assert_eq!(user.keys["interactingKeys"].len(), 1);
+    assert_eq!(user.keys["coordinatingKeys"].len(), 0);
+    assert_eq!(user.keys.get("coordinatingKeys").map(|keys| keys.len()), Some(0));
+    assert_eq!(user.keys.get("keys"), None);
+    assert_eq!(user.pending_prompts["ab12"].prompt, "ab12");
+    assert_eq!(user.pending_prompts["ab12"].new_signature, None);
+}
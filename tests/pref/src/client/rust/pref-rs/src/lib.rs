@@ -0,0 +1,206 @@
+pub mod structs;
+
+#[cfg(test)]
+mod tests;
+
+use reqwest::{Client, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::option::Option;
+use fount_rs::Fount;
+pub use crate::structs::{merge, merge_values, to_preferences, PrefUser, PreferenceError, Preferences, SuccessResult, MAX_VALUE_LENGTH};
+pub use service_directory_rs::{Service, ServiceDirectory};
+
+/// Preferences are generic over the caller's schema: anything that
+/// serializes to a flat-ish JSON object can be stored, and reads come back
+/// as whatever `DeserializeOwned` type the caller asks for.
+pub struct Pref {
+    base_url: String,
+    client: Client,
+    pub sessionless: Sessionless,
+}
+
+impl Pref {
+    pub fn new(base_url: Option<String>, sessionless: Option<Sessionless>) -> Self {
+        Pref {
+            base_url: base_url.unwrap_or("https://dev.pref.allyabase.com/".to_string()),
+            client: Client::new(),
+            sessionless: sessionless.unwrap_or(Sessionless::new()),
+        }
+    }
+
+    /// Points the client at pref in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory, sessionless: Option<Sessionless>) -> Self {
+        Self::new(Some(directory.url(Service::Pref)), sessionless)
+    }
+
+    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.client.get(url).send().await
+    }
+
+    async fn put(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .put(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn delete(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .delete(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    fn get_timestamp() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis()
+            .to_string()
+    }
+
+    pub async fn create_user<T: Serialize>(&self, hash: &str, preferences: &T) -> Result<PrefUser, Box<dyn std::error::Error>> {
+        let preferences = to_preferences(preferences)?;
+        let timestamp = Self::get_timestamp();
+        let pub_key = self.sessionless.public_key().to_hex();
+        let signature = self.sessionless.sign(format!("{}{}{}", timestamp, pub_key, hash)).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "pubKey": pub_key,
+            "hash": hash,
+            "preferences": preferences,
+            "signature": signature
+        });
+
+        let url = format!("{}user/create", self.base_url);
+        let res = self.put(&url, payload).await?;
+        let user: PrefUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    async fn read<T: DeserializeOwned>(&self, uuid: &str, hash: &str, path: &str) -> Result<T, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let signature = self.sessionless.sign(format!("{}{}{}", timestamp, uuid, hash)).to_hex();
+
+        let url = format!("{}user/{}/{}?timestamp={}&hash={}&signature={}", self.base_url, uuid, path, timestamp, hash, signature);
+        let res = self.get(&url).await?;
+        let preferences: Preferences<T> = res.json().await?;
+
+        Ok(preferences.preferences)
+    }
+
+    async fn write<T: Serialize + DeserializeOwned>(&self, uuid: &str, hash: &str, path: &str, preferences: &T) -> Result<T, Box<dyn std::error::Error>> {
+        let preferences = to_preferences(preferences)?;
+        let timestamp = Self::get_timestamp();
+        let signature = self.sessionless.sign(format!("{}{}{}", timestamp, uuid, hash)).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "hash": hash,
+            "preferences": preferences,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}/{}", self.base_url, uuid, path);
+        let res = self.put(&url, payload).await?;
+        let saved: Preferences<T> = res.json().await?;
+
+        Ok(saved.preferences)
+    }
+
+    pub async fn get_preferences<T: DeserializeOwned>(&self, uuid: &str, hash: &str) -> Result<T, Box<dyn std::error::Error>> {
+        self.read(uuid, hash, "preferences").await
+    }
+
+    /// Replaces the user's preferences and returns what pref stored.
+    pub async fn save_preferences<T: Serialize + DeserializeOwned>(&self, uuid: &str, hash: &str, preferences: &T) -> Result<T, Box<dyn std::error::Error>> {
+        self.write(uuid, hash, "preferences", preferences).await
+    }
+
+    pub async fn get_global_preferences<T: DeserializeOwned>(&self, uuid: &str, hash: &str) -> Result<T, Box<dyn std::error::Error>> {
+        self.read(uuid, hash, "global/preferences").await
+    }
+
+    /// Replaces the preferences every app of this user falls back to.
+    pub async fn save_global_preferences<T: Serialize + DeserializeOwned>(&self, uuid: &str, hash: &str, preferences: &T) -> Result<T, Box<dyn std::error::Error>> {
+        self.write(uuid, hash, "global/preferences", preferences).await
+    }
+
+    /// The user's preferences layered over their global ones, see `merge`.
+    pub async fn layered_preferences<T: DeserializeOwned>(&self, uuid: &str, hash: &str) -> Result<T, Box<dyn std::error::Error>> {
+        let global: Value = self.get_global_preferences(uuid, hash).await?;
+        let user: Value = self.get_preferences(uuid, hash).await?;
+
+        Ok(merge(&global, &user)?)
+    }
+
+    pub async fn delete_user(&self, uuid: &str, hash: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}", timestamp, uuid, hash);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "uuid": uuid,
+            "hash": hash,
+            "signature": signature
+        });
+
+        let url = format!("{}user/delete", self.base_url);
+        let res = self.delete(&url, payload).await?;
+
+        Ok(SuccessResult { success: res.status().is_success() })
+    }
+
+    /// Creates a user with `prefUserCreate`. The result carries the new uuid.
+    pub async fn magic_create_user<T: Serialize + DeserializeOwned>(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, hash: &str, preferences: &T) -> Result<Preferences<T>, Box<dyn std::error::Error>> {
+        let components = json!({
+            "hash": hash,
+            "preferences": to_preferences(preferences)?
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "prefUserCreate", ordinal, components).await?;
+
+        result.fields()
+    }
+
+    pub async fn magic_save_preferences<T: Serialize + DeserializeOwned>(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str, hash: &str, preferences: &T) -> Result<T, Box<dyn std::error::Error>> {
+        let components = json!({
+            "uuid": uuid,
+            "hash": hash,
+            "preferences": to_preferences(preferences)?
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "prefUserPreferences", ordinal, components).await?;
+
+        Ok(result.fields::<Preferences<T>>()?.preferences)
+    }
+
+    pub async fn magic_save_global_preferences<T: Serialize + DeserializeOwned>(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str, hash: &str, preferences: &T) -> Result<T, Box<dyn std::error::Error>> {
+        let components = json!({
+            "uuid": uuid,
+            "hash": hash,
+            "preferences": to_preferences(preferences)?
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "prefUserGlobalPreferences", ordinal, components).await?;
+
+        Ok(result.fields::<Preferences<T>>()?.preferences)
+    }
+
+    pub async fn magic_delete_user(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str, hash: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let components = json!({
+            "uuid": uuid,
+            "hash": hash
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "prefUserDelete", ordinal, components).await?;
+
+        Ok(SuccessResult { success: result.success })
+    }
+}
@@ -0,0 +1,102 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use serde_json::{Map, Value};
+
+// Pref refuses string values longer than this.
+pub const MAX_VALUE_LENGTH: usize = 256;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct PrefUser {
+    pub uuid: String,
+    pub pub_key: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// What pref sends back for preference reads and writes, with the
+/// preferences read into the caller's schema.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Preferences<T> {
+    pub uuid: Option<String>,
+    pub preferences: T
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuccessResult {
+    pub success: bool
+}
+
+#[derive(Debug)]
+pub enum PreferenceError {
+    NotAnObject,
+    ValueTooLong { key: String, length: usize },
+    Schema(serde_json::Error)
+}
+
+impl fmt::Display for PreferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreferenceError::NotAnObject => write!(f, "preferences have to serialize to an object"),
+            PreferenceError::ValueTooLong { key, length } => write!(f, "{} is {} characters, pref allows {}", key, length, MAX_VALUE_LENGTH),
+            PreferenceError::Schema(error) => write!(f, "preferences do not fit the schema: {}", error)
+        }
+    }
+}
+
+impl std::error::Error for PreferenceError {}
+
+impl From<serde_json::Error> for PreferenceError {
+    fn from(error: serde_json::Error) -> Self {
+        PreferenceError::Schema(error)
+    }
+}
+
+/// Serializes a schema into the object pref stores, checking the limits pref
+/// would otherwise reject it for.
+pub fn to_preferences<T: Serialize>(preferences: &T) -> Result<Map<String, Value>, PreferenceError> {
+    let Value::Object(map) = serde_json::to_value(preferences)? else {
+        return Err(PreferenceError::NotAnObject);
+    };
+
+    for (key, value) in &map {
+        if let Value::String(text) = value {
+            let length = text.chars().count();
+            if length > MAX_VALUE_LENGTH {
+                return Err(PreferenceError::ValueTooLong { key: key.clone(), length });
+            }
+        }
+    }
+
+    Ok(map)
+}
+
+/// Layers `user` over `global`. Nested objects are merged key by key and
+/// nulls in `user` leave the global value in place, so a user schema of
+/// `Option`s only overrides what the user actually set.
+pub fn merge_values(global: &Value, user: &Value) -> Value {
+    match (global, user) {
+        (Value::Object(defaults), Value::Object(overrides)) => {
+            let mut merged = defaults.clone();
+            for (key, value) in overrides {
+                let layered = match defaults.get(key) {
+                    Some(default) => merge_values(default, value),
+                    None => value.clone()
+                };
+                merged.insert(key.clone(), layered);
+            }
+            Value::Object(merged)
+        },
+        (global, Value::Null) => global.clone(),
+        (_, user) => user.clone()
+    }
+}
+
+/// Merges global defaults and user overrides into the schema `T`.
+pub fn merge<T: DeserializeOwned>(global: &impl Serialize, user: &impl Serialize) -> Result<T, PreferenceError> {
+    let merged = merge_values(&serde_json::to_value(global)?, &serde_json::to_value(user)?);
+
+    Ok(serde_json::from_value(merged)?)
+}
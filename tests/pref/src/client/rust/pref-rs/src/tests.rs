@@ -0,0 +1,158 @@
+use crate::{merge, merge_values, to_preferences, Pref, PrefUser, PreferenceError, SuccessResult};
+use fount_rs::Fount;
+use fount_rs::spellbook::SpellbookRegistry;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::direct_ports("localhost")).expect("service directory")
+}
+
+// config/service-spellbook.json prices the spells these tests cast.
+fn spellbooks() -> SpellbookRegistry {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../../../config/service-spellbook.json");
+    SpellbookRegistry::from_file(path).expect("service spellbook")
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct AppConfig {
+    theme: String,
+    language: String,
+    notifications: bool
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct AppOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    theme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notifications: Option<bool>
+}
+
+#[actix_rt::test]
+async fn test_pref() {
+
+    let pref = Pref::with_directory(&directory(), None);
+    let hash = "firstHash";
+    let defaults = AppConfig { theme: "system".to_string(), language: "en".to_string(), notifications: true };
+
+    async fn create_user(pref: &Pref, hash: &str) -> Option<PrefUser> {
+        let overrides = AppOverrides { theme: Some("dark".to_string()), ..Default::default() };
+        let result = pref.create_user(hash, &overrides).await;
+
+        match result {
+            Ok(user) => {
+                assert_eq!(
+                    user.uuid.len(),
+                    36
+                );
+                Some(user)
+            },
+            Err(error) => {
+                eprintln!("Error occurred create_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn delete_user(pref: &Pref, saved_user: &PrefUser, hash: &str) -> Option<SuccessResult> {
+        let result = pref.delete_user(&saved_user.uuid, hash).await;
+
+        match result {
+            Ok(success) => {
+                assert!(success.success);
+                Some(success)
+            },
+            Err(error) => {
+                eprintln!("Error occurred delete_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    let saved_user = create_user(&pref, hash).await;
+
+    if let Some(ref user) = saved_user {
+        let overrides: AppOverrides = pref.get_preferences(&user.uuid, hash).await.expect("preferences");
+        assert_eq!(overrides.theme.as_deref(), Some("dark"));
+
+        let overrides = AppOverrides { language: Some("es".to_string()), ..overrides };
+        let saved = pref.save_preferences(&user.uuid, hash, &overrides).await.expect("save preferences");
+        assert_eq!(saved, overrides);
+
+        let global = pref.save_global_preferences(&user.uuid, hash, &defaults).await.expect("save global preferences");
+        assert_eq!(global, defaults);
+        let global: AppConfig = pref.get_global_preferences(&user.uuid, hash).await.expect("global preferences");
+        assert_eq!(global, defaults);
+
+        let config: AppConfig = pref.layered_preferences(&user.uuid, hash).await.expect("layered preferences");
+        assert_eq!(config, AppConfig { theme: "dark".to_string(), language: "es".to_string(), notifications: true });
+    } else {
+        panic!("Failed to get user");
+    }
+
+    if let Some(ref user) = saved_user {
+        delete_user(&pref, user, hash).await.expect("delete");
+    }
+}
+
+#[actix_rt::test]
+async fn test_pref_magic() {
+    let fount = Fount::with_directory(&directory(), None).with_spellbooks(spellbooks());
+    let pref = Pref::with_directory(&directory(), None);
+    let caster = fount.create_user().await.expect("fount user");
+    let hash = "magicHash";
+
+    let preferences = AppConfig { theme: "dark".to_string(), language: "en".to_string(), notifications: true };
+    let created = pref.magic_create_user(&fount, &caster.uuid, 0, hash, &preferences).await.expect("prefUserCreate");
+    let uuid = created.uuid.expect("uuid");
+    assert_eq!(uuid.len(), 36);
+    assert_eq!(created.preferences, preferences);
+
+    let updated = AppConfig { theme: "light".to_string(), language: "es".to_string(), ..preferences };
+    assert_eq!(pref.magic_save_preferences(&fount, &caster.uuid, 1, &uuid, hash, &updated).await.expect("prefUserPreferences"), updated);
+
+    let global = json!({ "globalTheme": "system", "globalLanguage": "en-US" });
+    assert_eq!(pref.magic_save_global_preferences(&fount, &caster.uuid, 2, &uuid, hash, &global).await.expect("prefUserGlobalPreferences"), global);
+
+    assert!(pref.magic_delete_user(&fount, &caster.uuid, 3, &uuid, hash).await.expect("prefUserDelete").success);
+}
+
+#[test]
+fn test_merge_preferences() {
+    let defaults = AppConfig { theme: "system".to_string(), language: "en".to_string(), notifications: true };
+    let overrides = AppOverrides { notifications: Some(false), ..Default::default() };
+
+    let config: AppConfig = merge(&defaults, &overrides).expect("merge");
+    assert_eq!(config, AppConfig { notifications: false, ..defaults.clone() });
+    let config: AppConfig = merge(&defaults, &AppOverrides::default()).expect("merge nothing");
+    assert_eq!(config, defaults);
+
+    let global = json!({ "editor": { "tabs": 4, "font": "mono" }, "beta": false });
+    let user = json!({ "editor": { "tabs": 2 }, "beta": null, "locale": "fr" });
+    assert_eq!(merge_values(&global, &user), json!({ "editor": { "tabs": 2, "font": "mono" }, "beta": false, "locale": "fr" }));
+
+    let missing: Result<AppConfig, _> = merge(&json!({ "theme": "dark" }), &overrides);
+    assert!(matches!(missing, Err(PreferenceError::Schema(_))));
+}
+
+#[test]
+fn test_preference_limits() {
+    assert!(to_preferences(&json!({ "theme": "dark", "count": 3 })).is_ok());
+    assert!(matches!(to_preferences(&vec!["dark"]), Err(PreferenceError::NotAnObject)));
+
+    let long = json!({ "longValue": "x".repeat(300) });
+    match to_preferences(&long) {
+        Err(PreferenceError::ValueTooLong { key, length }) => {
+            assert_eq!(key, "longValue");
+            assert_eq!(length, 300);
+        },
+        other => panic!("expected ValueTooLong, got {:?}", other)
+    }
+}
@@ -0,0 +1,199 @@
+pub mod structs;
+
+#[cfg(test)]
+mod tests;
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, Response};
+use serde_json::json;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::option::Option;
+use fount_rs::Fount;
+use fount_rs::structs::SpellResult;
+pub use crate::structs::{ProfResponse, Profile, ProfileError, ProfileImage, ProfileUpdate, SuccessResult};
+pub use service_directory_rs::{Service, ServiceDirectory};
+
+pub struct Prof {
+    base_url: String,
+    client: Client,
+    pub sessionless: Sessionless,
+}
+
+impl Prof {
+    pub fn new(base_url: Option<String>, sessionless: Option<Sessionless>) -> Self {
+        Prof {
+            base_url: base_url.unwrap_or("https://dev.prof.allyabase.com/".to_string()),
+            client: Client::new(),
+            sessionless: sessionless.unwrap_or(Sessionless::new()),
+        }
+    }
+
+    /// Points the client at prof in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory, sessionless: Option<Sessionless>) -> Self {
+        Self::new(Some(directory.url(Service::Prof)), sessionless)
+    }
+
+    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.client.get(url).send().await
+    }
+
+    async fn delete(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .delete(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    fn get_timestamp() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis()
+            .to_string()
+    }
+
+    fn profile_url(&self, uuid: &str) -> String {
+        format!("{}user/{}/profile", self.base_url, uuid)
+    }
+
+    /// Profiles go up as a multipart form: the signed fields, the profile as
+    /// JSON under `profileData`, and the image, if any, under `image`.
+    fn profile_form(&self, uuid: &str, profile_data: String, image: Option<&ProfileImage>) -> Result<Form, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let signature = self.sessionless.sign(format!("{}{}", timestamp, uuid)).to_hex();
+
+        let mut form = Form::new()
+            .text("timestamp", timestamp)
+            .text("pubKey", self.sessionless.public_key().to_hex())
+            .text("signature", signature)
+            .text("profileData", profile_data);
+
+        if let Some(image) = image {
+            let part = Part::bytes(image.bytes.clone())
+                .file_name(image.file_name(uuid))
+                .mime_str(image.content_type())?;
+            form = form.part("image", part);
+        }
+
+        Ok(form)
+    }
+
+    pub async fn create_profile(&self, profile: &Profile, image: Option<&ProfileImage>) -> Result<Profile, Box<dyn std::error::Error>> {
+        let form = self.profile_form(&profile.uuid, serde_json::to_string(profile)?, image)?;
+
+        let res = self.client
+            .post(self.profile_url(&profile.uuid))
+            .multipart(form)
+            .send()
+            .await?;
+        let response: ProfResponse = res.json().await?;
+
+        Ok(response.into_profile()?)
+    }
+
+    /// Changes only the fields set in `update`, replacing the image if one is given.
+    pub async fn update_profile(&self, uuid: &str, update: &ProfileUpdate, image: Option<&ProfileImage>) -> Result<Profile, Box<dyn std::error::Error>> {
+        let form = self.profile_form(uuid, serde_json::to_string(update)?, image)?;
+
+        let res = self.client
+            .put(self.profile_url(uuid))
+            .multipart(form)
+            .send()
+            .await?;
+        let response: ProfResponse = res.json().await?;
+
+        Ok(response.into_profile()?)
+    }
+
+    pub async fn get_profile(&self, uuid: &str) -> Result<Profile, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let signature = self.sessionless.sign(format!("{}{}", timestamp, uuid)).to_hex();
+
+        let url = format!("{}?timestamp={}&signature={}", self.profile_url(uuid), timestamp, signature);
+        let res = self.get(&url).await?;
+        let response: ProfResponse = res.json().await?;
+
+        Ok(response.into_profile()?)
+    }
+
+    pub async fn get_profile_image(&self, uuid: &str) -> Result<ProfileImage, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let signature = self.sessionless.sign(format!("{}{}", timestamp, uuid)).to_hex();
+
+        let url = format!("{}/image?timestamp={}&signature={}", self.profile_url(uuid), timestamp, signature);
+        let res = self.get(&url).await?.error_for_status()?;
+        let content_type = res
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let bytes = res.bytes().await?.to_vec();
+
+        Ok(ProfileImage::from_content_type(bytes, &content_type)?)
+    }
+
+    pub async fn delete_profile(&self, uuid: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let signature = self.sessionless.sign(format!("{}{}", timestamp, uuid)).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "pubKey": self.sessionless.public_key().to_hex(),
+            "signature": signature
+        });
+
+        let res = self.delete(&self.profile_url(uuid), payload).await?;
+
+        Ok(SuccessResult { success: res.status().is_success() })
+    }
+
+    fn spell_profile(result: SpellResult) -> Result<Profile, Box<dyn std::error::Error>> {
+        let result = result.succeeded().map_err(|failed| ProfileError::Rejected(failed.0))?;
+
+        result.field("profile")
+    }
+
+    // Images travel base64 encoded in the spell components.
+    fn with_image(mut components: serde_json::Value, image: Option<&ProfileImage>) -> serde_json::Value {
+        if let Some(image) = image {
+            let (data, extension) = image.components();
+            components["imageData"] = json!(data);
+            components["imageExtension"] = json!(extension);
+        }
+        components
+    }
+
+    pub async fn magic_create_profile(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, profile: &Profile, image: Option<&ProfileImage>) -> Result<Profile, Box<dyn std::error::Error>> {
+        let components = Self::with_image(json!({
+            "uuid": profile.uuid,
+            "profileData": profile
+        }), image);
+        let result = fount.cast_from_spellbook(caster_uuid, "profUserProfile", ordinal, components).await?;
+
+        Self::spell_profile(result)
+    }
+
+    pub async fn magic_update_profile(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str, update: &ProfileUpdate, image: Option<&ProfileImage>) -> Result<Profile, Box<dyn std::error::Error>> {
+        let components = Self::with_image(json!({
+            "uuid": uuid,
+            "profileData": update
+        }), image);
+        let result = fount.cast_from_spellbook(caster_uuid, "profUserProfileUpdate", ordinal, components).await?;
+
+        Self::spell_profile(result)
+    }
+
+    pub async fn magic_delete_profile(&self, fount: &Fount, caster_uuid: &str, ordinal: u32, uuid: &str) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let components = json!({
+            "uuid": uuid
+        });
+        let result = fount.cast_from_spellbook(caster_uuid, "profUserProfileDelete", ordinal, components).await?;
+
+        Ok(SuccessResult { success: result.success })
+    }
+}
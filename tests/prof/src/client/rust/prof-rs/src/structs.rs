@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::Value;
+
+// The image types prof will store, by extension.
+const IMAGE_TYPES: [(&str, &str); 5] = [
+    ("png", "image/png"), ("jpg", "image/jpeg"), ("jpeg", "image/jpeg"), ("gif", "image/gif"), ("webp", "image/webp")
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct Profile {
+    pub uuid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bio: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<Value>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+impl Profile {
+    pub fn new(uuid: &str, name: &str, email: &str) -> Self {
+        Profile {
+            uuid: uuid.to_string(),
+            name: Some(name.to_string()),
+            email: Some(email.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_bio(mut self, bio: &str) -> Self {
+        self.bio = Some(bio.to_string());
+        self
+    }
+
+    pub fn with_tags(mut self, tags: &[&str]) -> Self {
+        self.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        self
+    }
+
+    pub fn has_image(&self) -> bool {
+        self.image_filename.is_some()
+    }
+}
+
+/// The fields to change on an existing profile; anything left `None` keeps
+/// its current value.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+pub struct ProfileUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bio: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileImage {
+    pub bytes: Vec<u8>,
+    pub extension: String
+}
+
+impl ProfileImage {
+    /// `extension` may be given with or without its dot.
+    pub fn new(bytes: Vec<u8>, extension: &str) -> Result<Self, ProfileError> {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        if !IMAGE_TYPES.iter().any(|(known, _)| *known == extension) {
+            return Err(ProfileError::UnsupportedImage(extension));
+        }
+
+        Ok(ProfileImage { bytes, extension })
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .ok_or_else(|| format!("{} has no extension", path.display()))?;
+
+        Ok(Self::new(std::fs::read(path)?, extension)?)
+    }
+
+    pub(crate) fn from_content_type(bytes: Vec<u8>, content_type: &str) -> Result<Self, ProfileError> {
+        let content_type = content_type.split(';').next().unwrap_or_default().trim();
+        match IMAGE_TYPES.iter().find(|(_, known)| *known == content_type) {
+            Some((extension, _)) => Self::new(bytes, extension),
+            None => Err(ProfileError::UnsupportedImage(content_type.to_string()))
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        IMAGE_TYPES
+            .iter()
+            .find(|(known, _)| *known == self.extension)
+            .map(|(_, content_type)| *content_type)
+            .unwrap_or("application/octet-stream")
+    }
+
+    pub fn file_name(&self, uuid: &str) -> String {
+        format!("{}.{}", uuid, self.extension)
+    }
+
+    /// The `imageData` and `imageExtension` components the MAGIC spells take.
+    pub(crate) fn components(&self) -> (String, String) {
+        (STANDARD.encode(&self.bytes), format!(".{}", self.extension))
+    }
+}
+
+/// How prof wraps a profile in its responses.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProfResponse {
+    #[serde(default)]
+    pub success: bool,
+    pub profile: Option<Profile>,
+    pub error: Option<String>
+}
+
+impl ProfResponse {
+    pub fn into_profile(self) -> Result<Profile, ProfileError> {
+        match self.profile {
+            Some(profile) if self.success => Ok(profile),
+            _ => Err(ProfileError::Rejected(self.error.unwrap_or("no profile returned".to_string())))
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProfileError {
+    UnsupportedImage(String),
+    Rejected(String)
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::UnsupportedImage(kind) => write!(f, "prof does not store {} images", kind),
+            ProfileError::Rejected(error) => write!(f, "prof rejected the profile: {}", error)
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SuccessResult {
+    pub success: bool
+}
@@ -0,0 +1,118 @@
+use crate::{ProfResponse, Prof, Profile, ProfileError, ProfileImage, ProfileUpdate};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use fount_rs::Fount;
+use fount_rs::spellbook::SpellbookRegistry;
+use serde_json::json;
+use sessionless::Sessionless;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::direct_ports("localhost")).expect("service directory")
+}
+
+// config/service-spellbook.json prices the spells these tests cast.
+fn spellbooks() -> SpellbookRegistry {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../../../config/service-spellbook.json");
+    SpellbookRegistry::from_file(path).expect("service spellbook")
+}
+
+// A 1x1 red pixel, the same image prof's JS tests upload.
+const RED_PIXEL: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8z8DwHwAFBQIAX8jx0gAAAABJRU5ErkJggg==";
+
+fn red_pixel() -> ProfileImage {
+    ProfileImage::new(STANDARD.decode(RED_PIXEL).expect("png"), ".png").expect("image")
+}
+
+#[actix_rt::test]
+async fn test_prof() {
+    let prof = Prof::with_directory(&directory(), None);
+    let uuid = Sessionless::generate_uuid().to_string();
+
+    let profile = Profile::new(&uuid, "Test User", "test@example.com")
+        .with_bio("This is a test profile")
+        .with_tags(&["developer", "tester"]);
+    let created = prof.create_profile(&profile, Some(&red_pixel())).await.expect("create profile");
+    assert_eq!(created.name.as_deref(), Some("Test User"));
+    assert!(created.tags.contains(&"developer".to_string()));
+    assert!(created.has_image());
+
+    let fetched = prof.get_profile(&uuid).await.expect("get profile");
+    assert_eq!(fetched.email.as_deref(), Some("test@example.com"));
+
+    let image = prof.get_profile_image(&uuid).await.expect("get image");
+    assert_eq!(image, red_pixel());
+
+    let update = ProfileUpdate { bio: Some("Updated bio information".to_string()), ..Default::default() };
+    let updated = prof.update_profile(&uuid, &update, None).await.expect("update profile");
+    assert_eq!(updated.bio.as_deref(), Some("Updated bio information"));
+    assert_eq!(updated.name.as_deref(), Some("Test User"));
+
+    assert!(prof.delete_profile(&uuid).await.expect("delete profile").success);
+    assert!(prof.get_profile(&uuid).await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_prof_magic() {
+    let fount = Fount::with_directory(&directory(), None).with_spellbooks(spellbooks());
+    let prof = Prof::with_directory(&directory(), None);
+    let caster = fount.create_user().await.expect("fount user");
+
+    let profile = Profile::new(&caster.uuid, "Test User", "test@example.com").with_tags(&["developer", "tester"]);
+    let created = prof.magic_create_profile(&fount, &caster.uuid, 0, &profile, None).await.expect("profUserProfile");
+    assert_eq!(created.email.as_deref(), Some("test@example.com"));
+
+    let update = ProfileUpdate {
+        name: Some("Updated Test User".to_string()),
+        tags: Some(vec!["developer".to_string(), "tester".to_string(), "magician".to_string()]),
+        ..Default::default()
+    };
+    let updated = prof.magic_update_profile(&fount, &caster.uuid, 1, &caster.uuid, &update, Some(&red_pixel())).await.expect("profUserProfileUpdate");
+    assert_eq!(updated.name.as_deref(), Some("Updated Test User"));
+    assert!(updated.has_image());
+
+    let missing = Sessionless::generate_uuid().to_string();
+    assert!(prof.magic_update_profile(&fount, &caster.uuid, 2, &missing, &update, None).await.is_err());
+
+    assert!(prof.magic_delete_profile(&fount, &caster.uuid, 3, &caster.uuid).await.expect("profUserProfileDelete").success);
+}
+
+#[test]
+fn test_profile_image() {
+    let image = red_pixel();
+    assert_eq!(image.extension, "png");
+    assert_eq!(image.content_type(), "image/png");
+    assert_eq!(image.file_name("abc"), "abc.png");
+    assert_eq!(image.components(), (RED_PIXEL.to_string(), ".png".to_string()));
+
+    assert_eq!(ProfileImage::new(vec![], "JPG").expect("jpg").content_type(), "image/jpeg");
+    assert_eq!(ProfileImage::new(vec![], ".tiff"), Err(ProfileError::UnsupportedImage("tiff".to_string())));
+    assert_eq!(ProfileImage::from_content_type(vec![1], "image/webp; charset=binary").expect("webp").extension, "webp");
+    assert!(ProfileImage::from_content_type(vec![1], "text/html").is_err());
+}
+
+#[test]
+fn test_profile_shapes() {
+    let profile = Profile::new("abc", "Test User", "test@example.com");
+    assert_eq!(serde_json::to_value(&profile).expect("json"), json!({ "uuid": "abc", "name": "Test User", "email": "test@example.com" }));
+
+    let update = ProfileUpdate { bio: Some("new".to_string()), ..Default::default() };
+    assert_eq!(serde_json::to_value(&update).expect("json"), json!({ "bio": "new" }));
+
+    let response: ProfResponse = serde_json::from_value(json!({
+        "success": true,
+        "profile": { "uuid": "abc", "name": "Test User", "imageFilename": "abc.png", "createdAt": "2024-01-01", "links": { "site": "x" } }
+    })).expect("response");
+    let profile = response.into_profile().expect("profile");
+    assert!(profile.has_image());
+    assert!(profile.tags.is_empty());
+    assert_eq!(profile.extra["links"], json!({ "site": "x" }));
+
+    let rejected: ProfResponse = serde_json::from_value(json!({ "success": false, "error": "Profile not found" })).expect("rejected");
+    assert_eq!(rejected.into_profile(), Err(ProfileError::Rejected("Profile not found".to_string())));
+
+    let refused = serde_json::from_value(json!({ "success": false, "error": "insufficient mp" })).expect("spell result");
+    let error = Prof::spell_profile(refused).expect_err("refused spell");
+    assert_eq!(error.downcast_ref::<ProfileError>(), Some(&ProfileError::Rejected("insufficient mp".to_string())));
+}
@@ -1,15 +1,18 @@
-use crate::{AddieUser, Order, ProductMeta, Sanora, SanoraUser, SuccessResult};
-use sessionless::hex::IntoHex;
+use crate::{Order, Orders, ProductMeta, Sanora, SanoraUser, SuccessResult};
 use std::collections::HashMap;
-use serde_json::json;
-use serde_json::Value;
-use rand::Rng;
+use std::path::Path;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::path_based("http://nginx:80")).expect("service directory")
+}
 
 #[actix_rt::test]
 async fn test_sanora() {
 
     let mut saved_user: Option<SanoraUser>;
-    let sanora = Sanora::new(Some("http://nginx:80/sanora/".to_string()), None);
+    let sanora = Sanora::with_directory(&directory(), None);
 
     async fn create_user(sanora: &Sanora) -> Option<SanoraUser> {
 	let result = sanora.create_user().await;
@@ -94,6 +97,25 @@ async fn test_sanora() {
         }
     }
 
+    async fn put_artifact(sanora: &Sanora, saved_user: &SanoraUser) -> Option<SuccessResult> {
+        let title = "My rust title".to_string();
+        let artifact = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../mocha/book.epub");
+
+        let result = sanora.put_artifact_from_path(&saved_user.uuid, &title, &artifact).await;
+
+        match result {
+            Ok(success) => {
+                assert!(success.success);
+                Some(success)
+            }
+            Err(error) => {
+                eprintln!("Error occured putting artifact: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
     async fn add_order(sanora: &Sanora, saved_user: &SanoraUser) -> Option<SanoraUser> {
         let productId = "foo-bar-baz".to_string();
         let address1 = "123 A Ave".to_string();
@@ -102,12 +124,13 @@ async fn test_sanora() {
         let zipCode = "12345".to_string();
 
         let order = Order {
-          productId,
+          product_id: productId,
           address1: Some(address1),
           address2: None,
           city: Some(city),
           state: Some(state),
-          zipCode: Some(zipCode)
+          zip_code: Some(zipCode),
+          extra: HashMap::new()
         };
   
         let result = sanora.add_order(&saved_user.uuid, &order).await;
@@ -131,7 +154,7 @@ async fn test_sanora() {
     async fn get_orders(sanora: &Sanora, saved_user: &SanoraUser) -> Option<Orders> {
         let productId = "foo-bar-baz".to_string();
 
-        let result = sanora.get_orders_for_product_id(&saved_user.uuid, &product_id);
+        let result = sanora.get_orders_for_product_id(&saved_user.uuid, &productId).await;
         
         match result {
             Ok(meta) => {
@@ -220,10 +243,18 @@ async fn test_sanora() {
 
     if let Some(ref user) = saved_user {
         Some(add_product(&sanora, user).await.expect("add product"));
+        put_artifact(&sanora, user).await.expect("put artifact");
     } else {
         panic!("Failed to add product");
     }
 
+    if let Some(ref user) = saved_user {
+        add_order(&sanora, user).await.expect("add order");
+        get_orders(&sanora, user).await.expect("get orders");
+    } else {
+        panic!("Failed to add order");
+    }
+
 
 /*    if let Some(ref user) = saved_user {
 	Some(add_processor_account(&sanora, user).await.expect("add processor"));
@@ -252,3 +283,23 @@ async fn test_sanora() {
 	panic!("Failed to delete user");
     } */    
 }
+
+#[test]
+fn test_order_serialization() {
+    let order = Order {
+        product_id: "foo-bar-baz".to_string(),
+        city: Some("Portland".to_string()),
+        ..Order::default()
+    };
+
+    let json = serde_json::to_value(&order).unwrap();
+    assert_eq!(json, serde_json::json!({ "productId": "foo-bar-baz", "city": "Portland" }));
+
+    let orders: Orders = serde_json::from_value(serde_json::json!({
+        "orders": [{ "productId": "foo", "zipCode": "12345", "baz": "bop" }]
+    })).unwrap();
+    assert_eq!(orders.orders[0].product_id, "foo");
+    assert_eq!(orders.orders[0].zip_code.as_deref(), Some("12345"));
+    assert_eq!(orders.orders[0].extra.get("baz"), Some(&serde_json::json!("bop")));
+    assert_eq!(serde_json::to_value(&orders.orders[0]).unwrap()["baz"], serde_json::json!("bop"));
+}
@@ -0,0 +1,244 @@
+pub mod structs;
+
+#[cfg(test)]
+mod tests;
+
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, Response};
+use serde_json::json;
+use sessionless::hex::IntoHex;
+use sessionless::Sessionless;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::option::Option;
+use std::path::Path;
+use std::sync::Mutex;
+pub use addie_rs::{AddieUser, PaymentIntent};
+pub use crate::structs::{Order, Orders, ProductMeta, SanoraUser, SuccessResult};
+pub use service_directory_rs::{Service, ServiceDirectory};
+
+pub struct Sanora {
+    base_url: String,
+    client: Client,
+    pub sessionless: Sessionless,
+    session_cookie: Mutex<Option<String>>,
+}
+
+impl Sanora {
+    pub fn new(base_url: Option<String>, sessionless: Option<Sessionless>) -> Self {
+        Sanora {
+            base_url: base_url.unwrap_or("https://dev.sanora.allyabase.com/".to_string()),
+            client: Client::new(),
+            sessionless: sessionless.unwrap_or(Sessionless::new()),
+            session_cookie: Mutex::new(None),
+        }
+    }
+
+    /// Points the client at sanora in `directory`.
+    pub fn with_directory(directory: &ServiceDirectory, sessionless: Option<Sessionless>) -> Self {
+        Self::new(Some(directory.url(Service::Sanora)), sessionless)
+    }
+
+    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.client.get(url).send().await
+    }
+
+    async fn put(&self, url: &str, payload: serde_json::Value) -> Result<Response, reqwest::Error> {
+        self.client
+            .put(url)
+            .json(&payload)
+            .send()
+            .await
+    }
+
+    async fn put_file(&self, url: &str, field: &str, file_name: &str, bytes: Vec<u8>, timestamp: &str, signature: &str) -> Result<Response, reqwest::Error> {
+        let form = Form::new().part(field.to_string(), Part::bytes(bytes).file_name(file_name.to_string()));
+        self.client
+            .put(url)
+            .header("x-pn-timestamp", timestamp)
+            .header("x-pn-signature", signature)
+            .multipart(form)
+            .send()
+            .await
+    }
+
+    fn get_timestamp() -> String {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis()
+            .to_string()
+    }
+
+    pub async fn create_user(&self) -> Result<SanoraUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let pub_key = self.sessionless.public_key().to_hex();
+        let signature = self.sessionless.sign(format!("{}{}", timestamp, pub_key)).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "pubKey": pub_key,
+            "signature": signature
+        });
+
+        let url = format!("{}user/create", self.base_url);
+        let res = self.put(&url, payload).await?;
+        let user: SanoraUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    pub async fn get_user_by_uuid(&self, uuid: &str) -> Result<SanoraUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let url = format!("{}user/{}?timestamp={}&signature={}", self.base_url, uuid, timestamp, signature);
+        let res = self.get(&url).await?;
+        let user: SanoraUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    pub async fn add_product(&self, uuid: &str, title: &str, description: &str, price: &u32) -> Result<ProductMeta, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}{}{}", timestamp, uuid, title, description, price);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "description": description,
+            "price": price,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}/product/{}", self.base_url, uuid, urlencoding::encode(title));
+        let res = self.put(&url, payload).await?;
+        let meta: ProductMeta = res.json().await?;
+
+        Ok(meta)
+    }
+
+    pub async fn get_product(&self, uuid: &str, title: &str) -> Result<ProductMeta, Box<dyn std::error::Error>> {
+        let url = format!("{}products/{}/{}", self.base_url, uuid, urlencoding::encode(title));
+        let res = self.get(&url).await?;
+        let meta: ProductMeta = res.json().await?;
+
+        Ok(meta)
+    }
+
+    pub async fn get_products(&self, uuid: &str) -> Result<Vec<ProductMeta>, Box<dyn std::error::Error>> {
+        let url = format!("{}products/{}", self.base_url, uuid);
+        let res = self.get(&url).await?;
+        let products: Vec<ProductMeta> = res.json().await?;
+
+        Ok(products)
+    }
+
+    /// Uploads the file buyers receive for `title`, e.g. an epub.
+    pub async fn put_artifact(&self, uuid: &str, title: &str, file_name: &str, artifact: Vec<u8>) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        self.put_product_file(uuid, title, "artifact", file_name, artifact).await
+    }
+
+    pub async fn put_artifact_from_path(&self, uuid: &str, title: &str, path: impl AsRef<Path>) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let (file_name, bytes) = read_file(path.as_ref())?;
+        self.put_artifact(uuid, title, &file_name, bytes).await
+    }
+
+    pub async fn put_image(&self, uuid: &str, title: &str, file_name: &str, image: Vec<u8>) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        self.put_product_file(uuid, title, "image", file_name, image).await
+    }
+
+    pub async fn put_image_from_path(&self, uuid: &str, title: &str, path: impl AsRef<Path>) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let (file_name, bytes) = read_file(path.as_ref())?;
+        self.put_image(uuid, title, &file_name, bytes).await
+    }
+
+    async fn put_product_file(&self, uuid: &str, title: &str, field: &str, file_name: &str, bytes: Vec<u8>) -> Result<SuccessResult, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}{}", timestamp, uuid, title);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let url = format!("{}user/{}/product/{}/{}", self.base_url, uuid, urlencoding::encode(title), field);
+        let res = self.put_file(&url, field, file_name, bytes, &timestamp, &signature).await?;
+        let success: SuccessResult = res.json().await?;
+
+        Ok(success)
+    }
+
+    pub async fn add_order(&self, uuid: &str, order: &Order) -> Result<SanoraUser, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let payload = json!({
+            "timestamp": timestamp,
+            "order": order,
+            "signature": signature
+        });
+
+        let url = format!("{}user/{}/orders", self.base_url, uuid);
+        let res = self.put(&url, payload).await?;
+        let user: SanoraUser = res.json().await?;
+
+        Ok(user)
+    }
+
+    pub async fn get_orders_for_product_id(&self, uuid: &str, product_id: &str) -> Result<Orders, Box<dyn std::error::Error>> {
+        let timestamp = Self::get_timestamp();
+        let message = format!("{}{}", timestamp, uuid);
+        let signature = self.sessionless.sign(&message).to_hex();
+
+        let url = format!("{}user/{}/orders/{}?timestamp={}&signature={}", self.base_url, uuid, urlencoding::encode(product_id), timestamp, signature);
+        let res = self.get(&url).await?;
+        let orders: Orders = res.json().await?;
+
+        Ok(orders)
+    }
+
+    /// Fetches a product page rendered with `template` (`generic`,
+    /// `generic-address-stripe`, `blog`, ...). Sanora starts a buyer session
+    /// when serving these pages, which `get_payment_intent` then uses.
+    pub async fn get_product_page(&self, uuid: &str, title: &str, template: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let url = format!("{}products/{}/{}/{}", self.base_url, uuid, urlencoding::encode(title), template);
+        let res = self.get(&url).await?;
+        if let Some(cookie) = res.headers().get(reqwest::header::SET_COOKIE) {
+            let cookie = cookie.to_str()?.split(';').next().unwrap_or_default().to_string();
+            *self.session_cookie.lock().unwrap() = Some(cookie);
+        }
+
+        Ok(res.text().await?)
+    }
+
+    pub async fn get_payment_intent(&self, processor: &str, amount: &u32, currency: &str) -> Result<PaymentIntent, Box<dyn std::error::Error>> {
+        let Some(cookie) = self.session_cookie.lock().unwrap().clone() else {
+            return Err("no buyer session, fetch a product page first".into());
+        };
+
+        let payload = json!({
+            "timestamp": Self::get_timestamp(),
+            "amount": amount,
+            "currency": currency
+        });
+
+        let url = format!("{}processor/{}/intent", self.base_url, processor);
+        let res = self.client
+            .put(&url)
+            .header(reqwest::header::COOKIE, cookie)
+            .json(&payload)
+            .send()
+            .await?;
+        let intent: PaymentIntent = res.json().await?;
+
+        Ok(intent)
+    }
+}
+
+fn read_file(path: &Path) -> Result<(String, Vec<u8>), Box<dyn std::error::Error>> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("{} has no file name", path.display()))?
+        .to_string();
+
+    Ok((file_name, std::fs::read(path)?))
+}
@@ -0,0 +1,305 @@
+use crate::{Order, Orders, ProductMeta, Sanora, SanoraUser, SuccessResult};
+use std::collections::HashMap;
+use std::path::Path;
+use crate::ServiceDirectory;
+
+// NODE_ENV=local, SUB_DOMAIN, ALLYABASE_BASE_URL or SHARON_CONFIG point these at another node.
+fn directory() -> ServiceDirectory {
+    ServiceDirectory::from_env_or(ServiceDirectory::direct_ports("localhost")).expect("service directory")
+}
+
+#[actix_rt::test]
+async fn test_sanora() {
+
+    let mut saved_user: Option<SanoraUser>;
+    let sanora = Sanora::with_directory(&directory(), None);
+
+    async fn create_user(sanora: &Sanora) -> Option<SanoraUser> {
+	let result = sanora.create_user().await;
+
+	match result {
+	    Ok(user) => {
+		println!("Successfully got SanoraUser: {}", user.uuid);
+		assert_eq!(
+		    user.uuid.len(),
+		    36
+		);
+                Some(user)
+	    },
+	    Err(error) => {
+		eprintln!("Error occurred create_user: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	}
+    }
+
+    async fn get_user_by_uuid(sanora: &Sanora, saved_user: &SanoraUser) -> Option<SanoraUser> {
+	let result = sanora.get_user_by_uuid(&saved_user.uuid).await; 
+     
+	match result {
+	    Ok(user) => {
+		assert_eq!(
+		    user.uuid.len(),
+		    36
+		);
+                Some(user)
+	    }
+	    Err(error) => {
+		eprintln!("Error occurred get_user: {}", error);
+		println!("Error details: {:?}", error);
+                None
+	    }
+	} 
+    }
+
+/*    async fn add_processor_account(sanora: &Sanora, saved_user: &SanoraUser) -> Option<SanoraUser> {
+        let email_seed: u32 = rand::thread_rng().gen_range(0..100000);
+        let email: String = format!("zach+{}@planetnine.app", &email_seed);
+        let result = sanora.add_processor_account(&saved_user.uuid, "Foo", &email).await;
+
+        match result {
+            Ok(user) => {
+                assert_eq!(
+                    user.uuid.len(),
+                    36
+                );
+                Some(user)
+            }
+            Err(error) => {
+                eprintln!("Error occurred get_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        } 
+    }*/
+
+    async fn add_product(sanora: &Sanora, saved_user: &SanoraUser) -> Option<ProductMeta> {
+        let title = "My rust title".to_string();
+        let description = "Here is a description of my sweet product".to_string();
+        let price = 2000;
+  
+        let result = sanora.add_product(&saved_user.uuid, &title, &description, &price).await;
+
+        match result {
+            Ok(meta) => {
+               assert_eq!(
+                    meta.uuid.len(),
+                    36
+                );
+                Some(meta)
+            }
+            Err(error) => {
+                eprintln!("Error occured adding product: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn put_artifact(sanora: &Sanora, saved_user: &SanoraUser) -> Option<SuccessResult> {
+        let title = "My rust title".to_string();
+        let artifact = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../mocha/book.epub");
+
+        let result = sanora.put_artifact_from_path(&saved_user.uuid, &title, &artifact).await;
+
+        match result {
+            Ok(success) => {
+                assert!(success.success);
+                Some(success)
+            }
+            Err(error) => {
+                eprintln!("Error occured putting artifact: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn add_order(sanora: &Sanora, saved_user: &SanoraUser) -> Option<SanoraUser> {
+        let productId = "foo-bar-baz".to_string();
+        let address1 = "123 A Ave".to_string();
+        let city = "Portland".to_string();
+        let state = "OR".to_string();
+        let zipCode = "12345".to_string();
+
+        let order = Order {
+          product_id: productId,
+          address1: Some(address1),
+          address2: None,
+          city: Some(city),
+          state: Some(state),
+          zip_code: Some(zipCode),
+          extra: HashMap::new()
+        };
+  
+        let result = sanora.add_order(&saved_user.uuid, &order).await;
+
+        match result {
+            Ok(meta) => {
+               assert_eq!(
+                    meta.uuid.len(),
+                    36
+                );
+                Some(meta)
+            }
+            Err(error) => {
+                eprintln!("Error occured adding product: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn get_orders(sanora: &Sanora, saved_user: &SanoraUser) -> Option<Orders> {
+        let productId = "foo-bar-baz".to_string();
+
+        let result = sanora.get_orders_for_product_id(&saved_user.uuid, &productId).await;
+        
+        match result {
+            Ok(meta) => {
+               assert_eq!(
+                    meta.orders.len(),
+                    1
+                );
+                Some(meta)
+            }
+            Err(error) => {
+                eprintln!("Error occured getting products: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+
+/*
+
+    async fn get_payment_intent(sanora: &Sanora, saved_user: &SanoraUser) -> Option<PaymentIntent> {
+        let payees: Vec<String> = Vec::new();
+        let result = sanora.get_payment_intent(&saved_user.uuid, "stripe", &2000, "USD", &payees).await;
+
+        match result {
+            Ok(intent) => {
+                assert_eq!(
+                    intent.customer.len(),
+                    18
+                );
+                Some(intent)
+            }
+            Err(error) => {
+                eprintln!("Error occurred get_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn get_payment_intent_without_splits(sanora: &Sanora, saved_user: &SanoraUser) -> Option<PaymentIntent> {
+        let result = sanora.get_payment_intent_without_splits(&saved_user.uuid, "stripe", &2000, "USD").await;
+
+        match result {
+            Ok(intent) => {
+                assert_eq!(
+                    intent.customer.len(),
+                    18
+                );
+                Some(intent)
+            }
+            Err(error) => {
+                eprintln!("Error occurred get_user: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }
+
+    async fn delete_user(sanora: &Sanora, saved_user: &SanoraUser) -> Option<SuccessResult> {
+        let result = sanora.delete_user(&saved_user.uuid).await;
+
+        match result {
+            Ok(success) => {
+                assert_eq!(
+                    success.success,
+                    true
+                );
+                Some(success)
+            }
+            Err(error) => {
+                eprintln!("Error occurred delete: {}", error);
+                println!("Error details: {:?}", error);
+                None
+            }
+        }
+    }*/
+
+    saved_user = Some(create_user(&sanora).await.expect("user"));
+
+    if let Some(ref user) = saved_user {
+        saved_user = Some(get_user_by_uuid(&sanora, user).await.expect("get user 1"));
+    } else {
+        panic!("Failed to get user");
+    }
+
+    if let Some(ref user) = saved_user {
+        Some(add_product(&sanora, user).await.expect("add product"));
+        put_artifact(&sanora, user).await.expect("put artifact");
+    } else {
+        panic!("Failed to add product");
+    }
+
+    if let Some(ref user) = saved_user {
+        add_order(&sanora, user).await.expect("add order");
+        get_orders(&sanora, user).await.expect("get orders");
+    } else {
+        panic!("Failed to add order");
+    }
+
+
+/*    if let Some(ref user) = saved_user {
+	Some(add_processor_account(&sanora, user).await.expect("add processor"));
+        saved_user = Some(get_user_by_uuid(&sanora, user).await.expect("adding processor account"));
+    } else {
+	panic!("Failed to add processor account");
+    }
+
+    if let Some(ref user) = saved_user {
+        Some(get_payment_intent(&sanora, user).await.expect("get payment intent"));
+        saved_user = Some(get_user_by_uuid(&sanora, user).await.expect("getting payment intent"));
+    } else {
+        panic!("Failed to get payment intent");
+    }
+
+    if let Some(ref user) = saved_user {
+        Some(get_payment_intent_without_splits(&sanora, user).await.expect("get payment intent without splits"));
+        saved_user = Some(get_user_by_uuid(&sanora, user).await.expect("getting payment intent"));
+    } else {
+        panic!("Failed to get payment intent");
+    }
+    
+    if let Some(ref user) = saved_user {
+	delete_user(&sanora, &user).await;
+    } else {
+	panic!("Failed to delete user");
+    } */    
+}
+
+#[test]
+fn test_order_serialization() {
+    let order = Order {
+        product_id: "foo-bar-baz".to_string(),
+        city: Some("Portland".to_string()),
+        ..Order::default()
+    };
+
+    let json = serde_json::to_value(&order).unwrap();
+    assert_eq!(json, serde_json::json!({ "productId": "foo-bar-baz", "city": "Portland" }));
+
+    let orders: Orders = serde_json::from_value(serde_json::json!({
+        "orders": [{ "productId": "foo", "zipCode": "12345", "baz": "bop" }]
+    })).unwrap();
+    assert_eq!(orders.orders[0].product_id, "foo");
+    assert_eq!(orders.orders[0].zip_code.as_deref(), Some("12345"));
+    assert_eq!(orders.orders[0].extra.get("baz"), Some(&serde_json::json!("bop")));
+    assert_eq!(serde_json::to_value(&orders.orders[0]).unwrap()["baz"], serde_json::json!("bop"));
+}